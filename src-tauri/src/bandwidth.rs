@@ -0,0 +1,146 @@
+//! Bandwidth Accounting Module
+//!
+//! Counts the bytes every engine receives into the `bandwidth_usage` table
+//! (one row per local day) and enforces the optional `monthly_cap_bytes` setting.
+//!
+//! Engines report through a [`UsageRecorder`]; totals are buffered in the
+//! [`BandwidthMeter`] and written to SQLite every few seconds.
+
+use crate::db;
+use chrono::Local;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager, Runtime};
+
+/// How often buffered usage is written to the database.
+const FLUSH_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Usage received but not yet written to the database.
+#[derive(Clone, Default)]
+pub struct BandwidthMeter {
+    pending: Arc<AtomicU64>,
+}
+
+impl BandwidthMeter {
+    /// Returns a recorder for one transfer that reports cumulative byte counts.
+    pub fn recorder(&self) -> UsageRecorder {
+        UsageRecorder {
+            pending: self.pending.clone(),
+            last_seen: AtomicU64::new(u64::MAX),
+        }
+    }
+
+    fn pending_bytes(&self) -> u64 {
+        self.pending.load(Ordering::Relaxed)
+    }
+
+    fn take_pending(&self) -> u64 {
+        self.pending.swap(0, Ordering::Relaxed)
+    }
+}
+
+/// Turns a transfer's cumulative byte count into usage increments.
+///
+/// The first observation is only a baseline, so bytes restored from disk on
+/// resume are not counted again. A count that goes backwards (restart from
+/// zero) becomes the new baseline.
+pub struct UsageRecorder {
+    pending: Arc<AtomicU64>,
+    last_seen: AtomicU64,
+}
+
+impl UsageRecorder {
+    pub fn observe(&self, total: u64) {
+        let previous = self.last_seen.swap(total, Ordering::Relaxed);
+        if previous != u64::MAX && total > previous {
+            self.pending.fetch_add(total - previous, Ordering::Relaxed);
+        }
+    }
+}
+
+fn today() -> String {
+    Local::now().format("%Y-%m-%d").to_string()
+}
+
+/// Starts a background loop that writes buffered usage every [`FLUSH_INTERVAL`].
+pub fn start_bandwidth_flusher<R: Runtime>(app: AppHandle<R>) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(FLUSH_INTERVAL).await;
+
+            let meter = app.state::<BandwidthMeter>();
+            let bytes = meter.take_pending();
+            if bytes == 0 {
+                continue;
+            }
+            let db_state = app.state::<db::DbState>();
+            if let Err(e) = db::add_bandwidth_usage(&db_state.path, &today(), bytes) {
+                tracing::error!("[Bandwidth] Failed to record usage: {}", e);
+                meter.pending.fetch_add(bytes, Ordering::Relaxed);
+            }
+        }
+    });
+}
+
+/// Returns true if `monthly_cap_bytes` is set and this month's usage has reached it.
+///
+/// Emits `cap-reached` with the current usage so the UI can explain why nothing started.
+pub fn cap_reached<R: Runtime>(app: &AppHandle<R>, db_path: &str) -> bool {
+    let cap = db::get_setting(db_path, "monthly_cap_bytes")
+        .ok()
+        .flatten()
+        .and_then(|v| v.parse::<u64>().ok())
+        .unwrap_or(0);
+    if cap == 0 {
+        return false;
+    }
+
+    let month = Local::now().format("%Y-%m").to_string();
+    let used = db::get_monthly_bandwidth_usage(db_path, &month)
+        .unwrap_or(0)
+        .max(0) as u64
+        + app.state::<BandwidthMeter>().pending_bytes();
+    if used < cap {
+        return false;
+    }
+
+    tracing::info!(
+        "[Bandwidth] Monthly cap reached ({} of {} bytes); not starting downloads",
+        used,
+        cap
+    );
+    let _ = app.emit(
+        "cap-reached",
+        serde_json::json!({ "used": used, "cap": cap }),
+    );
+    true
+}
+
+/// One day of usage returned by [`get_bandwidth_stats`].
+#[derive(serde::Serialize)]
+pub struct DailyUsage {
+    pub date: String,
+    pub bytes: u64,
+}
+
+/// Bridge: Returns daily usage totals between `from` and `to` (inclusive, `YYYY-MM-DD`).
+#[tauri::command]
+pub fn get_bandwidth_stats(
+    db_state: tauri::State<db::DbState>,
+    from: String,
+    to: String,
+) -> Result<Vec<DailyUsage>, String> {
+    for date in [&from, &to] {
+        chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d")
+            .map_err(|_| format!("Invalid date: {}", date))?;
+    }
+    let rows = db::get_bandwidth_usage(&db_state.path, &from, &to).map_err(|e| e.to_string())?;
+    Ok(rows
+        .into_iter()
+        .map(|(date, bytes)| DailyUsage {
+            date,
+            bytes: bytes.max(0) as u64,
+        })
+        .collect())
+}
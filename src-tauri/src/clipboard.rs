@@ -5,24 +5,181 @@
 
 use crate::db;
 use arboard::Clipboard;
-use std::time::Duration;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
 use tauri::{AppHandle, Emitter, Manager, Runtime};
 
+/// How long clipboard text must stay unchanged before it is reported.
+const AUTOCATCH_DEBOUNCE: Duration = Duration::from_secs(1);
+
+/// Hosts whose links are media pages rather than direct files.
+const VIDEO_HOSTS: &[&str] = &[
+    "youtube.com",
+    "youtu.be",
+    "vimeo.com",
+    "dailymotion.com",
+    "twitch.tv",
+    "soundcloud.com",
+];
+
+/// What kind of link was caught, so the frontend can pick the right dialog.
+#[derive(Debug, Clone, Copy, Serialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum UrlKind {
+    Http,
+    Magnet,
+    /// A link to a `.torrent` file.
+    Torrent,
+    Video,
+    Unknown,
+}
+
+/// Payload of the `autocatch-url` event.
+#[derive(Debug, Clone, Serialize)]
+pub struct CaughtUrl {
+    pub url: String,
+    pub kind: UrlKind,
+}
+
+/// Comma-separated domain lists from `autocatch_allowlist` / `autocatch_blocklist`.
+#[derive(Default)]
+struct DomainFilter {
+    allow: Vec<String>,
+    block: Vec<String>,
+}
+
+impl DomainFilter {
+    fn from_settings(settings: &HashMap<String, String>) -> Self {
+        let list = |key: &str| {
+            settings
+                .get(key)
+                .map(|v| {
+                    v.split(',')
+                        .map(|d| d.trim().trim_start_matches('.').to_lowercase())
+                        .filter(|d| !d.is_empty())
+                        .collect()
+                })
+                .unwrap_or_default()
+        };
+        Self {
+            allow: list("autocatch_allowlist"),
+            block: list("autocatch_blocklist"),
+        }
+    }
+
+    /// Blocked domains always lose; a non-empty allowlist admits only its domains.
+    /// Links without a host (magnets) are never filtered.
+    fn permits(&self, url: &str) -> bool {
+        let Some(host) = url_host(url) else {
+            return true;
+        };
+        if self.block.iter().any(|d| host_matches(&host, d)) {
+            return false;
+        }
+        self.allow.is_empty() || self.allow.iter().any(|d| host_matches(&host, d))
+    }
+}
+
+/// One entry of the `autocatch_rules` setting.
+#[derive(Debug, Deserialize)]
+struct CaptureRule {
+    pattern: String,
+    /// `true` catches matching text, `false` ignores it.
+    include: bool,
+}
+
+/// Compiled `autocatch_rules`: a JSON list like `[{"pattern": "\\.iso$", "include": true}]`.
+#[derive(Default)]
+struct CaptureRules {
+    source: String,
+    include: Vec<Regex>,
+    exclude: Vec<Regex>,
+}
+
+impl CaptureRules {
+    /// Compiles the rules; invalid JSON or patterns are logged and skipped.
+    fn compile(source: &str) -> Self {
+        let mut compiled = Self {
+            source: source.to_string(),
+            ..Default::default()
+        };
+        if source.trim().is_empty() {
+            return compiled;
+        }
+        let rules: Vec<CaptureRule> = match serde_json::from_str(source) {
+            Ok(rules) => rules,
+            Err(e) => {
+                tracing::error!("[Clipboard] Ignoring invalid autocatch_rules: {}", e);
+                return compiled;
+            }
+        };
+        for rule in rules {
+            match Regex::new(&rule.pattern) {
+                Ok(re) if rule.include => compiled.include.push(re),
+                Ok(re) => compiled.exclude.push(re),
+                Err(e) => {
+                    tracing::warn!("[Clipboard] Skipping rule {:?}: {}", rule.pattern, e)
+                }
+            }
+        }
+        compiled
+    }
+
+    /// `Some(true)` if an include rule matches, `Some(false)` if the text is
+    /// excluded or include rules exist but none match, `None` without a verdict.
+    fn verdict(&self, text: &str) -> Option<bool> {
+        if self.exclude.iter().any(|re| re.is_match(text)) {
+            return Some(false);
+        }
+        if self.include.is_empty() {
+            return None;
+        }
+        Some(self.include.iter().any(|re| re.is_match(text)))
+    }
+}
+
+/// True if `host` is `domain` or one of its subdomains.
+fn host_matches(host: &str, domain: &str) -> bool {
+    host == domain || host.ends_with(&format!(".{}", domain))
+}
+
+/// Lowercased host of a URL; scheme-less links like `example.com/file` are read as https.
+fn url_host(url: &str) -> Option<String> {
+    if url.to_lowercase().starts_with("magnet:") {
+        return None;
+    }
+    let parsed = url::Url::parse(url)
+        .ok()
+        .filter(|u| u.host_str().is_some())
+        .or_else(|| url::Url::parse(&format!("https://{}", url)).ok())?;
+    parsed.host_str().map(|h| h.to_lowercase())
+}
+
 /// Starts a background loop that polls the clipboard every second.
 ///
 /// It implements:
 /// - **Setting Polling**: Checks the `autocatch_enabled` setting every 5 seconds.
 /// - **Deduplication**: Only emits the `autocatch-url` event if the clipboard
 ///   content has changed since the last catch.
-/// - **URL Validation**: Heuristically determines if the clipboard contains a
-///   link relevant to Ciel.
+/// - **Debouncing**: Text must stay on the clipboard for [`AUTOCATCH_DEBOUNCE`],
+///   so a burst of copies only reports the last one.
+/// - **Capture Rules**: `autocatch_rules` regexes decide what is caught; the
+///   rules are recompiled only when the setting changes.
+/// - **URL Validation**: Without include rules, classifies the text with
+///   [`classify_url`] and skips unknown links. Domains rejected by the
+///   allow/block lists are always skipped.
 pub fn start_clipboard_monitor<R: Runtime>(app: AppHandle<R>) {
     tauri::async_runtime::spawn(async move {
         let mut clipboard = Clipboard::new().ok();
         let mut last_clipboard = String::new();
+        let mut pending: Option<(String, Instant)> = None;
 
         let mut last_settings_check = std::time::Instant::now() - Duration::from_secs(10);
         let mut cached_enabled = true;
+        let mut domain_filter = DomainFilter::default();
+        let mut capture_rules = CaptureRules::default();
 
         loop {
             tokio::time::sleep(Duration::from_secs(1)).await;
@@ -35,6 +192,11 @@ pub fn start_clipboard_monitor<R: Runtime>(app: AppHandle<R>) {
                     .get("autocatch_enabled")
                     .map(|v| v == "true")
                     .unwrap_or(true);
+                domain_filter = DomainFilter::from_settings(&settings);
+                let rules_source = settings.get("autocatch_rules").cloned().unwrap_or_default();
+                if rules_source != capture_rules.source {
+                    capture_rules = CaptureRules::compile(&rules_source);
+                }
                 last_settings_check = std::time::Instant::now();
             }
 
@@ -47,12 +209,28 @@ pub fn start_clipboard_monitor<R: Runtime>(app: AppHandle<R>) {
                 match cb.get_text() {
                     Ok(text) => {
                         let text = text.trim().to_string();
-                        if !text.is_empty() && text != last_clipboard {
-                            if is_valid_url(&text) {
-                                // Inform the frontend that a potential download was found.
-                                let _ = app.emit("autocatch-url", &text);
-                                last_clipboard = text;
+                        if text.is_empty() || text == last_clipboard {
+                            pending = None;
+                            continue;
+                        }
+                        let stable = matches!(&pending, Some((seen, since))
+                            if *seen == text && since.elapsed() >= AUTOCATCH_DEBOUNCE);
+                        if !stable {
+                            if pending.as_ref().map_or(true, |(seen, _)| *seen != text) {
+                                pending = Some((text, Instant::now()));
                             }
+                            continue;
+                        }
+                        pending = None;
+                        last_clipboard = text.clone();
+
+                        let kind = classify_url(&text);
+                        let wanted = capture_rules
+                            .verdict(&text)
+                            .unwrap_or(kind != UrlKind::Unknown);
+                        if wanted && domain_filter.permits(&text) {
+                            // Inform the frontend that a potential download was found.
+                            let _ = app.emit("autocatch-url", CaughtUrl { url: text, kind });
                         }
                     }
                     Err(_) => {
@@ -83,8 +261,35 @@ pub fn get_clipboard() -> Result<Option<String>, String> {
     }
 }
 
+/// Classifies a link the same way `validate_url_type` does, without touching the network.
+pub fn classify_url(url: &str) -> UrlKind {
+    if url.starts_with("magnet:") {
+        return UrlKind::Magnet;
+    }
+    if !is_valid_url(url) {
+        return UrlKind::Unknown;
+    }
+    let Some(host) = url_host(url) else {
+        return UrlKind::Unknown;
+    };
+    if is_torrent_link(url) {
+        return UrlKind::Torrent;
+    }
+    if VIDEO_HOSTS.iter().any(|d| host_matches(&host, d)) {
+        UrlKind::Video
+    } else {
+        UrlKind::Http
+    }
+}
+
+/// True if the link's path (query and fragment aside) names a `.torrent` file.
+pub(crate) fn is_torrent_link(url: &str) -> bool {
+    let path = url.split(['?', '#']).next().unwrap_or(url);
+    path.to_lowercase().ends_with(".torrent")
+}
+
 /// Heuristic: Determines if a string is a download-ready URL or Magnet link.
-fn is_valid_url(url: &str) -> bool {
+pub(crate) fn is_valid_url(url: &str) -> bool {
     let url_lower = url.to_lowercase();
 
     // Check for explicit protocols.
@@ -0,0 +1,94 @@
+use crate::db::{self, DbState, Download, DownloadProtocol, DownloadStatus, HistoryEvent};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use tauri::State;
+
+/// Bumped whenever the layout of [`Backup`] changes.
+const BACKUP_VERSION: u32 = 1;
+
+/// Settings that hold credentials and never leave the machine.
+const PRIVATE_SETTINGS: &[&str] = &["proxy_url"];
+
+/// Everything needed to rebuild the download list on another machine.
+#[derive(Serialize, Deserialize)]
+pub struct Backup {
+    pub version: u32,
+    pub exported_at: String,
+    pub downloads: Vec<Download>,
+    pub settings: HashMap<String, String>,
+    pub history: Vec<HistoryEvent>,
+}
+
+/// Bridge: Serializes all downloads, settings and history to JSON.
+///
+/// Chunk layouts are transient and left out, so unfinished HTTP/FTP downloads
+/// start over with a fresh range check. Session cookies and proxy credentials
+/// are not exported.
+#[tauri::command]
+pub async fn export_data(db_state: State<'_, DbState>) -> Result<String, String> {
+    let mut downloads = db::get_all_downloads(&db_state.path).map_err(|e| e.to_string())?;
+    for download in &mut downloads {
+        download.cookies = None;
+        download.proxy = None;
+    }
+    let mut settings = db::get_all_settings(&db_state.path).map_err(|e| e.to_string())?;
+    settings.retain(|key, _| !PRIVATE_SETTINGS.contains(&key.as_str()));
+    let history = db::get_all_history(&db_state.path).map_err(|e| e.to_string())?;
+
+    let backup = Backup {
+        version: BACKUP_VERSION,
+        exported_at: chrono::Utc::now().to_rfc3339(),
+        downloads,
+        settings,
+        history,
+    };
+    serde_json::to_string_pretty(&backup).map_err(|e| e.to_string())
+}
+
+/// Bridge: Restores a backup made by [`export_data`]; returns how many downloads were added.
+///
+/// With `merge`, downloads whose ID already exists are skipped and current
+/// settings are kept. Otherwise the download list and settings are replaced.
+/// Downloads that were running at export time come back paused.
+#[tauri::command]
+pub async fn import_data(
+    db_state: State<'_, DbState>,
+    json: String,
+    merge: bool,
+) -> Result<usize, String> {
+    let version = serde_json::from_str::<serde_json::Value>(&json)
+        .map_err(|e| format!("Invalid backup: {}", e))?
+        .get("version")
+        .and_then(|v| v.as_u64())
+        .ok_or("Invalid backup: missing version")?;
+    if version != u64::from(BACKUP_VERSION) {
+        return Err(format!("Unsupported backup version: {}", version));
+    }
+    let mut backup: Backup =
+        serde_json::from_str(&json).map_err(|e| format!("Invalid backup: {}", e))?;
+
+    for download in &mut backup.downloads {
+        download.speed = 0;
+        if download.status == DownloadStatus::Downloading {
+            download.status = DownloadStatus::Paused;
+        }
+        // Without chunk rows an unfinished transfer restarts from zero.
+        if download.status != DownloadStatus::Completed
+            && download.protocol != DownloadProtocol::Torrent
+        {
+            download.downloaded = 0;
+        }
+    }
+    backup
+        .settings
+        .retain(|key, _| !PRIVATE_SETTINGS.contains(&key.as_str()));
+
+    db::import_backup(
+        &db_state.path,
+        &backup.downloads,
+        &backup.settings,
+        &backup.history,
+        !merge,
+    )
+    .map_err(|e| e.to_string())
+}
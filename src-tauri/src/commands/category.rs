@@ -0,0 +1,177 @@
+use super::{download_base_dir, ensure_unique_path};
+use crate::db::{self, Download};
+use crate::downloader::part_file_path;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Runtime};
+
+/// Category every file falls back to when no rule matches.
+pub const FALLBACK_CATEGORY: &str = "Other";
+
+/// One entry of the `category_rules` setting.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CategoryRule {
+    pub category: String,
+    /// Extensions without the leading dot; matched case-insensitively.
+    pub extensions: Vec<String>,
+    /// Folder used by `auto_organize`; defaults to the category name.
+    #[serde(default)]
+    pub subfolder: Option<String>,
+}
+
+impl CategoryRule {
+    fn new(category: &str, extensions: &[&str]) -> Self {
+        Self {
+            category: category.to_string(),
+            extensions: extensions.iter().map(|e| e.to_string()).collect(),
+            subfolder: None,
+        }
+    }
+
+    fn matches(&self, extension: &str) -> bool {
+        self.extensions.iter().any(|e| {
+            e.trim()
+                .trim_start_matches('.')
+                .eq_ignore_ascii_case(extension)
+        })
+    }
+
+    /// Folder this rule's files are organized into.
+    pub fn folder(&self) -> &str {
+        self.subfolder
+            .as_deref()
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .unwrap_or(&self.category)
+    }
+}
+
+/// The built-in rules, used while `category_rules` is empty.
+pub fn default_category_rules() -> Vec<CategoryRule> {
+    vec![
+        CategoryRule::new(
+            "Video",
+            &["mp4", "mkv", "avi", "mov", "webm", "flv", "wmv", "m4v"],
+        ),
+        CategoryRule::new("Audio", &["mp3", "wav", "flac", "aac", "ogg", "m4a", "wma"]),
+        CategoryRule::new(
+            "Compressed",
+            &["zip", "rar", "7z", "tar", "gz", "bz2", "iso"],
+        ),
+        CategoryRule::new("Software", &["exe", "msi", "app", "dmg", "deb", "rpm"]),
+        CategoryRule::new(
+            "Documents",
+            &[
+                "pdf", "doc", "docx", "xls", "xlsx", "ppt", "pptx", "txt", "rtf", "epub",
+            ],
+        ),
+    ]
+}
+
+/// Parses a `category_rules` value; an empty value means the defaults.
+pub(crate) fn parse_category_rules(value: &str) -> Result<Vec<CategoryRule>, String> {
+    if value.trim().is_empty() {
+        return Ok(default_category_rules());
+    }
+    serde_json::from_str(value).map_err(|e| format!("Invalid category rules: {}", e))
+}
+
+/// Reads `category_rules`, falling back to the defaults if it is missing or invalid.
+pub(crate) fn load_category_rules(db_path: &str) -> Vec<CategoryRule> {
+    let value = db::get_setting(db_path, "category_rules")
+        .ok()
+        .flatten()
+        .unwrap_or_default();
+    parse_category_rules(&value).unwrap_or_else(|e| {
+        tracing::error!("[Category] {}; using defaults", e);
+        default_category_rules()
+    })
+}
+
+/// Finds the first rule whose extensions include the file's extension.
+pub(crate) fn match_category_rule<'a>(
+    rules: &'a [CategoryRule],
+    filename: &str,
+) -> Option<&'a CategoryRule> {
+    let extension = Path::new(filename).extension()?.to_str()?;
+    rules.iter().find(|rule| rule.matches(extension))
+}
+
+/// Renames `from` to `to`, copying and deleting when they are on different volumes.
+fn move_file(from: &Path, to: &Path) -> std::io::Result<()> {
+    if std::fs::rename(from, to).is_ok() {
+        return Ok(());
+    }
+    std::fs::copy(from, to)?;
+    std::fs::remove_file(from).or_else(|e| {
+        // Keep a single copy: undo the copy if the original cannot be removed.
+        let _ = std::fs::remove_file(to);
+        Err(e)
+    })
+}
+
+/// Moves a completed file into `<download_dir>/<category folder>/` when `organize_on_finish` is on.
+///
+/// Only files saved directly in the download directory are moved; anything the
+/// user placed elsewhere stays put. Returns the download with its final `filepath`.
+pub(crate) async fn organize_completed_download<R: Runtime>(
+    app: &AppHandle<R>,
+    db_path: &str,
+    mut download: Download,
+) -> Download {
+    let enabled = db::get_setting(db_path, "organize_on_finish")
+        .ok()
+        .flatten()
+        .map(|v| v == "true")
+        .unwrap_or(false);
+    if !enabled {
+        return download;
+    }
+
+    let base_dir = download_base_dir(app, db_path);
+    let source = PathBuf::from(&download.filepath);
+    if source.parent() != Some(base_dir.as_path()) || !source.is_file() {
+        return download;
+    }
+
+    let rules = load_category_rules(db_path);
+    let folder = match match_category_rule(&rules, &download.filename) {
+        Some(rule) => rule.folder().to_string(),
+        None if !download.category.is_empty() && download.category != FALLBACK_CATEGORY => {
+            download.category.clone()
+        }
+        None => return download,
+    };
+
+    let target_dir = base_dir.join(folder);
+    if let Err(e) = std::fs::create_dir_all(&target_dir) {
+        tracing::error!("[Organize] Cannot create {}: {}", target_dir.display(), e);
+        return download;
+    }
+    let file_name = source.file_name().unwrap_or_default();
+    let target = ensure_unique_path(
+        db_path,
+        target_dir.join(file_name).to_string_lossy().to_string(),
+    );
+
+    let (from, to) = (source.clone(), PathBuf::from(&target));
+    let moved = tokio::task::spawn_blocking(move || move_file(&from, &to)).await;
+    match moved {
+        Ok(Ok(())) => {
+            // Nothing should be left of the transfer at the old location.
+            let _ = std::fs::remove_file(part_file_path(&source));
+            let _ = db::update_download_path(db_path, &download.id, &target);
+            tracing::info!("[Organize] Moved {} to {}", download.filepath, target);
+            download.filepath = target;
+        }
+        Ok(Err(e)) => tracing::error!("[Organize] Failed to move {}: {}", download.filepath, e),
+        Err(e) => tracing::error!("[Organize] Move task failed: {}", e),
+    }
+    download
+}
+
+/// Bridge: Returns the built-in extension rules so the UI can seed `category_rules`.
+#[tauri::command]
+pub fn get_default_category_rules() -> Vec<CategoryRule> {
+    default_category_rules()
+}
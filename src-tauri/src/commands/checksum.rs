@@ -0,0 +1,40 @@
+use crate::db::{self, DbState};
+use crate::downloader::{verify_file_checksum, ChecksumAlgo};
+use std::path::PathBuf;
+use tauri::State;
+
+/// Bridge: Verifies a finished download against a published hash.
+///
+/// Hashing runs on a blocking thread; the outcome is recorded in the download's
+/// history as `verified` or `verify_failed`.
+#[tauri::command]
+pub async fn verify_download(
+    db_state: State<'_, DbState>,
+    id: String,
+    algo: ChecksumAlgo,
+    expected_hash: String,
+) -> Result<bool, String> {
+    let downloads = db::get_all_downloads(&db_state.path).map_err(|e| e.to_string())?;
+    let download = downloads
+        .into_iter()
+        .find(|d| d.id == id)
+        .ok_or("Download not found")?;
+
+    let path = PathBuf::from(&download.filepath);
+    if !path.is_file() {
+        return Err("Downloaded file not found on disk".to_string());
+    }
+
+    let expected = expected_hash.clone();
+    let matches = tokio::task::spawn_blocking(move || verify_file_checksum(&path, algo, &expected))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())?;
+
+    let detected = algo.resolve(&expected_hash).unwrap_or(algo);
+    let details = format!("{}: {}", detected.as_str(), expected_hash.trim());
+    let event = if matches { "verified" } else { "verify_failed" };
+    db::log_event(&db_state.path, &id, event, Some(&details)).ok();
+
+    Ok(matches)
+}
@@ -0,0 +1,93 @@
+use super::http::DownloadManager;
+use crate::db::{self, DbState};
+use serde::Serialize;
+use tauri::State;
+
+/// Where a chunk stands according to its stored progress.
+#[derive(Debug, Clone, Copy, Serialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ChunkState {
+    Completed,
+    /// Partly written; being fetched unless the download is paused.
+    Active,
+    Pending,
+}
+
+/// One row of the `chunks` table.
+#[derive(Debug, Clone, Serialize)]
+pub struct ChunkInfo {
+    pub start: i64,
+    /// Inclusive.
+    pub end: i64,
+    pub downloaded: i64,
+    pub state: ChunkState,
+}
+
+/// Per-chunk view of an HTTP download, for diagnosing chunks that stop moving.
+#[derive(Debug, Clone, Serialize)]
+pub struct ChunkProgress {
+    pub id: String,
+    /// Live connection count; 0 when the download is not running.
+    pub connections: u8,
+    pub completed: usize,
+    pub active: usize,
+    pub pending: usize,
+    /// Ordered by `start`. Empty for single-connection transfers, which keep no chunks.
+    pub chunks: Vec<ChunkInfo>,
+}
+
+/// Builds the chunk view from the database, so it works outside the transfer task.
+pub(crate) fn read_chunk_progress(
+    db_path: &str,
+    id: &str,
+    connections: u8,
+) -> Result<ChunkProgress, String> {
+    let mut records = db::get_download_chunks(db_path, id).map_err(|e| e.to_string())?;
+    records.sort_by_key(|c| c.start);
+
+    let chunks: Vec<ChunkInfo> = records
+        .into_iter()
+        .map(|c| {
+            let size = c.end - c.start + 1;
+            let state = if c.downloaded >= size {
+                ChunkState::Completed
+            } else if c.downloaded > 0 {
+                ChunkState::Active
+            } else {
+                ChunkState::Pending
+            };
+            ChunkInfo {
+                start: c.start,
+                end: c.end,
+                downloaded: c.downloaded.clamp(0, size),
+                state,
+            }
+        })
+        .collect();
+    let count = |state: ChunkState| chunks.iter().filter(|c| c.state == state).count();
+
+    Ok(ChunkProgress {
+        id: id.to_string(),
+        connections,
+        completed: count(ChunkState::Completed),
+        active: count(ChunkState::Active),
+        pending: count(ChunkState::Pending),
+        chunks,
+    })
+}
+
+/// Bridge: Returns the stored progress of each chunk of a download.
+#[tauri::command]
+pub async fn get_chunk_progress(
+    db_state: State<'_, DbState>,
+    manager: State<'_, DownloadManager>,
+    id: String,
+) -> Result<ChunkProgress, String> {
+    let connections = manager
+        .progress_snapshot()
+        .await
+        .get(&id)
+        .map(|p| p.connections)
+        .unwrap_or(0);
+    read_chunk_progress(&db_state.path, &id, connections)
+}
@@ -0,0 +1,124 @@
+use super::category::organize_completed_download;
+use super::http::{
+    combine_speed_limits, expected_checksum, release_slot, resolve_transport_options,
+    DownloadManager,
+};
+use super::{emit_download_update, execute_post_download_actions, set_and_emit_download_error};
+use crate::bandwidth::BandwidthMeter;
+use crate::db::{self, Download, DownloadStatus};
+use crate::downloader::DownloadConfig;
+use crate::ftp::FtpDownloader;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tauri::{AppHandle, Emitter, Manager, Runtime};
+use tokio::sync::mpsc;
+
+/// Internal: Spawns the task for an FTP/FTPS download.
+///
+/// Registers with the [`DownloadManager`] like an HTTP download, so pausing,
+/// speed limits and the tray/queue counters work unchanged.
+pub(super) async fn start_ftp_task<R: Runtime>(
+    app: AppHandle<R>,
+    db_path: String,
+    manager: DownloadManager,
+    download: Download,
+) -> Result<(), String> {
+    let id = download.id.clone();
+    let transport = resolve_transport_options(
+        &db_path,
+        &manager,
+        &download.url,
+        download.metadata.as_deref(),
+        1,
+    );
+    let own_speed_limit = download.speed_limit.max(0) as u64;
+    let (checksum_algo, expected_checksum) = expected_checksum(&download);
+
+    let config = DownloadConfig {
+        id: id.clone(),
+        url: download.url.clone(),
+        urls: vec![download.url.clone()],
+        filepath: PathBuf::from(&download.filepath),
+        connections: 1,
+        speed_limit: combine_speed_limits(own_speed_limit, transport.speed_limit),
+        size_hint: if download.size > 0 {
+            Some(download.size as u64)
+        } else {
+            None
+        },
+        stream_read_timeout_secs: transport.stream_read_timeout_secs,
+        username: download.username.clone(),
+        password: crate::secrets::reveal(download.password.clone()),
+        expected_checksum,
+        checksum_algo,
+        ..Default::default()
+    };
+
+    let (tx, mut rx) = mpsc::channel(1);
+    let is_cancelled = Arc::new(std::sync::atomic::AtomicBool::new(false));
+    manager.occupy_slot(&id);
+
+    tokio::spawn(async move {
+        let downloader = match FtpDownloader::new(config) {
+            Ok(downloader) => downloader,
+            Err(e) => {
+                set_and_emit_download_error(&app, &db_path, &id, &e.to_string());
+                release_slot(&app, &manager, &id).await;
+                return;
+            }
+        };
+        let downloader = downloader
+            .with_db(db_path.clone())
+            .with_cancel_signal(is_cancelled.clone());
+
+        manager
+            .add_active(
+                id.clone(),
+                tx,
+                downloader.get_progress(),
+                downloader.get_rate_limiter(),
+                own_speed_limit,
+            )
+            .await;
+
+        let manager_progress = manager.clone();
+        let usage = app.state::<BandwidthMeter>().recorder();
+        let download_task = downloader.download(move |progress| {
+            usage.observe(progress.downloaded);
+            manager_progress.queue_progress(progress);
+        });
+
+        tokio::select! {
+            res = download_task => {
+                match res {
+                    Ok(_) => {
+                        let downloaded = downloader.get_progress().lock().unwrap().downloaded;
+                        let _ = db::update_download_progress(&db_path, &id, downloaded as i64, 0);
+                        if downloaded > 0 {
+                            let _ = db::update_download_size(&db_path, &id, downloaded as i64);
+                        }
+                        let download = organize_completed_download(&app, &db_path, download).await;
+                        let _ = db::mark_download_completed(&db_path, &id);
+                        emit_download_update(&app, &db_path, &id);
+                        let _ = app.emit("download-completed", id.clone());
+                        execute_post_download_actions(app.clone(), db_path.clone(), download).await;
+                    }
+                    Err(e) => {
+                        set_and_emit_download_error(&app, &db_path, &id, &e.to_string());
+                    }
+                }
+            }
+            _ = rx.recv() => {
+                // The blocking transfer notices this on its next read and stops.
+                is_cancelled.store(true, std::sync::atomic::Ordering::Relaxed);
+                let _ = db::update_download_status(&db_path, &id, DownloadStatus::Paused);
+                emit_download_update(&app, &db_path, &id);
+                let _ = app.emit("download-paused", id.clone());
+            }
+        }
+
+        release_slot(&app, &manager, &id).await;
+    });
+
+    Ok(())
+}
@@ -0,0 +1,226 @@
+use super::{resolve_download_path, DownloadManager};
+use crate::db::{self, DbState, DownloadProtocol, DownloadStatus};
+use crate::torrent::TorrentManager;
+use std::path::Path;
+use tauri::{AppHandle, Runtime, State};
+
+/// Availability and version of an external helper binary (yt-dlp, ffmpeg).
+#[derive(serde::Serialize)]
+pub struct BinaryHealth {
+    pub available: bool,
+    pub version: Option<String>,
+}
+
+/// Whether the external tools needed for video downloads are installed.
+#[derive(serde::Serialize)]
+pub struct Dependencies {
+    pub yt_dlp: bool,
+    pub ffmpeg: bool,
+    pub yt_dlp_version: Option<String>,
+}
+
+/// A download whose persisted status disagrees with the in-memory engine state.
+#[derive(serde::Serialize)]
+pub struct PhantomDownload {
+    pub id: String,
+    pub filename: String,
+    pub protocol: DownloadProtocol,
+    /// True if the DB says `downloading` but no engine task owns it.
+    pub db_active_engine_idle: bool,
+}
+
+/// Consolidated diagnostics report, suitable for pasting into a bug report.
+#[derive(serde::Serialize)]
+pub struct AppHealth {
+    pub db_reachable: bool,
+    pub db_path: String,
+    /// Size of the SQLite file plus its WAL, in bytes.
+    pub db_size: u64,
+    pub download_dir: String,
+    pub download_dir_writable: bool,
+    pub download_dir_free_space: Option<u64>,
+    pub yt_dlp: BinaryHealth,
+    pub ffmpeg: BinaryHealth,
+    pub torrent_session_ready: bool,
+    pub torrent_listen_port: Option<u16>,
+    pub active_http: usize,
+    pub active_torrents: usize,
+    pub queued: usize,
+    pub phantom_active: Vec<PhantomDownload>,
+}
+
+/// Runs `<name> <version_arg>` and returns the first line of its output.
+fn probe_binary(name: &str, version_arg: &str) -> BinaryHealth {
+    match std::process::Command::new(name).arg(version_arg).output() {
+        Ok(output) if output.status.success() => BinaryHealth {
+            available: true,
+            version: String::from_utf8_lossy(&output.stdout)
+                .lines()
+                .next()
+                .map(|l| l.trim().to_string()),
+        },
+        _ => BinaryHealth {
+            available: false,
+            version: None,
+        },
+    }
+}
+
+/// Best-effort free space lookup using the platform's own tooling.
+fn free_space_bytes(dir: &Path) -> Option<u64> {
+    #[cfg(target_os = "windows")]
+    {
+        let drive = dir
+            .to_string_lossy()
+            .chars()
+            .next()
+            .filter(|c| c.is_ascii_alphabetic())?;
+        let output = std::process::Command::new("powershell")
+            .args([
+                "-NoProfile",
+                "-Command",
+                &format!("(Get-PSDrive {}).Free", drive),
+            ])
+            .output()
+            .ok()?;
+        String::from_utf8_lossy(&output.stdout)
+            .trim()
+            .parse::<u64>()
+            .ok()
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    {
+        // POSIX output: header line, then "fs blocks used available capacity mount".
+        let output = std::process::Command::new("df")
+            .arg("-Pk")
+            .arg(dir)
+            .output()
+            .ok()?;
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let line = stdout.lines().nth(1)?;
+        let available_kb = line.split_whitespace().nth(3)?.parse::<u64>().ok()?;
+        Some(available_kb * 1024)
+    }
+}
+
+fn is_dir_writable(dir: &Path) -> bool {
+    if std::fs::create_dir_all(dir).is_err() {
+        return false;
+    }
+    let probe = dir.join(format!(".ciel_write_test_{}", uuid::Uuid::new_v4()));
+    let writable = std::fs::write(&probe, b"ok").is_ok();
+    let _ = std::fs::remove_file(&probe);
+    writable
+}
+
+/// Bridge: Aggregates health checks from the DB, filesystem, external tools and engines.
+#[tauri::command]
+pub async fn get_health<R: Runtime>(
+    app: AppHandle<R>,
+    db_state: State<'_, DbState>,
+    manager: State<'_, DownloadManager>,
+    torrent_manager: State<'_, TorrentManager>,
+) -> Result<AppHealth, String> {
+    let db_path = db_state.path.clone();
+    let db_reachable = db::open_db(&db_path)
+        .and_then(|conn| conn.query_row("SELECT 1", [], |row| row.get::<_, i64>(0)))
+        .is_ok();
+    let db_size = [db_path.clone(), format!("{}-wal", db_path)]
+        .iter()
+        .filter_map(|p| std::fs::metadata(p).ok())
+        .map(|m| m.len())
+        .sum();
+
+    // Resolving a placeholder name yields the configured (or default) download directory.
+    let probe_path = resolve_download_path(&app, &db_path, "ciel_health_probe", None);
+    let download_dir = Path::new(&probe_path)
+        .parent()
+        .map(|p| p.to_path_buf())
+        .unwrap_or_default();
+
+    let (download_dir_writable, download_dir_free_space, yt_dlp, ffmpeg) = {
+        let dir = download_dir.clone();
+        tokio::task::spawn_blocking(move || {
+            (
+                is_dir_writable(&dir),
+                free_space_bytes(&dir),
+                probe_binary("yt-dlp", "--version"),
+                probe_binary("ffmpeg", "-version"),
+            )
+        })
+        .await
+        .map_err(|e| e.to_string())?
+    };
+
+    let (torrent_session_ready, torrent_listen_port) = torrent_manager.session_status().await;
+    let (active_http, _) = manager.get_global_status().await;
+    let (active_torrents, _) = torrent_manager.get_global_status().await;
+
+    let downloads = db::get_all_downloads(&db_path).unwrap_or_default();
+    let queued = downloads
+        .iter()
+        .filter(|d| d.status == DownloadStatus::Queued)
+        .count();
+
+    let mut phantom_active = Vec::new();
+    for d in &downloads {
+        let engine_active = match d.protocol {
+            DownloadProtocol::Torrent => torrent_manager.is_active(&d.id).await,
+            _ => manager.is_active(&d.id).await,
+        };
+        let db_active = d.status == DownloadStatus::Downloading;
+        // Paused torrents keep their session handle, so that pairing is expected.
+        let expected_idle_handle =
+            d.protocol == DownloadProtocol::Torrent && d.status == DownloadStatus::Paused;
+        let mismatch = if db_active {
+            !engine_active
+        } else {
+            engine_active && !expected_idle_handle
+        };
+        if mismatch {
+            phantom_active.push(PhantomDownload {
+                id: d.id.clone(),
+                filename: d.filename.clone(),
+                protocol: d.protocol.clone(),
+                db_active_engine_idle: db_active,
+            });
+        }
+    }
+
+    Ok(AppHealth {
+        db_reachable,
+        db_path,
+        db_size,
+        download_dir: download_dir.to_string_lossy().to_string(),
+        download_dir_writable,
+        download_dir_free_space,
+        yt_dlp,
+        ffmpeg,
+        torrent_session_ready,
+        torrent_listen_port,
+        active_http,
+        active_torrents,
+        queued,
+        phantom_active,
+    })
+}
+
+/// Bridge: Reports whether yt-dlp and ffmpeg can be run, so the UI can warn before a video download.
+#[tauri::command]
+pub async fn check_dependencies() -> Result<Dependencies, String> {
+    let (yt_dlp, ffmpeg) = tokio::task::spawn_blocking(|| {
+        (
+            probe_binary("yt-dlp", "--version"),
+            probe_binary("ffmpeg", "-version"),
+        )
+    })
+    .await
+    .map_err(|e| e.to_string())?;
+
+    Ok(Dependencies {
+        yt_dlp: yt_dlp.available,
+        ffmpeg: ffmpeg.available,
+        yt_dlp_version: yt_dlp.version,
+    })
+}
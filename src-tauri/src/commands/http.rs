@@ -1,45 +1,115 @@
+use super::category::organize_completed_download;
+use super::chunks::read_chunk_progress;
 use super::{
-    ensure_unique_path, execute_post_download_actions, get_category_from_filename,
+    adopt_discovered_filename, emit_download_update, ensure_unique_path,
+    execute_post_download_actions, get_category_from_filename, queue, remember_chosen_dir,
     resolve_download_path, set_and_emit_download_error,
 };
+use crate::bandwidth::{self, BandwidthMeter};
+use crate::clipboard::UrlKind;
 use crate::db::{self, DbState, Download, DownloadProtocol, DownloadStatus};
-use crate::downloader::{DownloadConfig, Downloader};
+use crate::downloader::{
+    apply_basic_auth, parse_headers, parse_proxy, split_remaining_chunks, split_url_credentials,
+    ChecksumAlgo, DownloadConfig, Downloader, ResourceValidator, SharedRateLimiter,
+};
+use crate::ftp::is_ftp_url;
+use crate::secrets::{self, Secret};
 use crate::torrent::TorrentManager;
 use rookie;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
-use tauri::{AppHandle, Emitter, Runtime, State};
-use tauri_plugin_notification::NotificationExt;
+use tauri::{AppHandle, Emitter, Manager, Runtime, State};
 use tokio::sync::{mpsc, Mutex};
 
 use std::fs;
 
+/// Default for the `max_open_handles` setting.
+const DEFAULT_MAX_OPEN_HANDLES: usize = 64;
+/// Upper bound for the `write_buffer_kb` setting (16 MiB per open file).
+const MAX_WRITE_BUFFER_KB: u64 = 16 * 1024;
+/// How long a paused download may take to flush and checkpoint its workers.
+const PAUSE_FLUSH_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+/// Cadence of the batched `downloads-progress` event.
+const PROGRESS_EMIT_INTERVAL: std::time::Duration = std::time::Duration::from_millis(250);
+
+/// Handles kept for a single in-flight HTTP download.
+struct ActiveHttpDownload {
+    cancel_tx: mpsc::Sender<()>,
+    progress: Arc<std::sync::Mutex<crate::downloader::DownloadProgress>>,
+    rate_limiter: Option<Arc<SharedRateLimiter>>,
+    /// The download's own cap in bytes/sec (0 = none), combined with the global limit.
+    own_speed_limit: u64,
+}
+
+/// Combines two speed caps where 0 means unlimited; the stricter non-zero cap wins.
+pub(crate) fn combine_speed_limits(a: u64, b: u64) -> u64 {
+    match (a, b) {
+        (0, limit) | (limit, 0) => limit,
+        (a, b) => a.min(b),
+    }
+}
+
 /// Orchestrates the lifecycle of active HTTP downloads.
 ///
 /// It acts as a registry for ongoing transfers, allowing the application
 /// to send cancellation signals to specific download tasks via `mpsc` channels.
 #[derive(Clone)]
 pub struct DownloadManager {
-    /// Internal map linking Download IDs to their respective cancellation senders and progress monitors.
-    active_downloads: Arc<
-        Mutex<
-            HashMap<
-                String,
-                (
-                    mpsc::Sender<()>,
-                    Arc<std::sync::Mutex<crate::downloader::DownloadProgress>>,
-                ),
-            >,
-        >,
-    >,
+    /// Internal map linking Download IDs to their cancellation senders, progress monitors and rate limiters.
+    active_downloads: Arc<Mutex<HashMap<String, ActiveHttpDownload>>>,
+    /// Temporary cap (bytes/sec) layered over the configured speed limit, e.g. by gaming mode.
+    speed_limit_override: Arc<std::sync::Mutex<Option<u64>>>,
+    /// Limit of the active `speed_schedule` window; replaces the configured limit while set.
+    scheduled_speed_limit: Arc<std::sync::Mutex<Option<u64>>>,
+    /// Global budget of open file handles/sockets shared by every HTTP worker.
+    handle_permits: Arc<tokio::sync::Semaphore>,
+    /// Capacity `handle_permits` was last sized to.
+    handle_capacity: Arc<std::sync::Mutex<usize>>,
+    /// IDs holding a `max_concurrent` slot, including transfers that are still starting up.
+    running: Arc<std::sync::Mutex<HashSet<String>>>,
+    /// Serializes queue admission so concurrent starts cannot overshoot `max_concurrent`.
+    queue_lock: Arc<Mutex<()>>,
+    /// Serializes picking a unique file path and recording it, so two downloads never get the same one.
+    path_lock: Arc<Mutex<()>>,
+    /// Connection caps learned this session from hosts that kept answering 429/503.
+    throttled_hosts: Arc<std::sync::Mutex<HashMap<String, u8>>>,
+    /// Latest progress of each HTTP/FTP transfer not yet sent to the frontend.
+    pending_progress: Arc<std::sync::Mutex<HashMap<String, crate::downloader::DownloadProgress>>>,
 }
 
 impl DownloadManager {
     pub fn new() -> Self {
         Self {
             active_downloads: Arc::new(Mutex::new(HashMap::new())),
+            speed_limit_override: Arc::new(std::sync::Mutex::new(None)),
+            scheduled_speed_limit: Arc::new(std::sync::Mutex::new(None)),
+            handle_permits: Arc::new(tokio::sync::Semaphore::new(DEFAULT_MAX_OPEN_HANDLES)),
+            handle_capacity: Arc::new(std::sync::Mutex::new(DEFAULT_MAX_OPEN_HANDLES)),
+            running: Arc::new(std::sync::Mutex::new(HashSet::new())),
+            queue_lock: Arc::new(Mutex::new(())),
+            path_lock: Arc::new(Mutex::new(())),
+            throttled_hosts: Arc::new(std::sync::Mutex::new(HashMap::new())),
+            pending_progress: Arc::new(std::sync::Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Returns the shared open-handle semaphore, resized to `limit` if the setting changed.
+    ///
+    /// Shrinking only retires permits that are currently free; handles already in use
+    /// are returned to the pool and retired as workers release them.
+    pub fn handle_permits(&self, limit: usize) -> Arc<tokio::sync::Semaphore> {
+        let limit = limit.max(1);
+        if let Ok(mut capacity) = self.handle_capacity.lock() {
+            if limit > *capacity {
+                self.handle_permits.add_permits(limit - *capacity);
+                *capacity = limit;
+            } else if limit < *capacity {
+                let retired = self.handle_permits.forget_permits(*capacity - limit);
+                *capacity -= retired;
+            }
         }
+        self.handle_permits.clone()
     }
 
     /// Registers a new active download and its cancellation hook.
@@ -48,45 +118,233 @@ impl DownloadManager {
         id: String,
         cancel_tx: mpsc::Sender<()>,
         progress: Arc<std::sync::Mutex<crate::downloader::DownloadProgress>>,
+        rate_limiter: Option<Arc<SharedRateLimiter>>,
+        own_speed_limit: u64,
     ) {
         let mut active = self.active_downloads.lock().await;
-        active.insert(id, (cancel_tx, progress));
+        active.insert(
+            id,
+            ActiveHttpDownload {
+                cancel_tx,
+                progress,
+                rate_limiter,
+                own_speed_limit,
+            },
+        );
     }
 
     /// Unregisters a download, typically called after a successful completion or an error.
+    ///
+    /// Also frees the download's queue slot.
     pub async fn remove_active(&self, id: &str) {
         let mut active = self.active_downloads.lock().await;
         active.remove(id);
+        self.discard_progress(id);
+        self.vacate_slot(id);
+    }
+
+    /// Claims a queue slot for a transfer that is about to start.
+    pub fn occupy_slot(&self, id: &str) {
+        if let Ok(mut running) = self.running.lock() {
+            running.insert(id.to_string());
+        }
+    }
+
+    /// Gives back a slot claimed with [`Self::occupy_slot`], e.g. once the torrent
+    /// engine counts the transfer itself or it failed to start.
+    pub fn vacate_slot(&self, id: &str) {
+        if let Ok(mut running) = self.running.lock() {
+            running.remove(id);
+        }
+    }
+
+    /// Waits until a cancelled transfer has released its slot, i.e. finished
+    /// writing its progress. Gives up after [`PAUSE_FLUSH_TIMEOUT`].
+    pub async fn wait_until_stopped(&self, id: &str) {
+        let deadline = std::time::Instant::now() + PAUSE_FLUSH_TIMEOUT;
+        while std::time::Instant::now() < deadline
+            && self.running.lock().map(|r| r.contains(id)).unwrap_or(false)
+        {
+            tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+        }
+    }
+
+    /// Number of HTTP/FTP transfers holding a queue slot.
+    pub fn running_count(&self) -> usize {
+        self.running.lock().map(|r| r.len()).unwrap_or(0)
+    }
+
+    /// Held while deciding whether a download may start or must wait in the queue.
+    pub async fn lock_queue(&self) -> tokio::sync::MutexGuard<'_, ()> {
+        self.queue_lock.lock().await
+    }
+
+    /// Held from choosing a file path with `ensure_unique_path` until it is stored.
+    pub async fn lock_paths(&self) -> tokio::sync::MutexGuard<'_, ()> {
+        self.path_lock.lock().await
+    }
+
+    /// Connection cap learned for `host` this session, if it throttled an earlier transfer.
+    pub fn throttled_host_cap(&self, host: &str) -> Option<u8> {
+        self.throttled_hosts.lock().ok()?.get(host).copied()
+    }
+
+    /// Lowers the session cap for `host` after it throttled a transfer at `cap` connections.
+    pub fn record_throttled_host(&self, host: &str, cap: u8) {
+        if let Ok(mut hosts) = self.throttled_hosts.lock() {
+            let entry = hosts.entry(host.to_string()).or_insert(cap);
+            *entry = (*entry).min(cap).max(1);
+        }
     }
 
     /// Signals an active download task to abort immediately.
     pub async fn cancel(&self, id: &str) {
         let mut active = self.active_downloads.lock().await;
-        if let Some((tx, _)) = active.get(id) {
+        if let Some(entry) = active.get(id) {
             // Signal the async task to stop.
-            let _ = tx.send(()).await;
+            let _ = entry.cancel_tx.send(()).await;
         }
         active.remove(id);
+        // A stale update must not follow the pause feedback.
+        self.discard_progress(id);
+    }
+
+    /// Records `progress` for the next `downloads-progress` batch, replacing any
+    /// older update of the same download.
+    pub fn queue_progress(&self, progress: crate::downloader::DownloadProgress) {
+        if let Ok(mut pending) = self.pending_progress.lock() {
+            pending.insert(progress.id.clone(), progress);
+        }
+    }
+
+    fn discard_progress(&self, id: &str) {
+        if let Ok(mut pending) = self.pending_progress.lock() {
+            pending.remove(id);
+        }
+    }
+
+    /// Takes every update queued since the last batch.
+    fn take_progress(&self) -> Vec<crate::downloader::DownloadProgress> {
+        self.pending_progress
+            .lock()
+            .map(|mut pending| pending.drain().map(|(_, p)| p).collect())
+            .unwrap_or_default()
     }
 
     pub async fn is_active(&self, id: &str) -> bool {
         self.active_downloads.lock().await.contains_key(id)
     }
 
+    /// IDs of the transfers currently registered as active.
+    pub async fn active_ids(&self) -> Vec<String> {
+        self.active_downloads.lock().await.keys().cloned().collect()
+    }
+
     /// Calculates aggregate download statistics for the system tray.
     pub async fn get_global_status(&self) -> (usize, u64) {
         let active = self.active_downloads.lock().await;
         let mut total_speed = 0;
         let count = active.len();
 
-        for (_, (_, progress)) in active.iter() {
-            if let Ok(p) = progress.lock() {
+        for entry in active.values() {
+            if let Ok(p) = entry.progress.lock() {
                 total_speed += p.speed;
             }
         }
 
         (count, total_speed)
     }
+
+    /// Clones the live progress of every running HTTP download, keyed by ID.
+    pub async fn progress_snapshot(&self) -> HashMap<String, crate::downloader::DownloadProgress> {
+        let active = self.active_downloads.lock().await;
+        active
+            .iter()
+            .filter_map(|(id, entry)| entry.progress.lock().ok().map(|p| (id.clone(), p.clone())))
+            .collect()
+    }
+
+    /// Sets or clears the temporary speed cap. `None` restores the configured limit.
+    pub fn set_speed_limit_override(&self, limit: Option<u64>) {
+        if let Ok(mut current) = self.speed_limit_override.lock() {
+            *current = limit.filter(|l| *l > 0);
+        }
+    }
+
+    /// Sets or clears the limit of the current `speed_schedule` window (`Some(0)` = unlimited).
+    pub fn set_scheduled_speed_limit(&self, limit: Option<u64>) {
+        if let Ok(mut current) = self.scheduled_speed_limit.lock() {
+            *current = limit;
+        }
+    }
+
+    /// Combines the configured global limit with any active override (0 = unlimited).
+    ///
+    /// A scheduled window takes the place of the configured limit while it is active.
+    pub fn effective_speed_limit(&self, configured: u64) -> u64 {
+        let override_limit = self
+            .speed_limit_override
+            .lock()
+            .ok()
+            .and_then(|current| *current);
+        let scheduled = self
+            .scheduled_speed_limit
+            .lock()
+            .ok()
+            .and_then(|current| *current);
+        combine_speed_limits(scheduled.unwrap_or(configured), override_limit.unwrap_or(0))
+    }
+
+    /// Pushes a new global speed limit into every running HTTP download without
+    /// restarting it. Each download keeps honouring its own cap if that is stricter.
+    pub async fn apply_speed_limit(&self, global_limit: u64) {
+        let active = self.active_downloads.lock().await;
+        for entry in active.values() {
+            if let Some(limiter) = &entry.rate_limiter {
+                limiter.set_limit(combine_speed_limits(entry.own_speed_limit, global_limit));
+            }
+        }
+    }
+
+    /// Changes one running download's own cap. Returns false if it isn't active.
+    pub async fn set_download_speed_limit(
+        &self,
+        id: &str,
+        own_limit: u64,
+        global_limit: u64,
+    ) -> bool {
+        let mut active = self.active_downloads.lock().await;
+        let Some(entry) = active.get_mut(id) else {
+            return false;
+        };
+        entry.own_speed_limit = own_limit;
+        if let Some(limiter) = &entry.rate_limiter {
+            limiter.set_limit(combine_speed_limits(own_limit, global_limit));
+        }
+        true
+    }
+}
+
+/// Re-splits the unfinished chunks of a paused HTTP download for a new connection count.
+///
+/// Already-downloaded bytes are preserved; downloads without a chunk layout yet
+/// (never started, or single-connection) are left untouched.
+pub(crate) fn rechunk_remaining(db_path: &str, id: &str, connections: u8) -> Result<(), String> {
+    let chunks = db::get_download_chunks(db_path, id).map_err(|e| e.to_string())?;
+    if chunks.is_empty() {
+        return Ok(());
+    }
+
+    let resplit = split_remaining_chunks(&chunks, connections);
+    tracing::info!(
+        "[{}] Re-chunking for {} connections: {} -> {} chunks",
+        id,
+        connections,
+        chunks.len(),
+        resplit.len()
+    );
+    db::replace_download_chunks(db_path, id, resplit).map_err(|e| e.to_string())?;
+    db::update_download_connections(db_path, id, connections as i32).map_err(|e| e.to_string())
 }
 
 /// Helper to transform Google Drive viewer links into direct download links.
@@ -122,19 +380,69 @@ fn transform_google_drive_url(url: &str) -> String {
     url.to_string()
 }
 
+/// Where a pasted link should be routed.
+#[derive(Debug, Clone, Copy, serde::Serialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum UrlType {
+    /// A file served directly over HTTP(S) or FTP.
+    DirectHttp,
+    Magnet,
+    /// A `.torrent` file, by path or by the server's content type.
+    TorrentFile,
+    /// A media page; known video hosts, or pages yt-dlp recognizes when `video_probe` is on.
+    Video,
+    /// An HTML page or a failed request.
+    Unsupported,
+}
+
+/// How long the optional yt-dlp probe may take before the link is treated as unsupported.
+const VIDEO_PROBE_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+
+/// Asks yt-dlp whether it can extract media from `url`, without downloading anything.
+async fn ytdlp_recognizes(url: &str) -> bool {
+    let mut command = tokio::process::Command::new("yt-dlp");
+    command
+        .args(["--simulate", "--quiet", "--no-warnings", "--no-playlist"])
+        .arg(url)
+        .stdin(std::process::Stdio::null())
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .kill_on_drop(true);
+    matches!(
+        tokio::time::timeout(VIDEO_PROBE_TIMEOUT, command.status()).await,
+        Ok(Ok(status)) if status.success()
+    )
+}
+
+/// Type of a link that answered with an HTML page: a video if `video_probe` is on
+/// and yt-dlp recognizes it, otherwise unsupported.
+async fn html_page_type(db_path: &str, url: &str) -> UrlType {
+    let probe = db::get_setting(db_path, "video_probe")
+        .ok()
+        .flatten()
+        .map(|v| v == "true")
+        .unwrap_or(false);
+    if probe && ytdlp_recognizes(url).await {
+        UrlType::Video
+    } else {
+        UrlType::Unsupported
+    }
+}
+
 /// Detailed metadata discovered during URL validation.
 #[derive(serde::Serialize)]
 pub struct UrlTypeInfo {
+    pub(super) url_type: UrlType,
     /// True if the URL follows the `magnet:` protocol.
     is_magnet: bool,
     /// The MIME type reported by the server (e.g., `application/zip`).
     content_type: Option<String>,
     /// Total file size reported by the server in bytes.
-    content_length: Option<u64>,
+    pub(super) content_length: Option<u64>,
     /// A suggested filename extracted from the `Content-Disposition` header.
-    hinted_filename: Option<String>,
+    pub(super) hinted_filename: Option<String>,
     /// The final resolved URL (useful for Drive tokens discovered during validation).
-    resolved_url: Option<String>,
+    pub(super) resolved_url: Option<String>,
 }
 
 /// Performs a lightweight inspection of a URL to determine its type and metadata.
@@ -147,18 +455,41 @@ pub async fn validate_url_type(
     url: String,
 ) -> Result<UrlTypeInfo, String> {
     let url = transform_google_drive_url(&url);
-    if url.starts_with("magnet:") {
+    // Links that can be classified offline, the same way auto-catch does.
+    let offline_type = match crate::clipboard::classify_url(&url) {
+        UrlKind::Magnet => Some(UrlType::Magnet),
+        UrlKind::Torrent => Some(UrlType::TorrentFile),
+        UrlKind::Video => Some(UrlType::Video),
+        UrlKind::Http | UrlKind::Unknown => None,
+    };
+    if let Some(url_type) = offline_type {
+        let hinted_filename = (url_type == UrlType::TorrentFile)
+            .then(|| crate::downloader::extract_filename(&url, &reqwest::header::HeaderMap::new()));
+        return Ok(UrlTypeInfo {
+            url_type,
+            is_magnet: url_type == UrlType::Magnet,
+            content_type: None,
+            content_length: None,
+            hinted_filename,
+            resolved_url: Some(url),
+        });
+    }
+    // FTP has no headers to sniff; the name comes from the path.
+    if is_ftp_url(&url) {
         return Ok(UrlTypeInfo {
-            is_magnet: true,
+            url_type: UrlType::DirectHttp,
+            is_magnet: false,
             content_type: None,
             content_length: None,
-            hinted_filename: None,
+            hinted_filename: Some(crate::downloader::extract_filename(
+                &url,
+                &reqwest::header::HeaderMap::new(),
+            )),
             resolved_url: Some(url),
         });
     }
 
-    let mut builder = reqwest::Client::builder()
-        .user_agent("Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36");
+    let mut builder = reqwest::Client::builder().user_agent(crate::downloader::DEFAULT_USER_AGENT);
 
     // Automatically fetch cookies if a browser is selected in settings
     if let Ok(Some(browser)) = db::get_setting(&db_state.path, "cookie_browser") {
@@ -174,7 +505,11 @@ pub async fn validate_url_type(
         }
     }
 
-    let client = builder.build().unwrap_or_default();
+    if let Some(proxy) = resolve_proxy(&db_state.path, None) {
+        builder = builder.proxy(parse_proxy(&proxy).map_err(|e| e.to_string())?);
+    }
+
+    let client = builder.build().map_err(|e| e.to_string())?;
 
     // Use GET with Range: bytes=0-0 to get headers (including Content-Disposition) without downloading
     let response = client
@@ -185,6 +520,7 @@ pub async fn validate_url_type(
         .await
         .map_err(|e| e.to_string())?;
 
+    let status = response.status();
     let headers = response.headers();
     let mut content_type = headers
         .get(reqwest::header::CONTENT_TYPE)
@@ -205,19 +541,33 @@ pub async fn validate_url_type(
 
     let hinted_filename = Some(crate::downloader::extract_filename(&url, headers));
 
-    // Special check for Google Drive: if it's returning HTML, it's likely the "virus scan" warning or login page.
-    if url.contains("drive.google.com/uc")
+    // Hosts like Google Drive or MediaFire answer with a confirmation page; follow the
+    // direct link on it. We need the FULL body, so we retry without the Range header.
+    if crate::downloader::has_resolver(&url)
         && content_type
             .as_ref()
             .map(|s| s.contains("text/html"))
             .unwrap_or(false)
     {
-        // Optimization: Try to find a confirm token in the HTML body if confirm=t failed.
-        // We need to fetch the FULL body, so we retry without the Range header.
         if let Ok(full_res) = client.get(&url).send().await {
             let body = full_res.text().await.unwrap_or_default();
 
-            // 1. Scrape metadata from HTML as a fallback
+            // The resolver never returns `url` itself, which stops the recursion.
+            if let Some(direct) = crate::downloader::resolve_direct_url(&url, &body) {
+                return Box::pin(validate_url_type(db_state, direct)).await;
+            }
+            if !url.contains("drive.google.com") {
+                return Ok(UrlTypeInfo {
+                    url_type: UrlType::Unsupported,
+                    is_magnet: false,
+                    content_type: Some("text/html".to_string()),
+                    content_length: None,
+                    hinted_filename,
+                    resolved_url: Some(url),
+                });
+            }
+
+            // Scrape metadata from Drive's HTML as a fallback
             let mut scraped_filename = None;
             let mut scraped_size = None;
             if let Some(re) =
@@ -229,48 +579,7 @@ pub async fn validate_url_type(
                 }
             }
 
-            // 2. Try to find confirm token and uuid
-            let mut found_token = None;
-            let mut found_uuid = None;
-
-            if let Some(re) = regex::Regex::new(r#"name="confirm" value="([^"]+)""#).ok() {
-                found_token = re
-                    .captures(&body)
-                    .and_then(|c| c.get(1))
-                    .map(|m| m.as_str().to_string());
-            }
-            if let Some(re) = regex::Regex::new(r#"name="uuid" value="([^"]+)""#).ok() {
-                found_uuid = re
-                    .captures(&body)
-                    .and_then(|c| c.get(1))
-                    .map(|m| m.as_str().to_string());
-            }
-
-            // 3. Loop Prevention: If we only found 't' and we already had it, or we found nothing new
-            if let Some(ref token) = found_token {
-                if token == "t" && url.contains("confirm=t") {
-                    found_token = None; // Stop recursion
-                }
-            }
-
-            if let Some(token) = found_token {
-                let mut new_url = url.clone();
-                if !new_url.contains("confirm=") {
-                    new_url = format!("{}&confirm={}", new_url, token);
-                } else {
-                    new_url = new_url.replace("confirm=t", &format!("confirm={}", token));
-                }
-
-                if let Some(uuid) = found_uuid {
-                    if !new_url.contains("uuid=") {
-                        new_url = format!("{}&uuid={}", new_url, uuid);
-                    }
-                }
-
-                return Box::pin(validate_url_type(db_state, new_url)).await;
-            }
-
-            // 4. Return Warning with scraped metadata
+            // Return Warning with scraped metadata
             let display_name = match (scraped_filename, scraped_size) {
                 (Some(n), Some(s)) => format!("{} ({}) - Login Required", n, s),
                 (Some(n), None) => format!("{} - Login Required", n),
@@ -278,6 +587,7 @@ pub async fn validate_url_type(
             };
 
             return Ok(UrlTypeInfo {
+                url_type: UrlType::Unsupported,
                 is_magnet: false,
                 content_type: Some("text/html".to_string()),
                 content_length: None,
@@ -335,7 +645,15 @@ pub async fn validate_url_type(
         }
     }
 
+    let url_type = match content_type.as_deref() {
+        _ if !status.is_success() => UrlType::Unsupported,
+        Some(ct) if ct.contains("application/x-bittorrent") => UrlType::TorrentFile,
+        Some(ct) if ct.contains("text/html") => html_page_type(&db_state.path, &url).await,
+        _ => UrlType::DirectHttp,
+    };
+
     Ok(UrlTypeInfo {
+        url_type,
         is_magnet: false,
         content_type,
         content_length,
@@ -344,17 +662,39 @@ pub async fn validate_url_type(
     })
 }
 
-/// New Deep Search for Firefox cookies on Windows to bypass file locks and find correct profiles.
+/// Folder holding the Firefox profile directories on this platform.
+fn firefox_profiles_dir() -> Option<PathBuf> {
+    #[cfg(target_os = "windows")]
+    {
+        Some(
+            dirs::config_dir()?
+                .join("Mozilla")
+                .join("Firefox")
+                .join("Profiles"),
+        )
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        Some(dirs::config_dir()?.join("Firefox").join("Profiles"))
+    }
+
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    {
+        Some(dirs::home_dir()?.join(".mozilla").join("firefox"))
+    }
+}
+
+/// Deep Search for Firefox cookies that bypasses file locks and finds every profile.
+///
+/// A running Firefox keeps `cookies.sqlite` locked, so each profile's database is
+/// copied to a temp file before reading.
 fn get_cookies_from_firefox_deep(url_str: &str) -> Option<String> {
     let domain = url::Url::parse(url_str).ok()?.host_str()?.to_string();
     let target_host = domain.to_lowercase();
 
-    // 1. Resolve Firefox Profile directory on Windows
-    let app_data = std::env::var("APPDATA").ok()?;
-    let profiles_path = Path::new(&app_data)
-        .join("Mozilla")
-        .join("Firefox")
-        .join("Profiles");
+    // 1. Resolve Firefox Profile directory
+    let profiles_path = firefox_profiles_dir()?;
 
     tracing::info!("[Firefox] Scaning for profiles in: {:?}", profiles_path);
     if !profiles_path.exists() {
@@ -420,10 +760,17 @@ fn get_cookies_from_firefox_deep(url_str: &str) -> Option<String> {
     }
 }
 
+/// True if a cookie read failed because the browser holds its database open.
+fn is_cookie_store_locked(error: &str) -> bool {
+    let error = error.to_lowercase();
+    error.contains("database is locked")
+        || error.contains("being used by another process")
+        || error.contains("os error 32")
+}
+
 /// Helper: Extracts cookies for a specific URL from a chosen browser using `rookie`.
 fn get_cookies_from_browser(browser: &str, url: &str) -> Option<String> {
-    // SPECIAL CASE: Deep Scan for Firefox on Windows
-    #[cfg(target_os = "windows")]
+    // SPECIAL CASE: Deep Scan for Firefox, which works while the browser is open
     if browser.to_lowercase() == "firefox" {
         if let Some(cookies) = get_cookies_from_firefox_deep(url) {
             return Some(cookies);
@@ -465,17 +812,21 @@ fn get_cookies_from_browser(browser: &str, url: &str) -> Option<String> {
             }
         }
         Err(e) => {
-            tracing::error!("Failed to extract cookies from {}: {}", browser, e);
+            if is_cookie_store_locked(&e.to_string()) {
+                tracing::warn!(
+                    "[Cookies] The {} cookie store is locked by the running browser; close it and retry to use its cookies.",
+                    browser
+                );
+            } else {
+                tracing::error!("Failed to extract cookies from {}: {}", browser, e);
+            }
             None
         }
     }
 }
 
 fn is_single_connection_host(db_path: &str, url: &str) -> bool {
-    let host = reqwest::Url::parse(url)
-        .ok()
-        .and_then(|u| u.host_str().map(|h| h.to_lowercase()));
-    let Some(host) = host else {
+    let Some(host) = url_host(url) else {
         return false;
     };
 
@@ -489,6 +840,535 @@ fn is_single_connection_host(db_path: &str, url: &str) -> bool {
         .any(|h| !h.is_empty() && h == host)
 }
 
+/// Lowercased host of `url`, the key for per-host settings.
+pub(super) fn url_host(url: &str) -> Option<String> {
+    reqwest::Url::parse(url)
+        .ok()
+        .and_then(|u| u.host_str().map(|h| h.to_lowercase()))
+}
+
+/// Connection cap for `host`: its `per_host_connections` entry (which also covers
+/// subdomains), else `default_connections`. `None` leaves `max_connections` alone.
+fn host_connection_limit(db_path: &str, host: &str) -> Option<u8> {
+    let overrides = db::get_setting(db_path, "per_host_connections")
+        .ok()
+        .flatten()
+        .and_then(|v| serde_json::from_str::<HashMap<String, u8>>(&v).ok())
+        .unwrap_or_default();
+    let per_host = overrides
+        .iter()
+        .filter(|(domain, _)| {
+            let domain = domain.trim().to_lowercase();
+            host == domain
+                || host
+                    .strip_suffix(domain.as_str())
+                    .is_some_and(|prefix| prefix.ends_with('.'))
+        })
+        // The most specific entry wins.
+        .max_by_key(|(domain, _)| domain.len())
+        .map(|(_, &limit)| limit);
+
+    per_host
+        .or_else(|| {
+            db::get_setting(db_path, "default_connections")
+                .ok()
+                .flatten()
+                .and_then(|v| v.parse::<u8>().ok())
+        })
+        .filter(|&limit| limit > 0)
+}
+
+/// Connection and throughput options resolved from settings for one HTTP download.
+pub(super) struct TransportOptions {
+    pub(super) connections: u8,
+    pub(super) host_forced_single: bool,
+    pub(super) known_single_connection: bool,
+    /// Global limit, tightened by any temporary override such as gaming mode.
+    pub(super) speed_limit: u64,
+    pub(super) force_multi: bool,
+    pub(super) max_open_handles: usize,
+    pub(super) stream_read_timeout_secs: u64,
+    pub(super) adaptive_read_timeout: bool,
+    /// Checkpoint chunk bytes and re-check them before trusting resumed progress.
+    pub(super) resume_verify: bool,
+    pub(super) max_retries: u32,
+    pub(super) retry_base_ms: u64,
+    pub(super) stall_timeout_secs: u64,
+    /// Write buffer per open file in KiB (0 = built-in size).
+    pub(super) write_buffer_kb: u64,
+    pub(super) durable_writes: bool,
+}
+
+/// The download's stored `checksum` as the downloader expects it.
+pub(super) fn expected_checksum(download: &Download) -> (ChecksumAlgo, Option<String>) {
+    match download
+        .checksum
+        .as_deref()
+        .and_then(ChecksumAlgo::parse_spec)
+    {
+        Some((algo, digest)) => (algo, Some(digest)),
+        None => (ChecksumAlgo::Auto, None),
+    }
+}
+
+/// Single source of truth for how settings shape an HTTP transfer.
+pub(super) fn resolve_transport_options(
+    db_path: &str,
+    manager: &DownloadManager,
+    url: &str,
+    metadata: Option<&str>,
+    requested_connections: i32,
+) -> TransportOptions {
+    let host_forced_single = is_single_connection_host(db_path, url);
+    let known_single_connection = metadata == Some("http_no_range") || host_forced_single;
+    let configured_max_connections = db::get_setting(db_path, "max_connections")
+        .ok()
+        .flatten()
+        .and_then(|v| v.parse::<u8>().ok())
+        .unwrap_or(16)
+        .max(1);
+    let persisted_connections = (requested_connections as u8).max(1);
+    let mut effective_connections = persisted_connections.min(configured_max_connections);
+    // Host caps apply before any speed-based scaling, whatever `max_connections` allows.
+    if let Some(host) = url_host(url) {
+        if let Some(limit) = host_connection_limit(db_path, &host) {
+            effective_connections = effective_connections.min(limit);
+        }
+        if let Some(cap) = manager.throttled_host_cap(&host) {
+            effective_connections = effective_connections.min(cap);
+        }
+    }
+    let connections = if known_single_connection {
+        1
+    } else {
+        effective_connections
+    };
+
+    let speed_limit = manager.effective_speed_limit(
+        db::get_setting(db_path, "speed_limit")
+            .ok()
+            .flatten()
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(0),
+    );
+    let max_open_handles = db::get_setting(db_path, "max_open_handles")
+        .ok()
+        .flatten()
+        .and_then(|v| v.parse::<usize>().ok())
+        .unwrap_or(DEFAULT_MAX_OPEN_HANDLES);
+    let force_multi_http = db::get_setting(db_path, "force_multi_http")
+        .ok()
+        .flatten()
+        .map(|v| v == "true")
+        .unwrap_or(false);
+
+    let stream_read_timeout_secs = db::get_setting(db_path, "stream_read_timeout")
+        .ok()
+        .flatten()
+        .and_then(|v| v.parse::<u64>().ok())
+        .unwrap_or(60)
+        .max(1);
+    let adaptive_read_timeout = db::get_setting(db_path, "stream_read_timeout_adaptive")
+        .ok()
+        .flatten()
+        .map(|v| v == "true")
+        .unwrap_or(false);
+    let resume_verify = db::get_setting(db_path, "resume_verify")
+        .ok()
+        .flatten()
+        .map(|v| v == "true")
+        .unwrap_or(false);
+    let max_retries = db::get_setting(db_path, "max_retries")
+        .ok()
+        .flatten()
+        .and_then(|v| v.parse::<u32>().ok())
+        .unwrap_or(5)
+        .max(1);
+    // `retry_delay` is stored in seconds.
+    let retry_base_ms = db::get_setting(db_path, "retry_delay")
+        .ok()
+        .flatten()
+        .and_then(|v| v.parse::<u64>().ok())
+        .unwrap_or(5)
+        .saturating_mul(1000);
+    let stall_timeout_secs = db::get_setting(db_path, "stall_timeout")
+        .ok()
+        .flatten()
+        .and_then(|v| v.parse::<u64>().ok())
+        .unwrap_or(20);
+    let write_buffer_kb = db::get_setting(db_path, "write_buffer_kb")
+        .ok()
+        .flatten()
+        .and_then(|v| v.parse::<u64>().ok())
+        .unwrap_or(0)
+        .min(MAX_WRITE_BUFFER_KB);
+    let durable_writes = db::get_setting(db_path, "durable_writes")
+        .ok()
+        .flatten()
+        .map(|v| v == "true")
+        .unwrap_or(false);
+
+    TransportOptions {
+        connections,
+        host_forced_single,
+        known_single_connection,
+        speed_limit,
+        force_multi: force_multi_http && !known_single_connection && connections > 1,
+        max_open_handles,
+        stream_read_timeout_secs,
+        adaptive_read_timeout,
+        resume_verify,
+        max_retries,
+        retry_base_ms,
+        stall_timeout_secs,
+        write_buffer_kb,
+        durable_writes,
+    }
+}
+
+/// Falls back to cookies extracted from the configured browser when none were given.
+fn resolve_cookies(db_path: &str, url: &str, cookies: Option<String>) -> Option<String> {
+    if cookies.as_ref().map(|s| !s.is_empty()).unwrap_or(false) {
+        return cookies;
+    }
+    match db::get_setting(db_path, "cookie_browser") {
+        Ok(Some(browser)) if browser != "none" => get_cookies_from_browser(&browser, url),
+        _ => cookies,
+    }
+}
+
+/// Picks the download's own proxy, or the global `proxy_url` setting if it has none.
+fn resolve_proxy(db_path: &str, proxy: Option<String>) -> Option<String> {
+    proxy
+        .filter(|p| !p.trim().is_empty())
+        .or_else(|| db::get_setting(db_path, "proxy_url").ok().flatten())
+        .map(|p| p.trim().to_string())
+        .filter(|p| !p.is_empty())
+}
+
+/// Decodes the requested filename and places it in the target folder.
+///
+/// Returns `(filename, filepath)`; the path is not yet made unique.
+fn resolve_target_path<R: Runtime>(
+    app: &AppHandle<R>,
+    db_path: &str,
+    filename: String,
+    output_folder: Option<String>,
+) -> (String, String) {
+    let mut filename =
+        if let Ok(decoded) = percent_encoding::percent_decode(filename.as_bytes()).decode_utf8() {
+            decoded.into_owned()
+        } else {
+            filename
+        };
+
+    if filename.is_empty() {
+        filename = "download_file".to_string();
+    }
+
+    // Finalize resolved path using the potentially updated filename and optional folder override
+    let resolved_path = resolve_download_path(app, db_path, &filename, output_folder);
+    (filename, resolved_path)
+}
+
+/// Picks a unique variant of `path` and keeps it reserved until the returned guard
+/// is dropped, which callers do once the download storing it has been inserted.
+pub(crate) async fn reserve_unique_path<'a>(
+    manager: &'a DownloadManager,
+    db_path: &str,
+    path: String,
+) -> (tokio::sync::MutexGuard<'a, ()>, String) {
+    let guard = manager.lock_paths().await;
+    (guard, ensure_unique_path(db_path, path))
+}
+
+/// The file name of the unique `path` picked for `filename`.
+fn unique_file_name(path: &str, filename: String) -> String {
+    Path::new(path)
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or(filename)
+}
+
+/// Optional per-download overrides for [`get_effective_config`].
+#[derive(serde::Deserialize, Default)]
+pub struct DownloadOverrides {
+    pub filename: Option<String>,
+    pub output_folder: Option<String>,
+    pub user_agent: Option<String>,
+    pub cookies: Option<String>,
+    pub connections: Option<i32>,
+    pub proxy: Option<String>,
+}
+
+/// The fully-resolved configuration a new HTTP download would start with.
+#[derive(serde::Serialize)]
+pub struct EffectiveDownloadConfig {
+    pub url: String,
+    pub filename: String,
+    pub filepath: String,
+    pub category: String,
+    pub connections: u8,
+    /// True if the host or a previous attempt forces single-connection mode.
+    pub single_connection: bool,
+    pub force_multi: bool,
+    /// Bytes/sec, 0 = unlimited.
+    pub speed_limit: u64,
+    pub max_open_handles: usize,
+    pub stream_read_timeout_secs: u64,
+    pub adaptive_read_timeout: bool,
+    pub resume_verify: bool,
+    pub max_retries: u32,
+    pub retry_base_ms: u64,
+    pub user_agent: String,
+    pub cookies: Option<String>,
+    pub proxy: Option<String>,
+}
+
+/// Bridge: Shows what `add_download` would use for a URL without starting anything.
+#[tauri::command]
+pub async fn get_effective_config<R: Runtime>(
+    app: AppHandle<R>,
+    db_state: State<'_, DbState>,
+    manager: State<'_, DownloadManager>,
+    url: String,
+    overrides: Option<DownloadOverrides>,
+) -> Result<EffectiveDownloadConfig, String> {
+    let overrides = overrides.unwrap_or_default();
+    let url = transform_google_drive_url(&url);
+
+    let requested_name = overrides.filename.unwrap_or_else(|| {
+        crate::downloader::extract_filename(&url, &reqwest::header::HeaderMap::new())
+    });
+    let (requested_name, resolved_path) = resolve_target_path(
+        &app,
+        &db_state.path,
+        requested_name,
+        overrides.output_folder,
+    );
+    let filepath = ensure_unique_path(&db_state.path, resolved_path);
+    let filename = unique_file_name(&filepath, requested_name);
+
+    let requested_connections = overrides.connections.unwrap_or_else(|| {
+        db::get_setting(&db_state.path, "max_connections")
+            .ok()
+            .flatten()
+            .and_then(|v| v.parse::<i32>().ok())
+            .unwrap_or(16)
+    });
+    let transport =
+        resolve_transport_options(&db_state.path, &manager, &url, None, requested_connections);
+
+    Ok(EffectiveDownloadConfig {
+        category: get_category_from_filename(&db_state.path, &filename),
+        cookies: resolve_cookies(&db_state.path, &url, overrides.cookies),
+        proxy: resolve_proxy(&db_state.path, overrides.proxy),
+        user_agent: overrides
+            .user_agent
+            .unwrap_or_else(|| crate::downloader::DEFAULT_USER_AGENT.to_string()),
+        url,
+        filename,
+        filepath,
+        connections: transport.connections,
+        single_connection: transport.known_single_connection,
+        force_multi: transport.force_multi,
+        speed_limit: transport.speed_limit,
+        max_open_handles: transport.max_open_handles,
+        stream_read_timeout_secs: transport.stream_read_timeout_secs,
+        adaptive_read_timeout: transport.adaptive_read_timeout,
+        resume_verify: transport.resume_verify,
+        max_retries: transport.max_retries,
+        retry_base_ms: transport.retry_base_ms,
+    })
+}
+
+/// Bridge: Sets (or clears, with 0) a per-download speed cap in bytes/sec.
+///
+/// Persisted for future runs and applied immediately if the download is active.
+#[tauri::command]
+pub async fn set_download_speed_limit(
+    db_state: State<'_, DbState>,
+    manager: State<'_, DownloadManager>,
+    id: String,
+    limit: u64,
+) -> Result<(), String> {
+    db::update_download_speed_limit(&db_state.path, &id, limit as i64)
+        .map_err(|e| e.to_string())?;
+
+    let global_limit = manager.effective_speed_limit(
+        db::get_setting(&db_state.path, "speed_limit")
+            .ok()
+            .flatten()
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(0),
+    );
+    manager
+        .set_download_speed_limit(&id, limit, global_limit)
+        .await;
+    Ok(())
+}
+
+/// Whether a refreshed link still serves the file the stored progress belongs to.
+///
+/// A size or validator that is unknown on either side is not treated as a change.
+fn refreshed_file_unchanged(
+    stored_size: i64,
+    stored: &ResourceValidator,
+    fresh_size: u64,
+    fresh: &ResourceValidator,
+) -> bool {
+    let size_changed = stored_size > 0 && fresh_size > 0 && fresh_size as i64 != stored_size;
+    let validator_changed = matches!(
+        (stored.if_range(), fresh.if_range()),
+        (Some(old), Some(new)) if old != new
+    );
+    !size_changed && !validator_changed
+}
+
+/// Bridge: Replaces an expired download link with a fresh one derived from its `source_page_url`.
+///
+/// The page is requested again: if it serves the file (possibly after redirects)
+/// its final URL is used; if it is a confirmation page of a host with a resolver,
+/// the link on it is. Chunk progress is kept when the new link reports the same
+/// size and validator, and discarded otherwise.
+#[tauri::command]
+pub async fn refresh_download_url<R: Runtime>(
+    app: AppHandle<R>,
+    db_state: State<'_, DbState>,
+    manager: State<'_, DownloadManager>,
+    id: String,
+) -> Result<Download, String> {
+    let downloads = db::get_all_downloads(&db_state.path).map_err(|e| e.to_string())?;
+    let mut download = downloads
+        .into_iter()
+        .find(|d| d.id == id)
+        .ok_or("Download not found")?;
+
+    match download.protocol {
+        DownloadProtocol::Http => {}
+        DownloadProtocol::Video => {
+            return Err("Video links cannot be refreshed: video downloads are not supported".into())
+        }
+        _ => return Err("Only HTTP download links can be refreshed".to_string()),
+    }
+    if download.status == DownloadStatus::Completed {
+        return Err("Download already completed".to_string());
+    }
+    if manager.is_active(&id).await {
+        return Err("Pause the download before refreshing its link".to_string());
+    }
+    let page = download
+        .source_page_url
+        .clone()
+        .ok_or("No source page was recorded for this download, so its link cannot be refreshed")?;
+
+    let request_headers = match download.headers.as_deref() {
+        Some(json) => {
+            let headers: Vec<(String, String)> =
+                serde_json::from_str(json).map_err(|e| e.to_string())?;
+            parse_headers(&headers).map_err(|e| e.to_string())?
+        }
+        None => reqwest::header::HeaderMap::new(),
+    };
+    let mut builder = reqwest::Client::builder().user_agent(
+        download
+            .user_agent
+            .clone()
+            .unwrap_or_else(|| crate::downloader::DEFAULT_USER_AGENT.to_string()),
+    );
+    let cookies = resolve_cookies(
+        &db_state.path,
+        &page,
+        secrets::reveal(download.cookies.clone()),
+    );
+    if let Some(value) = cookies.and_then(|c| reqwest::header::HeaderValue::from_str(&c).ok()) {
+        let mut headers = reqwest::header::HeaderMap::new();
+        headers.insert(reqwest::header::COOKIE, value);
+        builder = builder.default_headers(headers);
+    }
+    if let Some(proxy) = resolve_proxy(&db_state.path, download.proxy.clone()) {
+        builder = builder.proxy(parse_proxy(&proxy).map_err(|e| e.to_string())?);
+    }
+    let client = builder.build().map_err(|e| e.to_string())?;
+
+    let response = client
+        .get(&page)
+        .headers(request_headers.clone())
+        .send()
+        .await
+        .map_err(|e| format!("Could not reach the source page: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!("The source page returned {}", response.status()));
+    }
+    let final_url = response.url().to_string();
+    let is_html = response
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|ct| ct.contains("text/html"));
+    let fresh_url = if is_html {
+        let body = response.text().await.map_err(|e| e.to_string())?;
+        crate::downloader::resolve_direct_url(&final_url, &body)
+            .ok_or("The source page no longer links to the file; add it again from the page")?
+    } else {
+        // Served directly; drop the body without reading it.
+        drop(response);
+        final_url
+    };
+
+    // The stored credentials belong to the file's host, not the source page.
+    let mut probe_headers = request_headers.clone();
+    apply_basic_auth(
+        &mut probe_headers,
+        &fresh_url,
+        download.username.as_deref(),
+        secrets::reveal(download.password.clone()).as_deref(),
+    );
+    let (_, total, _, validator) =
+        crate::downloader::check_range_support(&client, &fresh_url, &probe_headers)
+            .await
+            .map_err(|e| format!("The refreshed link does not work: {}", e))?;
+    let stored = ResourceValidator {
+        etag: download.etag.clone(),
+        last_modified: download.last_modified.clone(),
+    };
+    let keep_progress = refreshed_file_unchanged(download.size, &stored, total, &validator);
+    if !keep_progress {
+        db::delete_download_chunks(&db_state.path, &id).map_err(|e| e.to_string())?;
+        db::update_download_progress(&db_state.path, &id, 0, 0).map_err(|e| e.to_string())?;
+        db::update_download_validator(&db_state.path, &id, None, None)
+            .map_err(|e| e.to_string())?;
+        let part = crate::downloader::part_file_path(Path::new(&download.filepath));
+        let _ = fs::remove_file(part);
+        download.downloaded = 0;
+        download.etag = None;
+        download.last_modified = None;
+    }
+
+    db::update_download_url(&db_state.path, &id, &fresh_url).map_err(|e| e.to_string())?;
+    if download.status == DownloadStatus::Error {
+        db::update_download_status(&db_state.path, &id, DownloadStatus::Paused)
+            .map_err(|e| e.to_string())?;
+        download.status = DownloadStatus::Paused;
+        download.error_message = None;
+    }
+    db::log_event(
+        &db_state.path,
+        &id,
+        "link_refreshed",
+        Some(if keep_progress {
+            "Link refreshed; progress kept"
+        } else {
+            "Link refreshed; the file changed, so it restarts from zero"
+        }),
+    )
+    .ok();
+    emit_download_update(&app, &db_state.path, &id);
+
+    download.url = fresh_url;
+    Ok(download)
+}
+
 /// Bridge: Initiates a new HTTP download.
 ///
 /// This command:
@@ -496,6 +1376,9 @@ fn is_single_connection_host(db_path: &str, url: &str) -> bool {
 /// 2. Ensures a unique path to prevent collisions.
 /// 3. Persists the record to the database.
 /// 4. Dispatches the async download task.
+///
+/// An absolute `filepath` is the path picked in the frontend's save dialog (the
+/// `ask_location` setting); it replaces `filename` and `output_folder`.
 #[tauri::command]
 pub async fn add_download<R: Runtime>(
     app: AppHandle<R>,
@@ -504,23 +1387,213 @@ pub async fn add_download<R: Runtime>(
     torrent_manager: State<'_, TorrentManager>,
     url: String,
     filename: String,
-    _filepath: String,
+    filepath: String,
     output_folder: Option<String>,
     user_agent: Option<String>,
-    mut cookies: Option<String>,
+    cookies: Option<String>,
     size: Option<u64>,
     start_paused: Option<bool>,
+    proxy: Option<String>,
+    mirrors: Option<Vec<String>>,
+    checksum: Option<String>,
+    source_page_url: Option<String>,
+    username: Option<String>,
+    password: Option<String>,
+) -> Result<Download, String> {
+    // An absolute path is used as-is by `resolve_download_path`, skipping `auto_organize`.
+    let chosen = filepath.trim();
+    let (filename, output_folder) = if Path::new(chosen).is_absolute() {
+        remember_chosen_dir(&db_state.path, Path::new(chosen));
+        (chosen.to_string(), None)
+    } else {
+        (filename, output_folder)
+    };
+    create_http_download(
+        app,
+        db_state,
+        manager,
+        torrent_manager,
+        NewHttpDownload {
+            url,
+            filename,
+            output_folder,
+            user_agent,
+            cookies,
+            size,
+            start_paused,
+            proxy,
+            headers: None,
+            mirrors,
+            checksum,
+            source_page_url,
+            username,
+            password,
+        },
+    )
+    .await
+}
+
+/// Bridge: Same as `add_download`, plus custom request headers such as
+/// `Referer` or `Authorization`.
+///
+/// Headers are validated before anything is persisted.
+#[tauri::command]
+pub async fn add_download_with_headers<R: Runtime>(
+    app: AppHandle<R>,
+    db_state: State<'_, DbState>,
+    manager: State<'_, DownloadManager>,
+    torrent_manager: State<'_, TorrentManager>,
+    url: String,
+    filename: String,
+    output_folder: Option<String>,
+    user_agent: Option<String>,
+    cookies: Option<String>,
+    size: Option<u64>,
+    start_paused: Option<bool>,
+    proxy: Option<String>,
+    headers: Vec<(String, String)>,
+    mirrors: Option<Vec<String>>,
+    checksum: Option<String>,
+    source_page_url: Option<String>,
+    username: Option<String>,
+    password: Option<String>,
+) -> Result<Download, String> {
+    create_http_download(
+        app,
+        db_state,
+        manager,
+        torrent_manager,
+        NewHttpDownload {
+            url,
+            filename,
+            output_folder,
+            user_agent,
+            cookies,
+            size,
+            start_paused,
+            proxy,
+            headers: Some(headers),
+            mirrors,
+            checksum,
+            source_page_url,
+            username,
+            password,
+        },
+    )
+    .await
+}
+
+/// User-supplied options for a new HTTP download.
+///
+/// Internal callers set what they know and leave the rest to `Default`.
+#[derive(Default)]
+pub(crate) struct NewHttpDownload {
+    pub(crate) url: String,
+    pub(crate) filename: String,
+    pub(crate) output_folder: Option<String>,
+    pub(crate) user_agent: Option<String>,
+    pub(crate) cookies: Option<String>,
+    pub(crate) size: Option<u64>,
+    pub(crate) start_paused: Option<bool>,
+    pub(crate) proxy: Option<String>,
+    pub(crate) headers: Option<Vec<(String, String)>>,
+    /// Alternate URLs for the same file.
+    pub(crate) mirrors: Option<Vec<String>>,
+    /// Expected digest as `algo:hex` or bare hex.
+    pub(crate) checksum: Option<String>,
+    /// Page the link was copied from; defaults to a `Referer` header.
+    pub(crate) source_page_url: Option<String>,
+    /// HTTP Basic auth; taken from the URL's `user:password@` when not given.
+    pub(crate) username: Option<String>,
+    pub(crate) password: Option<String>,
+}
+
+/// Shared body of the `add_download*` commands, also used to add links found by
+/// URL import and the watch folder.
+pub(crate) async fn create_http_download<R: Runtime>(
+    app: AppHandle<R>,
+    db_state: State<'_, DbState>,
+    manager: State<'_, DownloadManager>,
+    torrent_manager: State<'_, TorrentManager>,
+    request: NewHttpDownload,
 ) -> Result<Download, String> {
+    let NewHttpDownload {
+        url,
+        filename,
+        output_folder,
+        user_agent,
+        cookies,
+        size,
+        start_paused,
+        proxy,
+        headers,
+        mirrors,
+        checksum,
+        source_page_url,
+        username,
+        password,
+    } = request;
     let url = transform_google_drive_url(&url);
+    let protocol = if is_ftp_url(&url) {
+        DownloadProtocol::Ftp
+    } else {
+        DownloadProtocol::Http
+    };
+    let username = username.filter(|u| !u.is_empty());
+    // Credentials are stored apart (the password in the keyring), so the URL shown,
+    // logged and handed to hooks holds no password. FTP logs in with them too.
+    let (url, username, password) = match split_url_credentials(&url) {
+        (url, Some((user, pass))) if username.is_none() => (url, Some(user), Some(pass)),
+        (url, _) => (url, username, password),
+    };
 
-    // Automatically fetch cookies if a browser is selected in settings and none provided
-    if cookies.is_none() || cookies.as_ref().map(|s| s.is_empty()).unwrap_or(false) {
-        if let Ok(Some(browser)) = db::get_setting(&db_state.path, "cookie_browser") {
-            if browser != "none" {
-                cookies = get_cookies_from_browser(&browser, &url);
+    // Reject a bad proxy or header up front instead of failing once the transfer starts.
+    let proxy = proxy.filter(|p| !p.trim().is_empty());
+    if let Some(ref p) = proxy {
+        parse_proxy(p).map_err(|e| e.to_string())?;
+    }
+    let referer = headers.as_ref().and_then(|headers| {
+        headers
+            .iter()
+            .find(|(name, _)| name.trim().eq_ignore_ascii_case("referer"))
+            .map(|(_, value)| value.trim().to_string())
+    });
+    let source_page_url = source_page_url
+        .map(|u| u.trim().to_string())
+        .or(referer)
+        .filter(|u| !u.is_empty());
+    let headers = match headers.filter(|h| !h.is_empty()) {
+        Some(headers) => {
+            parse_headers(&headers).map_err(|e| e.to_string())?;
+            Some(serde_json::to_string(&headers).map_err(|e| e.to_string())?)
+        }
+        None => None,
+    };
+    let mirrors = match mirrors.filter(|m| !m.is_empty()) {
+        Some(mirrors) => {
+            for mirror in &mirrors {
+                let valid = url::Url::parse(mirror)
+                    .map(|u| matches!(u.scheme(), "http" | "https"))
+                    .unwrap_or(false);
+                if !valid {
+                    return Err(format!("Invalid mirror URL: {}", mirror));
+                }
             }
+            Some(serde_json::to_string(&mirrors).map_err(|e| e.to_string())?)
         }
-    }
+        None => None,
+    };
+    let checksum = match checksum.filter(|c| !c.trim().is_empty()) {
+        Some(spec) => {
+            let (algo, digest) = ChecksumAlgo::parse_spec(&spec)
+                .ok_or_else(|| format!("Invalid checksum: {}", spec.trim()))?;
+            Some(format!("{}:{}", algo.as_str(), digest))
+        }
+        None => None,
+    };
+
+    // Automatically fetch cookies if a browser is selected in settings and none provided
+    let cookies = resolve_cookies(&db_state.path, &url, cookies);
 
     // Get max connections from settings
     let max_connections = db::get_setting(&db_state.path, "max_connections")
@@ -531,43 +1604,28 @@ pub async fn add_download<R: Runtime>(
 
     // Streamline: No synchronous sniffing here.
     // The Downloader will handle metadata discovery in the background to prevent UI lag.
-    let mut filename =
-        if let Ok(decoded) = percent_encoding::percent_decode(filename.as_bytes()).decode_utf8() {
-            decoded.into_owned()
-        } else {
-            filename
-        };
-
-    if filename.is_empty() {
-        filename = "download_file".to_string();
-    }
+    // The path stays reserved until the row is inserted, so a concurrent add picks another.
+    let (filename, resolved_path) =
+        resolve_target_path(&app, &db_state.path, filename, output_folder);
+    let (paths_guard, final_resolved_path) =
+        reserve_unique_path(&manager, &db_state.path, resolved_path).await;
+    let final_filename = unique_file_name(&final_resolved_path, filename);
 
-    // Finalize resolved path using the potentially updated filename and optional folder override
-    let resolved_path = resolve_download_path(&app, &db_state.path, &filename, output_folder);
-    let final_resolved_path = ensure_unique_path(&db_state.path, resolved_path);
-
-    // Extract the final unique filename from the path
-    let final_filename = Path::new(&final_resolved_path)
-        .file_name()
-        .map(|n| n.to_string_lossy().to_string())
-        .unwrap_or_else(|| filename.clone());
+    // Over the monthly data cap, new downloads are kept but not started.
+    let start_paused =
+        Some(start_paused.unwrap_or(false) || bandwidth::cap_reached(&app, &db_state.path));
 
     // Queue enforcement: Check if we can start immediately or must queue
-    let max_simultaneous = db::get_setting(&db_state.path, "max_concurrent")
-        .ok()
-        .flatten()
-        .and_then(|v| v.parse::<usize>().ok())
-        .unwrap_or(3);
-
-    let active_count = manager.active_downloads.lock().await.len();
-    let (torrent_active, _) = torrent_manager.get_global_status().await;
-    let should_queue =
-        !start_paused.unwrap_or(false) && (active_count + torrent_active) >= max_simultaneous;
+    let _queue = manager.lock_queue().await;
+    let should_queue = !start_paused.unwrap_or(false)
+        && queue::slots_in_use(&db_state.path, &manager, &torrent_manager).await
+            >= queue::max_concurrent(&db_state.path);
 
     let id = uuid::Uuid::new_v4().to_string();
     let download = Download {
         id: id.clone(),
         url: url.clone(),
+        category: get_category_from_filename(&db_state.path, &final_filename),
         filename: final_filename,
         filepath: final_resolved_path,
         size: size.unwrap_or(0) as i64,
@@ -579,7 +1637,7 @@ pub async fn add_download<R: Runtime>(
         } else {
             DownloadStatus::Downloading
         },
-        protocol: DownloadProtocol::Http,
+        protocol,
         speed: 0,
         connections: max_connections,
         created_at: chrono::Utc::now().to_rfc3339(),
@@ -588,11 +1646,22 @@ pub async fn add_download<R: Runtime>(
         info_hash: None,
         metadata: None,
         user_agent,
-        cookies,
-        category: get_category_from_filename(&filename),
+        cookies: secrets::protect(&id, Secret::Cookies, cookies),
+        priority: db::get_next_priority(&db_state.path).unwrap_or(0),
+        speed_limit: 0,
+        proxy,
+        headers,
+        etag: None,
+        last_modified: None,
+        mirrors,
+        checksum,
+        source_page_url,
+        username,
+        password: secrets::protect(&id, Secret::Password, password),
     };
 
     db::insert_download(&db_state.path, &download).map_err(|e| e.to_string())?;
+    drop(paths_guard);
     db::log_event(
         &db_state.path,
         &download.id,
@@ -606,25 +1675,47 @@ pub async fn add_download<R: Runtime>(
         }),
     )
     .ok();
+    emit_download_update(&app, &db_state.path, &download.id);
 
     // Only start if not paused and not queued
     if !start_paused.unwrap_or(false) && !should_queue {
-        start_download_task(
-            app,
-            db_state.path.clone(),
-            manager.inner().clone(),
-            download.clone(),
-        )
-        .await?;
+        let db_path = db_state.path.clone();
+        let manager = manager.inner().clone();
+        if download.protocol == DownloadProtocol::Ftp {
+            super::ftp::start_ftp_task(app, db_path, manager, download.clone()).await?;
+        } else {
+            start_download_task(app, db_path, manager, download.clone()).await?;
+        }
     }
 
     Ok(download)
 }
 
+/// Sends the queued HTTP/FTP progress as one `downloads-progress` event (an array)
+/// every [`PROGRESS_EMIT_INTERVAL`], so IPC load no longer grows with the number
+/// of running downloads.
+pub(crate) fn start_progress_emitter<R: Runtime>(app: AppHandle<R>) {
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(PROGRESS_EMIT_INTERVAL);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+        loop {
+            interval.tick().await;
+            let manager = app.state::<DownloadManager>();
+            let mut batch = manager.take_progress();
+            // Updates queued after a pause or cancel would flip the row back to downloading.
+            let active = manager.active_ids().await;
+            batch.retain(|progress| active.contains(&progress.id));
+            if !batch.is_empty() {
+                let _ = app.emit("downloads-progress", batch);
+            }
+        }
+    });
+}
+
 /// Internal: Spawns the long-running async task for an HTTP download.
 ///
 /// It sets up:
-/// - Real-time progress emission via Tauri events.
+/// - Progress batching through [`DownloadManager::queue_progress`].
 /// - Graceful cancellation handling.
 /// - Database persistence of progress and final status.
 /// - OS-level notifications on completion/failure.
@@ -637,43 +1728,41 @@ pub(super) async fn start_download_task<R: Runtime>(
     let id = download.id.clone();
     let url = download.url.clone();
     let filepath = download.filepath.clone();
-    let filename = download.filename.clone(); // Clone filename for use in tokio::spawn
-    let host_forced_single = is_single_connection_host(&db_path, &download.url);
-    let known_single_connection =
-        download.metadata.as_deref() == Some("http_no_range") || host_forced_single;
-    let configured_max_connections = db::get_setting(&db_path, "max_connections")
-        .ok()
-        .flatten()
-        .and_then(|v| v.parse::<u8>().ok())
-        .unwrap_or(16)
-        .max(1);
-    let persisted_connections = (download.connections as u8).max(1);
-    let effective_connections = persisted_connections.min(configured_max_connections);
-    let connections = if known_single_connection {
-        1
-    } else {
-        effective_connections
-    };
+    let TransportOptions {
+        connections,
+        host_forced_single,
+        known_single_connection,
+        speed_limit,
+        force_multi,
+        max_open_handles,
+        stream_read_timeout_secs,
+        adaptive_read_timeout,
+        resume_verify,
+        max_retries,
+        retry_base_ms,
+        stall_timeout_secs,
+        write_buffer_kb,
+        durable_writes,
+    } = resolve_transport_options(
+        &db_path,
+        &manager,
+        &download.url,
+        download.metadata.as_deref(),
+        download.connections,
+    );
+
+    // The download's own cap, if any, applies on top of the global limit.
+    let own_speed_limit = download.speed_limit.max(0) as u64;
+    let speed_limit = combine_speed_limits(own_speed_limit, speed_limit);
 
     // Create cancellation channel and signal
     let (tx, mut rx) = mpsc::channel(1);
     let is_cancelled = Arc::new(std::sync::atomic::AtomicBool::new(false));
-
-    // Fetch global speed limit
-    let speed_limit = db::get_setting(&db_path, "speed_limit")
-        .ok()
-        .flatten()
-        .and_then(|v| v.parse::<u64>().ok())
-        .unwrap_or(0);
-    let force_multi_http = db::get_setting(&db_path, "force_multi_http")
-        .ok()
-        .flatten()
-        .map(|v| v == "true")
-        .unwrap_or(false);
+    manager.occupy_slot(&id);
 
     // Spawn download in background
     tokio::spawn(async move {
-        let mut cookies = download.cookies.clone();
+        let mut cookies = secrets::reveal(download.cookies.clone());
 
         // Automatic Browser Cookie Extraction
         if cookies.is_none() {
@@ -682,14 +1771,25 @@ pub(super) async fn start_download_task<R: Runtime>(
                     cookies = get_cookies_from_browser(&browser, &url);
                     if let Some(ref c) = cookies {
                         // Log success and update DB so we don't have to extract every time for this link
-                        let _ = db::update_download_cookies(&db_path, &id, c);
+                        if let Some(stored) =
+                            secrets::protect(&id, Secret::Cookies, Some(c.clone()))
+                        {
+                            let _ = db::update_download_cookies(&db_path, &id, &stored);
+                        }
                     }
                 }
             }
         }
 
+        let mirrors: Vec<String> = download
+            .mirrors
+            .as_deref()
+            .and_then(|m| serde_json::from_str(m).ok())
+            .unwrap_or_default();
+        let (checksum_algo, expected_checksum) = expected_checksum(&download);
         let config = DownloadConfig {
             id: id.clone(),
+            urls: std::iter::once(url.clone()).chain(mirrors).collect(),
             url,
             filepath: PathBuf::from(filepath),
             connections,
@@ -697,12 +1797,33 @@ pub(super) async fn start_download_task<R: Runtime>(
             speed_limit,
             user_agent: download.user_agent.clone(),
             cookies,
-            force_multi: force_multi_http && !known_single_connection && connections > 1,
+            force_multi,
             size_hint: if download.size > 0 {
                 Some(download.size as u64)
             } else {
                 None
             },
+            stream_read_timeout_secs,
+            adaptive_read_timeout,
+            resume_verify,
+            max_retries,
+            retry_base_ms,
+            stall_timeout_secs,
+            write_buffer_kb,
+            durable_writes,
+            proxy: resolve_proxy(&db_path, download.proxy.clone()),
+            headers: download
+                .headers
+                .as_deref()
+                .and_then(|h| serde_json::from_str(h).ok()),
+            username: download.username.clone(),
+            password: secrets::reveal(download.password.clone()),
+            validator: ResourceValidator {
+                etag: download.etag.clone(),
+                last_modified: download.last_modified.clone(),
+            },
+            expected_checksum,
+            checksum_algo,
         };
 
     if known_single_connection {
@@ -719,27 +1840,64 @@ pub(super) async fn start_download_task<R: Runtime>(
         }
     }
 
-        let downloader = Downloader::new(config)
+        let downloader = match Downloader::new(config) {
+            Ok(downloader) => downloader,
+            Err(e) => {
+                set_and_emit_download_error(&app, &db_path, &id, &e.to_string());
+                release_slot(&app, &manager, &id).await;
+                return;
+            }
+        };
+        let downloader = downloader
             .with_db(db_path.clone())
-            .with_cancel_signal(is_cancelled.clone()); // Pass signal
+            .with_cancel_signal(is_cancelled.clone()) // Pass signal
+            .with_handle_permits(manager.handle_permits(max_open_handles));
 
         let progress_obj = downloader.get_progress();
         manager
-            .add_active(id.clone(), tx, progress_obj.clone())
+            .add_active(
+                id.clone(),
+                tx,
+                progress_obj.clone(),
+                downloader.get_rate_limiter(),
+                own_speed_limit,
+            )
             .await;
 
         let id_inner = id.clone();
         let db_path_inner = db_path.clone();
         let app_clone = app.clone();
-        let filename_inner = filename.clone(); // Clone filename for notification
+        let usage = app.state::<BandwidthMeter>().recorder();
+        // With `detailed_progress`, chunk state read from the DB follows at most once a second.
+        let detailed_progress = db::get_setting(&db_path, "detailed_progress")
+            .ok()
+            .flatten()
+            .map(|v| v == "true")
+            .unwrap_or(false);
+        let db_path_progress = db_path.clone();
+        let last_chunk_emit = std::sync::Mutex::new(std::time::Instant::now());
+        let manager_progress = manager.clone();
 
         // Wrap download in a select to handle cancellation
         let download_task = downloader.download(move |progress| {
-            let _ = app_clone.emit("download-progress", progress);
+            usage.observe(progress.downloaded);
+            if detailed_progress {
+                let mut last = last_chunk_emit.lock().unwrap();
+                if last.elapsed() >= std::time::Duration::from_secs(1) {
+                    *last = std::time::Instant::now();
+                    if let Ok(chunks) =
+                        read_chunk_progress(&db_path_progress, &progress.id, progress.connections)
+                    {
+                        let _ = app_clone.emit("download-chunk-progress", chunks);
+                    }
+                }
+            }
+            manager_progress.queue_progress(progress);
         });
+        tokio::pin!(download_task);
 
         tokio::select! {
-            res = download_task => {
+            res = &mut download_task => {
                 match res {
                     Ok(_) => {
                         // Get final stats from downloader if possible to ensure DB is accurate
@@ -771,43 +1929,154 @@ pub(super) async fn start_download_task<R: Runtime>(
                             );
                         }
 
+                        let download_clone = adopt_discovered_filename(&db_path_inner, &manager, download.clone()).await;
+                        let download_clone = organize_completed_download(&app, &db_path_inner, download_clone).await;
                         let _ = db::mark_download_completed(&db_path_inner, &id_inner);
+                        emit_download_update(&app, &db_path_inner, &id_inner);
                         let _ = app.emit("download-completed", id_inner.clone());
 
-                        // Native Notification
-                        app.notification()
-                            .builder()
-                            .title("Download Completed")
-                            .body(format!("{} has finished downloading successfully.", filename_inner))
-                            .show().ok();
-
                         // Post-Download Actions
-                        let download_clone = download.clone();
                         execute_post_download_actions(app.clone(), db_path_inner.clone(), download_clone).await;
                     }
                     Err(e) => {
                         let err_msg = e.to_string();
                         set_and_emit_download_error(&app, &db_path_inner, &id_inner, &err_msg);
-
-                        // Native Notification
-                        app.notification()
-                            .builder()
-                            .title("Download Failed")
-                            .body(format!("Failed to download {}: {}", filename_inner, e))
-                            .show().ok();
                     }
                 }
             }
             _ = rx.recv() => {
                 // Signal cancellation to workers
                 is_cancelled.store(true, std::sync::atomic::Ordering::Relaxed);
+                // Let the workers flush their buffers and checkpoint every chunk, so
+                // the resume continues from the last byte written.
+                if tokio::time::timeout(PAUSE_FLUSH_TIMEOUT, &mut download_task).await.is_err() {
+                    tracing::error!("[{}] Workers did not stop within {}s of pausing", id_inner, PAUSE_FLUSH_TIMEOUT.as_secs());
+                }
                 let _ = db::update_download_status(&db_path_inner, &id_inner, DownloadStatus::Paused);
+                emit_download_update(&app, &db_path_inner, &id_inner);
                 let _ = app.emit("download-paused", id_inner.clone());
             }
         }
 
-        manager.remove_active(&id_inner).await;
+        // Later downloads from a host that kept throttling start at the count it tolerated.
+        if let (Some(cap), Some(host)) = (
+            downloader.throttled_connection_cap(),
+            url_host(&download.url),
+        ) {
+            tracing::info!(
+                "[{}] {} throttled repeatedly; capping it at {} connections this session",
+                id_inner,
+                host,
+                cap
+            );
+            manager.record_throttled_host(&host, cap);
+        }
+
+        release_slot(&app, &manager, &id_inner).await;
     });
 
     Ok(())
 }
+
+/// Internal: Unregisters a finished transfer and lets the queue promote the next download.
+///
+/// `queue-slot-freed` fires only after the slot is released, so the queue processor
+/// never sees the finishing download as still running.
+pub(super) async fn release_slot<R: Runtime>(
+    app: &AppHandle<R>,
+    manager: &DownloadManager,
+    id: &str,
+) {
+    manager.remove_active(id).await;
+    let _ = app.emit("queue-slot-freed", id.to_string());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn validator(etag: Option<&str>, last_modified: Option<&str>) -> ResourceValidator {
+        ResourceValidator {
+            etag: etag.map(str::to_string),
+            last_modified: last_modified.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn refreshed_link_keeps_progress_for_the_same_file() {
+        let v1 = validator(Some("\"v1\""), None);
+        assert!(refreshed_file_unchanged(1000, &v1, 1000, &v1));
+    }
+
+    #[test]
+    fn refreshed_link_restarts_when_the_size_changes() {
+        let v1 = validator(Some("\"v1\""), None);
+        assert!(!refreshed_file_unchanged(1000, &v1, 1001, &v1));
+    }
+
+    #[test]
+    fn refreshed_link_restarts_when_the_validator_changes() {
+        let stored = validator(Some("\"v1\""), None);
+        let fresh = validator(Some("\"v2\""), None);
+        assert!(!refreshed_file_unchanged(1000, &stored, 1000, &fresh));
+
+        let stored = validator(None, Some("Mon, 01 Jan 2024 00:00:00 GMT"));
+        let fresh = validator(None, Some("Tue, 02 Jan 2024 00:00:00 GMT"));
+        assert!(!refreshed_file_unchanged(1000, &stored, 1000, &fresh));
+    }
+
+    #[test]
+    fn refreshed_link_ignores_unknown_size_and_validators() {
+        let v1 = validator(Some("\"v1\""), None);
+        let none = ResourceValidator::default();
+        assert!(refreshed_file_unchanged(0, &v1, 1000, &v1));
+        assert!(refreshed_file_unchanged(1000, &v1, 0, &v1));
+        assert!(refreshed_file_unchanged(1000, &none, 1000, &v1));
+        assert!(refreshed_file_unchanged(1000, &v1, 1000, &none));
+    }
+
+    /// Reserves a path for `file.zip` and stores it, as `create_http_download` does.
+    async fn reserve(manager: &DownloadManager, db_path: &str, dir: &Path) -> String {
+        let requested = dir.join("file.zip").to_string_lossy().to_string();
+        let (_paths, path) = reserve_unique_path(manager, db_path, requested).await;
+        // Give the other add a chance to run between choosing and storing.
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        db::open_db(db_path)
+            .unwrap()
+            .execute(
+                "INSERT INTO downloads (id, url, filename, filepath, created_at) VALUES (?1, '', 'file.zip', ?2, '')",
+                rusqlite::params![uuid::Uuid::new_v4().to_string(), path],
+            )
+            .unwrap();
+        path
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn concurrent_adds_of_the_same_name_get_distinct_paths() {
+        let dir = std::env::temp_dir().join(format!("ciel-test-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let db_path = dir.join("ciel.db").to_string_lossy().to_string();
+        db::init_db(&db_path).unwrap();
+        let manager = DownloadManager::new();
+
+        let (first, second) = tokio::join!(
+            reserve(&manager, &db_path, &dir),
+            reserve(&manager, &db_path, &dir)
+        );
+        assert_ne!(first, second);
+        let mut names: Vec<_> = [&first, &second]
+            .iter()
+            .filter_map(|p| Path::new(p).file_name())
+            .collect();
+        names.sort();
+        assert_eq!(names, ["file (1).zip", "file.zip"]);
+        std::fs::remove_dir_all(dir).ok();
+    }
+
+    #[test]
+    fn refreshed_link_compares_last_modified_when_etags_are_weak() {
+        let stored = validator(Some("W/\"a\""), Some("Mon, 01 Jan 2024 00:00:00 GMT"));
+        let fresh = validator(Some("W/\"b\""), Some("Mon, 01 Jan 2024 00:00:00 GMT"));
+        assert!(refreshed_file_unchanged(1000, &stored, 1000, &fresh));
+    }
+}
@@ -0,0 +1,131 @@
+use super::http::{self, DownloadManager};
+use crate::clipboard::is_valid_url;
+use crate::db::{self, DbState, Download};
+use crate::torrent::TorrentManager;
+use tauri::{AppHandle, Emitter, Manager, Runtime, State};
+
+/// A line of an imported list that did not become a download.
+#[derive(serde::Serialize)]
+pub struct RejectedLine {
+    /// 1-based line number in the submitted content.
+    pub line: usize,
+    pub url: String,
+    pub reason: String,
+}
+
+/// Outcome of [`import_url_list`].
+#[derive(serde::Serialize)]
+pub struct ImportSummary {
+    /// IDs of the downloads that were created, in list order.
+    pub accepted: Vec<String>,
+    pub rejected: Vec<RejectedLine>,
+}
+
+/// Bridge: Adds every link in `content` (one per line) as a download.
+///
+/// Blank lines and `#` comments are skipped. Each link goes through the same
+/// validation and duplicate check as a single add, and new downloads respect
+/// `max_concurrent`, so anything over the limit waits in the queue.
+#[tauri::command]
+pub async fn import_url_list<R: Runtime>(
+    app: AppHandle<R>,
+    db_state: State<'_, DbState>,
+    content: String,
+    category: Option<String>,
+) -> Result<ImportSummary, String> {
+    let category = category
+        .map(|c| c.trim().to_string())
+        .filter(|c| !c.is_empty());
+    let mut summary = ImportSummary {
+        accepted: Vec::new(),
+        rejected: Vec::new(),
+    };
+
+    for (index, line) in content.lines().enumerate() {
+        let url = line.trim();
+        if url.is_empty() || url.starts_with('#') {
+            continue;
+        }
+
+        match import_link(&app, url).await {
+            Ok(download) => {
+                if let Some(ref category) = category {
+                    let _ = db::update_download_category(&db_state.path, &download.id, category);
+                }
+                let _ = app.emit("download-added", download.id.clone());
+                summary.accepted.push(download.id);
+            }
+            Err(reason) => summary.rejected.push(RejectedLine {
+                line: index + 1,
+                url: url.to_string(),
+                reason,
+            }),
+        }
+    }
+
+    tracing::info!(
+        "[Import] {} link(s) added, {} rejected",
+        summary.accepted.len(),
+        summary.rejected.len()
+    );
+    Ok(summary)
+}
+
+/// Validates a single link and adds it as a torrent or HTTP/FTP download.
+async fn import_link<R: Runtime>(app: &AppHandle<R>, url: &str) -> Result<Download, String> {
+    if !is_valid_url(url) {
+        return Err("Not a recognizable link".to_string());
+    }
+    let db_state = app.state::<DbState>();
+    if db::find_download_by_url(&db_state.path, url)
+        .ok()
+        .flatten()
+        .is_some()
+    {
+        return Err("Already in the download list".to_string());
+    }
+
+    if url.starts_with("magnet:") {
+        return super::add_torrent(
+            app.clone(),
+            app.state::<DbState>(),
+            app.state::<DownloadManager>(),
+            app.state::<TorrentManager>(),
+            url.to_string(),
+            "magnet".to_string(),
+            String::new(),
+            None,
+            None,
+            None,
+            None,
+            None,
+        )
+        .await;
+    }
+
+    // Bare "host/path" links are assumed to be https.
+    let url = if url.contains("://") {
+        url.to_string()
+    } else {
+        format!("https://{}", url)
+    };
+    let info = http::validate_url_type(app.state::<DbState>(), url.clone()).await?;
+    let url = info.resolved_url.unwrap_or(url);
+    let filename = info.hinted_filename.unwrap_or_else(|| {
+        crate::downloader::extract_filename(&url, &reqwest::header::HeaderMap::new())
+    });
+
+    http::create_http_download(
+        app.clone(),
+        app.state::<DbState>(),
+        app.state::<DownloadManager>(),
+        app.state::<TorrentManager>(),
+        http::NewHttpDownload {
+            url,
+            filename,
+            size: info.content_length,
+            ..Default::default()
+        },
+    )
+    .await
+}
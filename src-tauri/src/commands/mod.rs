@@ -1,15 +1,26 @@
+pub mod backup;
+pub mod category;
+pub mod checksum;
+pub mod chunks;
+pub mod ftp;
+pub mod health;
 pub mod http;
-mod queue;
+pub mod import;
+pub mod queue;
+pub mod resume_state;
+pub mod stats;
 pub mod torrent;
 
 pub use http::{add_download, validate_url_type, DownloadManager, UrlTypeInfo};
-pub use queue::process_queue;
+pub use queue::{process_queue, resume_interrupted};
 pub use torrent::{add_torrent, analyze_torrent, start_selective_torrent};
 
 use crate::db::{self, DbState, Download, DownloadProtocol, DownloadStatus};
 use crate::torrent::TorrentManager;
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
 use tauri::{AppHandle, Emitter, Manager, Runtime, State};
 
 use self::torrent::parse_optional_torrent_indices_metadata;
@@ -31,31 +42,9 @@ pub(crate) fn resolve_download_path<R: Runtime>(
         return provided_path.to_string();
     }
 
-    let base_dir = if let Some(folder) = override_folder {
-        PathBuf::from(folder)
-    } else {
-        // Get configured download path
-        let configured_path = db::get_setting(db_path, "download_path")
-            .unwrap_or(None)
-            .unwrap_or_default();
-
-        if !configured_path.is_empty() {
-            let path = PathBuf::from(&configured_path);
-            if path.is_absolute() {
-                path
-            } else {
-                app.path()
-                    .download_dir()
-                    .unwrap_or_else(|_| PathBuf::from("."))
-                    .join(path)
-            }
-        } else {
-            // Fallback to system's downloads folder via Tauri
-            app.path()
-                .download_dir()
-                .unwrap_or_else(|_| PathBuf::from("."))
-                .join("Ciel Downloads")
-        }
+    let base_dir = match override_folder {
+        Some(folder) => PathBuf::from(folder),
+        None => download_base_dir(app, db_path),
     };
 
     // --- START AUTO-ORGANIZE LOGIC ---
@@ -65,16 +54,11 @@ pub(crate) fn resolve_download_path<R: Runtime>(
         .unwrap_or(false);
 
     let base_dir = if auto_organize {
-        let category = get_category_from_filename(
-            p.file_name()
-                .unwrap_or_default()
-                .to_str()
-                .unwrap_or_default(),
-        );
-        if category != "Other" {
-            base_dir.join(category)
-        } else {
-            base_dir
+        let rules = category::load_category_rules(db_path);
+        let filename = p.file_name().unwrap_or_default().to_string_lossy();
+        match category::match_category_rule(&rules, &filename) {
+            Some(rule) => base_dir.join(rule.folder()),
+            None => base_dir,
         }
     } else {
         base_dir
@@ -101,6 +85,90 @@ pub(crate) fn resolve_download_path<R: Runtime>(
     absolute_path.to_string_lossy().to_string()
 }
 
+/// The configured `download_path`, or the system's downloads folder as a fallback.
+pub(crate) fn download_base_dir<R: Runtime>(app: &tauri::AppHandle<R>, db_path: &str) -> PathBuf {
+    // Get configured download path
+    let configured_path = db::get_setting(db_path, "download_path")
+        .unwrap_or(None)
+        .unwrap_or_default();
+
+    if !configured_path.is_empty() {
+        let path = PathBuf::from(&configured_path);
+        if path.is_absolute() {
+            path
+        } else {
+            app.path()
+                .download_dir()
+                .unwrap_or_else(|_| PathBuf::from("."))
+                .join(path)
+        }
+    } else {
+        // Fallback to system's downloads folder via Tauri
+        app.path()
+            .download_dir()
+            .unwrap_or_else(|_| PathBuf::from("."))
+            .join("Ciel Downloads")
+    }
+}
+
+/// Setting listing the folders picked in save dialogs, as a JSON array of paths.
+const CHOSEN_DIRS_SETTING: &str = "chosen_download_dirs";
+
+fn chosen_download_dirs(db_path: &str) -> Vec<PathBuf> {
+    db::get_setting(db_path, CHOSEN_DIRS_SETTING)
+        .ok()
+        .flatten()
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
+/// Remembers the folder of a path picked in the save dialog as a download folder,
+/// so the file and its `.ciel-part` file can be deleted with the download.
+pub(crate) fn remember_chosen_dir(db_path: &str, path: &Path) {
+    // A filesystem root is never trusted as a whole.
+    let Some(dir) = path.parent().filter(|dir| dir.parent().is_some()) else {
+        return;
+    };
+    let mut dirs = chosen_download_dirs(db_path);
+    if dirs.iter().any(|known| known == dir) {
+        return;
+    }
+    dirs.push(dir.to_path_buf());
+    if let Ok(json) = serde_json::to_string(&dirs) {
+        let _ = db::set_setting(db_path, CHOSEN_DIRS_SETTING, &json);
+    }
+}
+
+/// Whether `path` lies inside a known download directory (the configured
+/// `download_path`, the system downloads folder or a folder picked in the save
+/// dialog), never being one itself.
+///
+/// Guards file deletion against paths that were tampered with in the database.
+pub(crate) fn is_within_download_dir<R: Runtime>(
+    app: &tauri::AppHandle<R>,
+    db_path: &str,
+    path: &Path,
+) -> bool {
+    // The file itself may be gone, so resolve its folder instead.
+    let (Some(parent), Some(name)) = (path.parent(), path.file_name()) else {
+        return false;
+    };
+    let Ok(parent) = parent.canonicalize() else {
+        return false;
+    };
+    let resolved = parent.join(name);
+
+    let mut known = vec![download_base_dir(app, db_path)];
+    if let Ok(system) = app.path().download_dir() {
+        known.push(system);
+    }
+    known.extend(chosen_download_dirs(db_path));
+    known
+        .iter()
+        .filter_map(|dir| dir.canonicalize().ok())
+        .any(|dir| resolved.starts_with(&dir) && resolved != dir)
+}
+
 /// Prevents file overwriting by appending a numeric suffix (e.g., "file (1).txt")
 /// if a collision is detected on the disk OR in the database.
 pub(crate) fn ensure_unique_path(db_path: &str, path_str: String) -> String {
@@ -137,24 +205,26 @@ pub(crate) fn ensure_unique_path(db_path: &str, path_str: String) -> String {
     }
 }
 
-/// Map file extensions to broad categories for UI filtering.
-pub fn get_category_from_filename(filename: &str) -> String {
-    let path = Path::new(filename);
-    let extension = path
-        .extension()
-        .and_then(|s| s.to_str())
-        .unwrap_or("")
-        .to_lowercase();
+/// Map file extensions to broad categories for UI filtering, using the `category_rules` setting.
+pub fn get_category_from_filename(db_path: &str, filename: &str) -> String {
+    let rules = category::load_category_rules(db_path);
+    category::match_category_rule(&rules, filename)
+        .map(|rule| rule.category.clone())
+        .unwrap_or_else(|| category::FALLBACK_CATEGORY.to_string())
+}
 
-    match extension.as_str() {
-        "mp4" | "mkv" | "avi" | "mov" | "webm" | "flv" | "wmv" | "m4v" => "Video".to_string(),
-        "mp3" | "wav" | "flac" | "aac" | "ogg" | "m4a" | "wma" => "Audio".to_string(),
-        "zip" | "rar" | "7z" | "tar" | "gz" | "bz2" | "iso" => "Compressed".to_string(),
-        "exe" | "msi" | "app" | "dmg" | "deb" | "rpm" => "Software".to_string(),
-        "pdf" | "doc" | "docx" | "xls" | "xlsx" | "ppt" | "pptx" | "txt" | "rtf" | "epub" => {
-            "Documents".to_string()
-        }
-        _ => "Other".to_string(),
+/// Emits `download-updated` with the stored row of `id` after a status change.
+///
+/// Every transition (created, started, paused, queued, completed, errored) goes
+/// through here so listeners can replace their copy of the download instead of
+/// reconciling the per-transition events. Live transfer stats stay on the
+/// separate, high-frequency `download-progress` event.
+pub(crate) fn emit_download_update<R: Runtime>(app: &AppHandle<R>, db_path: &str, id: &str) {
+    let download = db::get_all_downloads(db_path)
+        .ok()
+        .and_then(|all| all.into_iter().find(|d| d.id == id));
+    if let Some(download) = download {
+        let _ = app.emit("download-updated", download);
     }
 }
 
@@ -176,6 +246,46 @@ pub(crate) fn set_and_emit_download_error<R: Runtime>(
 ) {
     let _ = db::update_download_error(db_path, id, message);
     emit_download_error_event(app, id, message);
+    emit_download_update(app, db_path, id);
+
+    if let Ok(downloads) = db::get_all_downloads(db_path) {
+        if let Some(download) = downloads.iter().find(|d| d.id == id) {
+            crate::notify::notify_error(app, db_path, &download.filename, message);
+        }
+    }
+}
+
+/// How long to wait for further completions before revealing a finished file.
+const REVEAL_WINDOW: Duration = Duration::from_secs(3);
+
+/// Path of the latest completed download waiting to be revealed by `open_folder_on_finish`.
+///
+/// Completions that land within [`REVEAL_WINDOW`] of each other open a single
+/// file-manager window, pointed at whichever finished last.
+#[derive(Default)]
+pub struct FolderReveal {
+    pending: Mutex<Option<String>>,
+}
+
+/// Queues `path` to be revealed once the current burst of completions settles.
+fn reveal_when_settled<R: Runtime>(app: &AppHandle<R>, db_path: &str, path: String) {
+    let reveal = app.state::<FolderReveal>();
+    let first_in_burst = reveal.pending.lock().unwrap().replace(path).is_none();
+    if !first_in_burst {
+        return;
+    }
+
+    let app = app.clone();
+    let db_path = db_path.to_string();
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(REVEAL_WINDOW).await;
+        let latest = app.state::<FolderReveal>().pending.lock().unwrap().take();
+        if let Some(path) = latest {
+            if let Err(e) = show_in_folder_internal(app, &db_path, path) {
+                tracing::warn!("[Finish] Cannot open download folder: {}", e);
+            }
+        }
+    });
 }
 
 /// Triggers post-transfer logic like opening the target folder or system power management.
@@ -186,6 +296,12 @@ pub(crate) async fn execute_post_download_actions<R: Runtime>(
     db_path: String,
     download: Download,
 ) {
+    // The caller's copy may predate the final size and path; prefer the stored row.
+    let download = db::get_all_downloads(&db_path)
+        .ok()
+        .and_then(|all| all.into_iter().find(|d| d.id == download.id))
+        .unwrap_or(download);
+
     // 1. Open Folder on Finish
     let open_folder = db::get_setting(&db_path, "open_folder_on_finish")
         .ok()
@@ -194,61 +310,38 @@ pub(crate) async fn execute_post_download_actions<R: Runtime>(
         .unwrap_or(false);
 
     if open_folder {
-        // Use the internal helper that doesn't require State
-        let _ = show_in_folder_internal(app, &db_path, download.filepath.clone());
+        reveal_when_settled(&app, &db_path, download.filepath.clone());
     }
 
-    // 2. Sound notification (handled by frontend or system toast by default, but we can add more if needed)
+    // 2. Native notification (batched, filtered by the notification settings)
+    crate::notify::notify_completed(
+        &app,
+        &db_path,
+        &download.filename,
+        download.size.max(0) as u64,
+    );
 
-    // 3. Shutdown on Finish
-    let shutdown_enabled = db::get_setting(&db_path, "shutdown_on_finish")
-        .ok()
-        .flatten()
-        .map(|v| v == "true")
-        .unwrap_or(false);
+    // 3. User hooks (command / webhook), fire-and-forget
+    crate::hooks::spawn_post_download_hooks(&db_path, &download);
 
-    if shutdown_enabled {
-        // Check if there are ANY other active downloads
-        if let Ok(downloads) = db::get_all_downloads(&db_path) {
-            let active_count = downloads
-                .iter()
-                .filter(|d| d.id != download.id) // Exclude current one as it might still be in 'Downloading' status during this call
-                .filter(|d| d.status == db::DownloadStatus::Downloading)
-                .count();
-
-            if active_count == 0 {
-                // All downloads finished! Trigger shutdown.
-                #[cfg(target_os = "windows")]
-                {
-                    let _ = std::process::Command::new("shutdown")
-                        .args(&[
-                            "/s",
-                            "/t",
-                            "60",
-                            "/c",
-                            "Ciel: All downloads finished. Shutting down in 60s.",
-                        ])
-                        .spawn();
-                }
-                #[cfg(target_os = "linux")]
-                {
-                    let _ = std::process::Command::new("shutdown").args(&["+1"]).spawn();
-                }
-                #[cfg(target_os = "macos")]
-                {
-                    let _ = std::process::Command::new("shutdown")
-                        .args(&["-h", "+1"])
-                        .spawn();
-                }
-            }
-        }
-    }
+    // 4. Shutdown / sleep / quit once the last download is done
+    crate::finish::schedule_finish_action(&app, &db_path, &download.id).await;
 }
 
 /// Bridge: Fetches the full list of downloads for the Frontend.
+///
+/// With `queue_order`, queued downloads are listed first in the order the queue
+/// will start them (see `reorder_queue`).
 #[tauri::command]
-pub fn get_downloads(db_state: State<DbState>) -> Result<Vec<Download>, String> {
-    db::get_all_downloads(&db_state.path).map_err(|e| e.to_string())
+pub fn get_downloads(
+    db_state: State<DbState>,
+    queue_order: Option<bool>,
+) -> Result<Vec<Download>, String> {
+    if queue_order.unwrap_or(false) {
+        db::get_all_downloads_queue_order(&db_state.path).map_err(|e| e.to_string())
+    } else {
+        db::get_all_downloads(&db_state.path).map_err(|e| e.to_string())
+    }
 }
 
 /// Bridge: Pauses an active transfer.
@@ -269,6 +362,12 @@ pub async fn pause_download<R: Runtime>(
         .find(|d| d.id == id)
         .ok_or("Download not found")?;
 
+    // Pausing a seeding torrent only stops the upload; the download stays completed.
+    if download.protocol == DownloadProtocol::Torrent && torrent_manager.is_seeding(&id).await {
+        torrent_manager.set_seeding(&id, false).await;
+        return Ok(());
+    }
+
     if download.protocol == DownloadProtocol::Torrent {
         torrent_manager.pause_torrent(&id).await?;
     } else {
@@ -278,6 +377,13 @@ pub async fn pause_download<R: Runtime>(
     db::log_event(&db_state.path, &id, "paused", None).ok();
     db::update_download_status(&db_state.path, &id, DownloadStatus::Paused)
         .map_err(|e| e.to_string())?;
+    emit_download_update(&app, &db_state.path, &id);
+
+    // HTTP/FTP tasks free their slot themselves once they stop; a paused torrent
+    // stops counting immediately, so promote the next download now.
+    if download.protocol == DownloadProtocol::Torrent {
+        let _ = app.emit("queue-slot-freed", id.clone());
+    }
 
     // Immediate UI Feedback
     // We construct a partial object that the frontend will merge/handle
@@ -303,6 +409,9 @@ pub async fn pause_download<R: Runtime>(
 }
 
 /// Bridge: Resumes a previously paused transfer.
+///
+/// With `rechunk`, an HTTP download's unfinished ranges are re-split for the
+/// current `max_connections` so raising the connection count adds parallelism.
 #[tauri::command]
 pub async fn resume_download<R: Runtime>(
     app: AppHandle<R>,
@@ -310,6 +419,7 @@ pub async fn resume_download<R: Runtime>(
     manager: State<'_, DownloadManager>,
     torrent_manager: State<'_, TorrentManager>,
     id: String,
+    rechunk: Option<bool>,
 ) -> Result<(), String> {
     let downloads = db::get_all_downloads(&db_state.path).map_err(|e| e.to_string())?;
     let mut download = downloads
@@ -331,20 +441,30 @@ pub async fn resume_download<R: Runtime>(
         return Err("Download already completed".to_string());
     }
 
-    // Idempotency guard for HTTP downloads:
+    // Idempotency guard for HTTP/FTP downloads:
     // if already active in memory, do not start another worker task.
-    if download.protocol == DownloadProtocol::Http && manager.is_active(&id).await {
+    let uses_manager = matches!(
+        download.protocol,
+        DownloadProtocol::Http | DownloadProtocol::Ftp
+    );
+    if uses_manager && manager.is_active(&id).await {
         // Keep DB state consistent in case it's stale.
         if download.status != DownloadStatus::Downloading {
             db::update_download_status(&db_state.path, &id, DownloadStatus::Downloading)
                 .map_err(|e| e.to_string())?;
+            emit_download_update(&app, &db_state.path, &id);
         }
         return Ok(());
     }
+    if uses_manager {
+        // A just-paused transfer may still be checkpointing its chunks.
+        manager.wait_until_stopped(&id).await;
+    }
 
     db::update_download_status(&db_state.path, &id, DownloadStatus::Downloading)
         .map_err(|e| e.to_string())?;
     db::log_event(&db_state.path, &id, "resumed", None).ok();
+    emit_download_update(&app, &db_state.path, &id);
 
     match download.protocol {
         DownloadProtocol::Torrent => {
@@ -475,6 +595,29 @@ pub async fn resume_download<R: Runtime>(
                 }
             }
         }
+        DownloadProtocol::Ftp => {
+            let _ = app.emit(
+                "download-progress",
+                serde_json::json!({
+                    "id": id,
+                    "total": download.size.max(0) as u64,
+                    "downloaded": download.downloaded.max(0) as u64,
+                    "speed": 0u64,
+                    "eta": 0u64,
+                    "connections": 1u64,
+                    "status_text": "Resuming...",
+                    "status_phase": "resuming",
+                    "phase_elapsed_secs": 0u64,
+                }),
+            );
+            ftp::start_ftp_task(
+                app,
+                db_state.path.clone(),
+                manager.inner().clone(),
+                download.clone(),
+            )
+            .await?;
+        }
         _ => {
             let known_single_connection = download.metadata.as_deref() == Some("http_no_range");
             let _ = app.emit("download-progress", serde_json::json!({
@@ -490,6 +633,9 @@ pub async fn resume_download<R: Runtime>(
                 "status_phase": if known_single_connection { "restarting" } else { "resuming" },
                 "phase_elapsed_secs": 0u64,
             }));
+            if rechunk.unwrap_or(false) && !known_single_connection {
+                http::rechunk_remaining(&db_state.path, &id, download.connections.max(1) as u8)?;
+            }
             http::start_download_task(
                 app,
                 db_state.path.clone(),
@@ -520,7 +666,8 @@ pub async fn get_download_events(
 
 /// Bridge: Permanently removes a download from the registry and aborts it if active.
 #[tauri::command]
-pub async fn delete_download(
+pub async fn delete_download<R: Runtime>(
+    app: AppHandle<R>,
     db_state: State<'_, DbState>,
     manager: State<'_, DownloadManager>,
     torrent_manager: State<'_, TorrentManager>,
@@ -532,23 +679,42 @@ pub async fn delete_download(
     let download_opt = downloads.into_iter().find(|d| d.id == id);
 
     if let Some(download) = download_opt {
+        let filepath = PathBuf::from(&download.filepath);
+        let deletable = is_within_download_dir(&app, &db_state.path, &filepath);
+        // Report a file that can't be deleted instead of silently keeping it.
+        if delete_files && !deletable && download.protocol != DownloadProtocol::Torrent {
+            return Err(format!(
+                "{} is outside the download folders, so it can't be deleted. Remove the download without deleting its file.",
+                download.filepath
+            ));
+        }
+
         // 2. Clear from DB FIRST to ensure it doesn't "ghost" back into the UI.
         // This makes the deletion feel instant to the user.
         db::delete_download_by_id(&db_state.path, &id).map_err(|e| {
             tracing::error!("Failed to delete DB record for {}: {}", id, e);
             e.to_string()
         })?;
+        crate::secrets::forget(&download);
 
         // 3. Cleanup Engine (Fire-and-forget in a background task)
         // This prevents hangs in the engine (e.g. searching for missing files) from blocking the UI.
         let tm = torrent_manager.inner().clone();
         let m = manager.inner().clone();
+        if !deletable {
+            tracing::error!(
+                "Not deleting files of {}: {} is outside the download folders",
+                id,
+                download.filepath
+            );
+        }
 
         tokio::spawn(async move {
             if download.protocol == DownloadProtocol::Torrent {
-                let _ = tm
-                    .delete_torrent(&id, delete_files, Some(download.filepath.clone()))
-                    .await;
+                // librqbit only removes the torrent's own files; the manual fallback
+                // removes whole folders, so it needs the guard.
+                let target_path = deletable.then(|| download.filepath.clone());
+                let _ = tm.delete_torrent(&id, delete_files, target_path).await;
                 if let Some(hash) = download.info_hash {
                     let _ = tm.delete_torrent_by_hash(hash, delete_files).await;
                 } else if let Some(hash) =
@@ -558,10 +724,15 @@ pub async fn delete_download(
                 }
             } else {
                 m.cancel(&id).await;
+                // Wait for the Downloader to flush and close its file handles.
+                m.wait_until_stopped(&id).await;
+                if !deletable {
+                    return;
+                }
+                // An unfinished part file is useless without its DB record.
+                let _ = std::fs::remove_file(crate::downloader::part_file_path(&filepath));
                 if delete_files {
-                    // Slight delay to ensure Downloader has flushed and closed the file handle
-                    tokio::time::sleep(tokio::time::Duration::from_millis(500)).await;
-                    let _ = std::fs::remove_file(&download.filepath);
+                    let _ = std::fs::remove_file(&filepath);
                 }
             }
         });
@@ -576,6 +747,140 @@ pub async fn delete_download(
     Ok(())
 }
 
+/// Bridge: Renames a download's file, on disk and in the registry.
+///
+/// Only paused, queued or completed HTTP/FTP downloads can be renamed, so no
+/// worker holds the file open. An unfinished `.ciel-part` file is renamed along
+/// with it. Returns the updated download.
+#[tauri::command]
+pub async fn rename_download(
+    db_state: State<'_, DbState>,
+    manager: State<'_, DownloadManager>,
+    id: String,
+    new_name: String,
+) -> Result<Download, String> {
+    let downloads = db::get_all_downloads(&db_state.path).map_err(|e| e.to_string())?;
+    let mut download = downloads
+        .into_iter()
+        .find(|d| d.id == id)
+        .ok_or("Download not found")?;
+
+    if download.protocol == DownloadProtocol::Torrent {
+        return Err("Torrent downloads cannot be renamed".to_string());
+    }
+    if !matches!(
+        download.status,
+        DownloadStatus::Paused | DownloadStatus::Queued | DownloadStatus::Completed
+    ) || manager.is_active(&id).await
+    {
+        return Err("Pause the download before renaming it".to_string());
+    }
+
+    let new_name = crate::downloader::sanitize_filename(new_name.trim());
+    if new_name == "." || new_name == ".." {
+        return Err("Invalid file name".to_string());
+    }
+    if new_name == download.filename {
+        return Ok(download);
+    }
+
+    let old_path = PathBuf::from(&download.filepath);
+    let folder = old_path.parent().unwrap_or(Path::new("."));
+    let _paths = manager.lock_paths().await;
+    let new_path = ensure_unique_path(
+        &db_state.path,
+        folder.join(&new_name).to_string_lossy().to_string(),
+    );
+    let new_path_buf = PathBuf::from(&new_path);
+
+    let old_part = crate::downloader::part_file_path(&old_path);
+    let new_part = crate::downloader::part_file_path(&new_path_buf);
+    let part_renamed = old_part.exists();
+    if part_renamed {
+        std::fs::rename(&old_part, &new_part).map_err(|e| e.to_string())?;
+    }
+    if old_path.exists() {
+        if let Err(e) = std::fs::rename(&old_path, &new_path_buf) {
+            if part_renamed {
+                let _ = std::fs::rename(&new_part, &old_part);
+            }
+            return Err(e.to_string());
+        }
+    }
+
+    // `ensure_unique_path` may have added a suffix, so store the name actually used.
+    let filename = new_path_buf
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or(new_name);
+    db::update_download_path(&db_state.path, &id, &new_path).map_err(|e| e.to_string())?;
+    db::update_download_name(&db_state.path, &id, &filename).map_err(|e| e.to_string())?;
+    db::log_event(
+        &db_state.path,
+        &id,
+        "renamed",
+        Some(&format!("{} -> {}", download.filename, filename)),
+    )
+    .ok();
+
+    download.filepath = new_path;
+    download.filename = filename;
+    Ok(download)
+}
+
+/// Gives a finished HTTP download the name its server announced during the transfer.
+///
+/// A `Content-Disposition` name found after the path was chosen only updates the
+/// registry's `filename`. Once complete, the file is moved to a unique path with that
+/// name. Returns the download with its final name and path.
+pub(crate) async fn adopt_discovered_filename(
+    db_path: &str,
+    manager: &DownloadManager,
+    mut download: Download,
+) -> Download {
+    let discovered = db::get_all_downloads(db_path)
+        .ok()
+        .and_then(|all| all.into_iter().find(|d| d.id == download.id))
+        .map(|d| d.filename);
+    let Some(discovered) = discovered else {
+        return download;
+    };
+    let old_path = PathBuf::from(&download.filepath);
+    let new_name = crate::downloader::sanitize_filename(discovered.trim());
+    let current_name = old_path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string());
+    if new_name.is_empty() || Some(&new_name) == current_name.as_ref() || !old_path.is_file() {
+        return download;
+    }
+
+    let folder = old_path.parent().unwrap_or(Path::new("."));
+    let _paths = manager.lock_paths().await;
+    let new_path = ensure_unique_path(
+        db_path,
+        folder.join(&new_name).to_string_lossy().to_string(),
+    );
+    if let Err(e) = std::fs::rename(&old_path, &new_path) {
+        tracing::error!("[{}] Failed to rename to {}: {}", download.id, new_path, e);
+        return download;
+    }
+    let filename = Path::new(&new_path)
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or(new_name);
+    db::update_download_path(db_path, &download.id, &new_path).ok();
+    db::update_download_name(db_path, &download.id, &filename).ok();
+    tracing::info!(
+        "[{}] Saved as {} (server-provided name)",
+        download.id,
+        new_path
+    );
+
+    download.filepath = new_path;
+    download.filename = filename;
+    download
+}
+
 /// Bridge: Fetches the entire configuration map.
 #[tauri::command]
 pub fn get_settings(db_state: State<DbState>) -> Result<HashMap<String, String>, String> {
@@ -583,9 +888,41 @@ pub fn get_settings(db_state: State<DbState>) -> Result<HashMap<String, String>,
 }
 
 /// Bridge: Updates a specific configuration key.
+///
+/// A new `speed_limit` is pushed straight into running HTTP downloads and a new
+/// `log_level` takes effect immediately.
 #[tauri::command]
-pub fn update_setting(db_state: State<DbState>, key: String, value: String) -> Result<(), String> {
-    db::set_setting(&db_state.path, &key, &value).map_err(|e| e.to_string())
+pub async fn update_setting(
+    app: AppHandle,
+    db_state: State<'_, DbState>,
+    manager: State<'_, DownloadManager>,
+    key: String,
+    value: String,
+) -> Result<(), String> {
+    if key == "speed_schedule" && !value.trim().is_empty() {
+        crate::scheduler::parse_speed_schedule(&value)?;
+    }
+    if key == "category_rules" {
+        category::parse_category_rules(&value)?;
+    }
+    if key == "torrent_listen_port" {
+        crate::torrent::parse_listen_port(&value)?;
+    }
+    if key == "log_level" {
+        crate::logging::parse_log_level(&value)?;
+    }
+    db::set_setting(&db_state.path, &key, &value).map_err(|e| e.to_string())?;
+
+    if key == "speed_limit" {
+        let configured = value.trim().parse::<u64>().unwrap_or(0);
+        manager
+            .apply_speed_limit(manager.effective_speed_limit(configured))
+            .await;
+    }
+    if key == "log_level" {
+        crate::logging::set_level(&app, &value);
+    }
+    Ok(())
 }
 
 /// Bridge: Opens the OS file explorer and focuses the downloaded file/folder.
@@ -602,7 +939,8 @@ pub fn show_in_folder<R: Runtime>(
 ///
 /// - Windows: Uses `explorer.exe /select` to highlight the file.
 /// - MacOS: Uses `open -R`.
-/// - Linux: Uses `xdg-open` on the parent folder.
+/// - Linux: Uses the file manager's select option or `FileManager1.ShowItems`,
+///   falling back to `xdg-open` on the parent folder.
 pub fn show_in_folder_internal<R: Runtime>(
     app: AppHandle<R>,
     db_path: &str,
@@ -633,26 +971,28 @@ pub fn show_in_folder_internal<R: Runtime>(
 
         let p = p_buf.as_path();
 
+        // explorer.exe parses its own command line, so arguments are passed raw
+        // and quoted here rather than with the standard library's escaping.
+        use std::os::windows::process::CommandExt;
+
         if p.exists() {
-            if p.is_dir() {
+            let arg = if p.is_dir() {
                 // If it's a directory, just open it
-                let _ = std::process::Command::new("explorer.exe").arg(p).spawn();
+                quote_explorer_path(p)
             } else {
                 // If it's a file, select it in its parent folder
-                // We use lossy string conversion to handle potential unicode issues safely
-                let path_str = p.to_string_lossy().to_string();
-                let _ = std::process::Command::new("explorer.exe")
-                    .arg("/select,")
-                    .arg(path_str)
-                    .spawn();
-            }
+                build_explorer_select_arg(p)
+            };
+            let _ = std::process::Command::new("explorer.exe")
+                .raw_arg(arg)
+                .spawn();
         } else {
             // If the specific file doesn't exist (e.g. download in progress),
             // try opening its parent directory
             if let Some(parent) = p.parent() {
                 if parent.exists() {
                     let _ = std::process::Command::new("explorer.exe")
-                        .arg(parent)
+                        .raw_arg(quote_explorer_path(parent))
                         .spawn();
                 }
             }
@@ -678,6 +1018,9 @@ pub fn show_in_folder_internal<R: Runtime>(
     #[cfg(target_os = "linux")]
     {
         let p = Path::new(&path);
+        if p.exists() && (select_with_file_manager(p) || select_with_dbus(p)) {
+            return Ok(());
+        }
         let folder = if p.is_dir() {
             p
         } else {
@@ -693,8 +1036,141 @@ pub fn show_in_folder_internal<R: Runtime>(
     Ok(())
 }
 
-/// Clear finished downloads
+/// Quotes `path` for explorer.exe so spaces and commas stay part of the path.
+///
+/// Windows paths cannot contain `"`, so wrapping in quotes needs no escaping.
+#[cfg(target_os = "windows")]
+fn quote_explorer_path(path: &Path) -> String {
+    format!("\"{}\"", path.to_string_lossy())
+}
+
+/// Builds the single `/select,"<path>"` argument that highlights `path` in explorer.exe.
+///
+/// Passing `/select,` and the path as separate arguments lets explorer split
+/// paths containing commas and open the wrong folder.
+#[cfg(target_os = "windows")]
+fn build_explorer_select_arg(path: &Path) -> String {
+    format!("/select,{}", quote_explorer_path(path))
+}
+
+/// Selects `path` using the default file manager's own flag, when it has one.
+///
+/// The file manager is taken from the `inode/directory` handler reported by `xdg-mime`.
+#[cfg(target_os = "linux")]
+fn select_with_file_manager(path: &Path) -> bool {
+    let handler = std::process::Command::new("xdg-mime")
+        .args(["query", "default", "inode/directory"])
+        .output()
+        .ok()
+        .map(|out| String::from_utf8_lossy(&out.stdout).to_lowercase())
+        .unwrap_or_default();
+
+    // Nemo selects a file passed on its own; the others need `--select`.
+    let (program, select_flag) = if handler.contains("nautilus") {
+        ("nautilus", Some("--select"))
+    } else if handler.contains("dolphin") {
+        ("dolphin", Some("--select"))
+    } else if handler.contains("caja") {
+        ("caja", Some("--select"))
+    } else if handler.contains("nemo") {
+        ("nemo", None)
+    } else {
+        return false;
+    };
+
+    let mut command = std::process::Command::new(program);
+    if let Some(flag) = select_flag {
+        command.arg(flag);
+    }
+    command.arg(path).spawn().is_ok()
+}
+
+/// Asks whichever file manager owns `org.freedesktop.FileManager1` to show `path`.
+#[cfg(target_os = "linux")]
+fn select_with_dbus(path: &Path) -> bool {
+    let Ok(uri) = url::Url::from_file_path(path) else {
+        return false;
+    };
+    std::process::Command::new("dbus-send")
+        .args([
+            "--session",
+            "--print-reply",
+            "--reply-timeout=2000",
+            "--dest=org.freedesktop.FileManager1",
+            "/org/freedesktop/FileManager1",
+            "org.freedesktop.FileManager1.ShowItems",
+        ])
+        // dbus-send splits array items on commas, so escape them in the URI.
+        .arg(format!("array:string:{}", uri.as_str().replace(',', "%2C")))
+        .arg("string:")
+        .output()
+        .map(|out| out.status.success())
+        .unwrap_or(false)
+}
+
+/// Removes completed downloads that finished more than `days` days ago.
+///
+/// Files are only deleted with `remove_files`, and only inside a known download
+/// folder. Returns how many downloads were removed.
+pub(crate) async fn cleanup_completed_older_than<R: Runtime>(
+    app: &AppHandle<R>,
+    db_path: &str,
+    days: u32,
+    remove_files: bool,
+) -> Result<usize, String> {
+    let cutoff = (chrono::Utc::now() - chrono::Duration::days(i64::from(days))).to_rfc3339();
+    let deleted = db::delete_completed_before(db_path, &cutoff).map_err(|e| e.to_string())?;
+    deleted.iter().for_each(crate::secrets::forget);
+
+    let torrent_manager = app.state::<TorrentManager>();
+    for download in &deleted {
+        let filepath = PathBuf::from(&download.filepath);
+        let deletable = remove_files && is_within_download_dir(app, db_path, &filepath);
+        if download.protocol == DownloadProtocol::Torrent {
+            // A completed torrent may still be seeding.
+            let target_path = deletable.then(|| download.filepath.clone());
+            let _ = torrent_manager
+                .delete_torrent(&download.id, deletable, target_path)
+                .await;
+        } else if deletable {
+            let _ = std::fs::remove_file(&filepath);
+        }
+    }
+    if !deleted.is_empty() {
+        tracing::info!(
+            "[Cleanup] Removed {} download(s) completed over {} day(s) ago",
+            deleted.len(),
+            days
+        );
+    }
+    Ok(deleted.len())
+}
+
+/// Bridge: Removes completed downloads older than `days`, optionally with their files.
 #[tauri::command]
-pub fn clear_finished(db_state: State<DbState>) -> Result<(), String> {
-    db::delete_finished_downloads(&db_state.path).map_err(|e| e.to_string())
+pub async fn cleanup_old_downloads<R: Runtime>(
+    app: AppHandle<R>,
+    db_state: State<'_, DbState>,
+    days: u32,
+    remove_files: bool,
+) -> Result<usize, String> {
+    cleanup_completed_older_than(&app, &db_state.path, days, remove_files).await
+}
+
+/// Clear finished downloads: only completed, only errored, or both.
+///
+/// Returns how many downloads were removed.
+#[tauri::command]
+pub fn clear_finished(
+    db_state: State<DbState>,
+    filter: db::FinishedFilter,
+) -> Result<usize, String> {
+    let finished = db::get_all_downloads(&db_state.path).map_err(|e| e.to_string())?;
+    let removed =
+        db::delete_finished_downloads(&db_state.path, filter).map_err(|e| e.to_string())?;
+    finished
+        .iter()
+        .filter(|d| filter.matches(&d.status))
+        .for_each(crate::secrets::forget);
+    Ok(removed)
 }
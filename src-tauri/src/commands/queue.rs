@@ -1,34 +1,73 @@
+use crate::commands::ftp;
 use crate::commands::http::{self, DownloadManager};
-use crate::commands::set_and_emit_download_error;
+use crate::commands::{emit_download_update, set_and_emit_download_error};
 use crate::db::{self, DbState, DownloadProtocol, DownloadStatus};
 use crate::torrent::TorrentManager;
+use std::collections::HashMap;
 use std::path::Path;
 use tauri::{AppHandle, Emitter, Manager, Runtime, State};
 
 use super::torrent::parse_optional_torrent_indices_metadata;
 
+/// Reads the `max_concurrent` setting: how many transfers may run at once.
+pub(super) fn max_concurrent(db_path: &str) -> usize {
+    db::get_setting(db_path, "max_concurrent")
+        .ok()
+        .flatten()
+        .and_then(|v| v.parse::<usize>().ok())
+        .unwrap_or(3)
+}
+
+/// Whether torrents count toward (and wait for) the `max_concurrent` cap.
+pub(super) fn torrents_share_queue(db_path: &str) -> bool {
+    db::get_setting(db_path, "queue_include_torrents")
+        .ok()
+        .flatten()
+        .map(|v| v != "false")
+        .unwrap_or(true)
+}
+
+/// Number of running transfers that count toward `max_concurrent`.
+///
+/// HTTP/FTP transfers are counted from the moment they are started, so a download
+/// that is still resolving its URL already holds its slot.
+pub(super) async fn slots_in_use(
+    db_path: &str,
+    manager: &DownloadManager,
+    torrent_manager: &TorrentManager,
+) -> usize {
+    let torrents = if torrents_share_queue(db_path) {
+        torrent_manager.running_count().await
+    } else {
+        0
+    };
+    manager.running_count() + torrents
+}
+
 /// QUEUE PROCESSOR
 ///
 /// Checks if the number of active downloads is below the limit, and if so,
 /// starts the next queued download from the database.
+///
+/// Runs whenever a transfer completes, fails, is paused or frees its slot.
 pub async fn process_queue<R: Runtime>(app: AppHandle<R>) {
     let db_state: State<DbState> = app.state();
     let manager: State<DownloadManager> = app.state();
     let torrent_manager: State<TorrentManager> = app.state();
+    // Transfers stopped on the way out must not hand their slots to queued ones.
+    if app.state::<crate::shutdown::ShutdownState>().is_started() {
+        return;
+    }
+    let queue_guard = manager.lock_queue().await;
+    // Torrents picked below, started once the queue lock is released.
+    let mut torrent_starts = Vec::new();
 
     // Loop until we max out slots or run out of queued items
     loop {
         // 1. Check Limits
-        let max_simultaneous = db::get_setting(&db_state.path, "max_concurrent")
-            .ok()
-            .flatten()
-            .and_then(|v| v.parse::<usize>().ok())
-            .unwrap_or(3);
-
-        let (http_active, _) = manager.get_global_status().await;
-        let (torrent_active, _) = torrent_manager.get_global_status().await;
-
-        if (http_active + torrent_active) >= max_simultaneous {
+        if slots_in_use(&db_state.path, &manager, &torrent_manager).await
+            >= max_concurrent(&db_state.path)
+        {
             break;
         }
 
@@ -42,6 +81,11 @@ pub async fn process_queue<R: Runtime>(app: AppHandle<R>) {
             }
         };
 
+        // Queued downloads wait until the monthly data cap resets or is raised.
+        if crate::bandwidth::cap_reached(&app, &db_state.path) {
+            break;
+        }
+
         // 3. Start Download
         let id = next_download.id.clone();
         tracing::info!("Queue Processor: Starting {}", next_download.filename);
@@ -60,6 +104,7 @@ pub async fn process_queue<R: Runtime>(app: AppHandle<R>) {
             Some("Auto-started from queue"),
         )
         .ok();
+        emit_download_update(&app, &db_state.path, &id);
         let _ = app.emit("download-started", id.clone());
 
         match next_download.protocol {
@@ -76,6 +121,19 @@ pub async fn process_queue<R: Runtime>(app: AppHandle<R>) {
                     set_and_emit_download_error(&app, &db_state.path, &id, &e);
                 }
             }
+            DownloadProtocol::Ftp => {
+                if let Err(e) = ftp::start_ftp_task(
+                    app.clone(),
+                    db_state.path.clone(),
+                    manager.inner().clone(),
+                    next_download,
+                )
+                .await
+                {
+                    tracing::error!("Failed to start queued FTP download {}: {}", id, e);
+                    set_and_emit_download_error(&app, &db_state.path, &id, &e);
+                }
+            }
             DownloadProtocol::Torrent => {
                 let path = Path::new(&next_download.filepath);
                 let base_folder = path
@@ -93,40 +151,216 @@ pub async fn process_queue<R: Runtime>(app: AppHandle<R>) {
                     }
                 };
 
-                if !torrent_manager.wait_until_ready(30000).await {
-                    tracing::error!(
-                        "Queue Processor: torrent engine still initializing; will retry {}",
-                        id
-                    );
-                    let _ = db::update_download_status(&db_state.path, &id, DownloadStatus::Queued);
-                    break;
-                }
-
-                if let Err(e) = torrent_manager
-                    .add_magnet(
-                        app.clone(),
-                        id.clone(),
-                        next_download.url.clone(),
-                        base_folder,
-                        db_state.path.clone(),
-                        indices,
-                        next_download.size as u64,
-                        next_download.downloaded.max(0) as u64,
-                        true,  // is_resume
-                        false, // start_paused
-                        None,
-                    )
-                    .await
-                {
-                    tracing::error!("Failed to start queued torrent {}: {}", id, e);
-                    set_and_emit_download_error(&app, &db_state.path, &id, &e);
+                // Queued torrents only exist while they share the queue, but a setting
+                // flipped since must not make one hold an HTTP slot.
+                if torrents_share_queue(&db_state.path) {
+                    manager.occupy_slot(&id);
                 }
+                torrent_starts.push((next_download, base_folder, indices));
             }
             DownloadProtocol::Video => {
                 // TODO: Implement video download queuing when video support is fully added
                 tracing::error!("Video queuing not yet supported for {}", id);
                 let _ = db::update_download_status(&db_state.path, &id, DownloadStatus::Error);
+                emit_download_update(&app, &db_state.path, &id);
+            }
+        }
+    }
+
+    // Waiting on the engine and resolving magnets can take seconds; the slots claimed
+    // above keep the cap while other adds go through the queue lock meanwhile.
+    drop(queue_guard);
+    if torrent_starts.is_empty() {
+        return;
+    }
+    let engine_ready = torrent_manager.wait_until_ready(30000).await;
+    for (next_download, base_folder, indices) in torrent_starts {
+        let id = next_download.id.clone();
+        if !engine_ready {
+            tracing::error!(
+                "Queue Processor: torrent engine still initializing; will retry {}",
+                id
+            );
+            manager.vacate_slot(&id);
+            let _ = db::update_download_status(&db_state.path, &id, DownloadStatus::Queued);
+            emit_download_update(&app, &db_state.path, &id);
+            continue;
+        }
+
+        let started = torrent_manager
+            .add_magnet(
+                app.clone(),
+                id.clone(),
+                next_download.url.clone(),
+                base_folder,
+                db_state.path.clone(),
+                indices,
+                next_download.size as u64,
+                next_download.downloaded.max(0) as u64,
+                true,  // is_resume
+                false, // start_paused
+                None,
+            )
+            .await;
+        manager.vacate_slot(&id);
+        if let Err(e) = started {
+            tracing::error!("Failed to start queued torrent {}: {}", id, e);
+            set_and_emit_download_error(&app, &db_state.path, &id, &e);
+        }
+    }
+}
+
+/// Restarts the HTTP/FTP downloads that were running when Ciel last closed.
+///
+/// With `auto_resume` on, they go back to the front of the queue and the queue
+/// processor restarts them within `max_concurrent`; otherwise they are marked
+/// paused. Torrents are restored separately when the frontend loads.
+pub async fn resume_interrupted<R: Runtime>(app: AppHandle<R>) {
+    let db_path = app.state::<DbState>().path.clone();
+    let mut interrupted = db::get_all_downloads(&db_path)
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|d| {
+            d.status == DownloadStatus::Downloading
+                && matches!(d.protocol, DownloadProtocol::Http | DownloadProtocol::Ftp)
+        })
+        .collect::<Vec<_>>();
+    if interrupted.is_empty() {
+        return;
+    }
+
+    let auto_resume = db::get_setting(&db_path, "auto_resume")
+        .ok()
+        .flatten()
+        .map(|v| v == "true")
+        .unwrap_or(true);
+    tracing::info!(
+        "[Startup] {} interrupted download(s); {}",
+        interrupted.len(),
+        if auto_resume { "resuming" } else { "pausing" }
+    );
+
+    // Moving each one to the top in reverse keeps their previous order.
+    interrupted.sort_by_key(|d| d.priority);
+    for download in interrupted.iter().rev() {
+        if auto_resume {
+            db::move_download_to_queue_edge(&db_path, &download.id, true).ok();
+            db::update_download_status(&db_path, &download.id, DownloadStatus::Queued).ok();
+        } else {
+            db::update_download_status(&db_path, &download.id, DownloadStatus::Paused).ok();
+            db::log_event(
+                &db_path,
+                &download.id,
+                "paused",
+                Some("Interrupted by app exit"),
+            )
+            .ok();
+        }
+        emit_download_update(&app, &db_path, &download.id);
+    }
+
+    if auto_resume {
+        process_queue(app).await;
+    }
+}
+
+/// Bridge: Rewrites queue order to match the given list of IDs (drag-and-drop reorder).
+///
+/// The first ID becomes the next download promoted by the queue processor.
+#[tauri::command]
+pub fn reorder_queue(db_state: State<DbState>, ordered_ids: Vec<String>) -> Result<(), String> {
+    db::reorder_queue(&db_state.path, &ordered_ids).map_err(|e| e.to_string())
+}
+
+/// Bridge: Moves a download to the top or bottom of the queue.
+///
+/// `position` must be either `"top"` or `"bottom"`.
+#[tauri::command]
+pub fn move_in_queue(db_state: State<DbState>, id: String, position: String) -> Result<(), String> {
+    let to_top = match position.as_str() {
+        "top" => true,
+        "bottom" => false,
+        other => return Err(format!("Unknown queue position: {}", other)),
+    };
+    db::move_download_to_queue_edge(&db_state.path, &id, to_top).map_err(|e| e.to_string())
+}
+
+/// Aggregate progress across everything that is downloading or waiting in the queue.
+#[derive(serde::Serialize)]
+pub struct QueueSummary {
+    /// Bytes left across active and queued downloads of known size.
+    pub remaining_bytes: u64,
+    /// Combined current speed in bytes/sec.
+    pub speed: u64,
+    /// Seconds until all known-size work finishes at the current speed, if moving.
+    pub eta: Option<u64>,
+    /// Active/queued downloads without a known size; excluded from `remaining_bytes` and `eta`.
+    pub unknown_size: usize,
+    /// Number of downloads per status (`downloading`, `queued`, `paused`, ...).
+    pub status_counts: HashMap<String, usize>,
+}
+
+/// Bridge: Returns queue-wide remaining bytes, speed and ETA for a global progress header.
+///
+/// Live HTTP progress comes from the `DownloadManager`; torrents use their session
+/// stats when available and the persisted row otherwise.
+#[tauri::command]
+pub async fn get_queue_summary(
+    db_state: State<'_, DbState>,
+    manager: State<'_, DownloadManager>,
+    torrent_manager: State<'_, TorrentManager>,
+) -> Result<QueueSummary, String> {
+    let downloads = db::get_all_downloads(&db_state.path).map_err(|e| e.to_string())?;
+    let live_http = manager.progress_snapshot().await;
+
+    let mut summary = QueueSummary {
+        remaining_bytes: 0,
+        speed: 0,
+        eta: None,
+        unknown_size: 0,
+        status_counts: HashMap::new(),
+    };
+
+    for d in &downloads {
+        *summary
+            .status_counts
+            .entry(d.status.as_str().to_string())
+            .or_insert(0) += 1;
+
+        if d.status != DownloadStatus::Downloading && d.status != DownloadStatus::Queued {
+            continue;
+        }
+
+        let (downloaded, total, speed) = match (live_http.get(&d.id), &d.protocol) {
+            (Some(p), _) => (p.downloaded, p.total, p.speed),
+            (None, DownloadProtocol::Torrent) => {
+                match torrent_manager.get_stats_snapshot(&d.id).await {
+                    Some(s) => (s.progress_bytes, s.total_bytes, d.speed.max(0) as u64),
+                    None => (
+                        d.downloaded.max(0) as u64,
+                        d.size.max(0) as u64,
+                        d.speed.max(0) as u64,
+                    ),
+                }
             }
+            (None, _) => (d.downloaded.max(0) as u64, d.size.max(0) as u64, 0),
+        };
+
+        if d.status == DownloadStatus::Downloading {
+            summary.speed += speed;
         }
+        if total == 0 {
+            summary.unknown_size += 1;
+        } else {
+            summary.remaining_bytes += total.saturating_sub(downloaded);
+        }
+    }
+
+    if summary.speed > 0 {
+        summary.eta = Some(summary.remaining_bytes / summary.speed);
+    } else if summary.remaining_bytes == 0 {
+        summary.eta = Some(0);
     }
+
+    Ok(summary)
 }
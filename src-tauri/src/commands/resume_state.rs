@@ -0,0 +1,179 @@
+use super::DownloadManager;
+use crate::db::{self, DbState, Download, DownloadProtocol, DownloadStatus};
+use crate::downloader::{part_file_path, ChunkRecord, PART_FILE_SUFFIX};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::io::Read;
+use std::path::Path;
+use tauri::{AppHandle, Runtime, State};
+
+/// Bumped whenever the on-disk layout of [`ResumeState`] changes.
+const RESUME_STATE_VERSION: u32 = 1;
+
+/// Portable snapshot of a paused HTTP download, used to continue it on another machine.
+#[derive(Serialize, Deserialize)]
+pub struct ResumeState {
+    pub version: u32,
+    /// The download row as it was at export time (cookies and proxy are never exported).
+    pub download: Download,
+    pub chunks: Vec<ChunkRecord>,
+    /// Size and SHA-256 of the partial file, checked on import.
+    pub file_size: u64,
+    pub file_sha256: String,
+}
+
+fn sha256_file(path: &Path) -> std::io::Result<String> {
+    let mut file = std::fs::File::open(path)?;
+    let mut hasher = Sha256::new();
+    let mut buffer = [0u8; 64 * 1024];
+    loop {
+        let count = file.read(&mut buffer)?;
+        if count == 0 {
+            break;
+        }
+        hasher.update(&buffer[..count]);
+    }
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// Hashes a (possibly multi-GB) file off the async runtime.
+async fn fingerprint(path: String) -> Result<(u64, String), String> {
+    tokio::task::spawn_blocking(move || {
+        let path = Path::new(&path);
+        let size = std::fs::metadata(path).map_err(|e| e.to_string())?.len();
+        let hash = sha256_file(path).map_err(|e| e.to_string())?;
+        Ok((size, hash))
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Bridge: Writes a paused download's chunk layout and metadata to `dest_path`.
+///
+/// The partial (`.ciel-part`) file itself is not bundled; copy it alongside the exported state.
+#[tauri::command]
+pub async fn export_resume_state(
+    db_state: State<'_, DbState>,
+    manager: State<'_, DownloadManager>,
+    id: String,
+    dest_path: String,
+) -> Result<(), String> {
+    let downloads = db::get_all_downloads(&db_state.path).map_err(|e| e.to_string())?;
+    let mut download = downloads
+        .into_iter()
+        .find(|d| d.id == id)
+        .ok_or("Download not found")?;
+
+    if download.protocol != DownloadProtocol::Http {
+        return Err("Only HTTP downloads can be exported".to_string());
+    }
+    if download.status == DownloadStatus::Completed {
+        return Err("Download already completed".to_string());
+    }
+    if manager.is_active(&id).await {
+        return Err("Pause the download before exporting it".to_string());
+    }
+
+    let chunks = db::get_download_chunks(&db_state.path, &id).map_err(|e| e.to_string())?;
+    if chunks.is_empty() {
+        return Err("Download has no resumable progress to export".to_string());
+    }
+
+    let part = part_file_path(Path::new(&download.filepath));
+    let (file_size, file_sha256) = fingerprint(part.to_string_lossy().to_string()).await?;
+
+    // Session cookies and proxy credentials must not leave the machine.
+    download.cookies = None;
+    download.proxy = None;
+
+    let state = ResumeState {
+        version: RESUME_STATE_VERSION,
+        download,
+        chunks,
+        file_size,
+        file_sha256,
+    };
+    let json = serde_json::to_string_pretty(&state).map_err(|e| e.to_string())?;
+    std::fs::write(&dest_path, json).map_err(|e| e.to_string())?;
+
+    db::log_event(&db_state.path, &id, "exported", Some(&dest_path)).ok();
+    Ok(())
+}
+
+/// Bridge: Recreates a download from an exported resume state and its partial file.
+///
+/// The partial file at `partial_path` must be byte-identical to the one that was
+/// exported, otherwise chunks would be stitched onto incompatible data.
+#[tauri::command]
+pub async fn import_resume_state<R: Runtime>(
+    app: AppHandle<R>,
+    db_state: State<'_, DbState>,
+    state_path: String,
+    partial_path: String,
+) -> Result<Download, String> {
+    let json = std::fs::read_to_string(&state_path).map_err(|e| e.to_string())?;
+    let state: ResumeState =
+        serde_json::from_str(&json).map_err(|e| format!("Invalid resume state: {}", e))?;
+
+    if state.version != RESUME_STATE_VERSION {
+        return Err(format!(
+            "Unsupported resume state version: {}",
+            state.version
+        ));
+    }
+
+    // The download continues in `<final>.ciel-part`, whatever the copied file is called.
+    let final_path = partial_path
+        .strip_suffix(PART_FILE_SUFFIX)
+        .unwrap_or(&partial_path)
+        .to_string();
+    if db::check_filepath_exists(&db_state.path, &final_path).unwrap_or(false) {
+        return Err("Another download already uses this file".to_string());
+    }
+
+    let (file_size, file_sha256) = fingerprint(partial_path.clone()).await?;
+    if file_size != state.file_size || file_sha256 != state.file_sha256 {
+        return Err("Partial file does not match the exported resume state".to_string());
+    }
+
+    let part = part_file_path(Path::new(&final_path));
+    if Path::new(&partial_path) != part {
+        std::fs::rename(&partial_path, &part).map_err(|e| e.to_string())?;
+    }
+
+    let id = uuid::Uuid::new_v4().to_string();
+    let filename = Path::new(&final_path)
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| state.download.filename.clone());
+
+    let download = Download {
+        id: id.clone(),
+        filename,
+        filepath: final_path,
+        status: DownloadStatus::Paused,
+        speed: 0,
+        created_at: chrono::Utc::now().to_rfc3339(),
+        completed_at: None,
+        error_message: None,
+        cookies: None,
+        priority: db::get_next_priority(&db_state.path).unwrap_or(0),
+        ..state.download
+    };
+
+    let chunks = state
+        .chunks
+        .into_iter()
+        .map(|c| ChunkRecord {
+            download_id: id.clone(),
+            ..c
+        })
+        .collect::<Vec<_>>();
+
+    db::insert_download(&db_state.path, &download).map_err(|e| e.to_string())?;
+    db::replace_download_chunks(&db_state.path, &id, chunks).map_err(|e| e.to_string())?;
+    db::log_event(&db_state.path, &id, "imported", Some(&state_path)).ok();
+    super::emit_download_update(&app, &db_state.path, &id);
+
+    Ok(download)
+}
@@ -0,0 +1,8 @@
+use crate::db::{self, DbState, DownloadStatistics};
+use tauri::State;
+
+/// Bridge: Returns all-time totals for a statistics dashboard.
+#[tauri::command]
+pub async fn get_statistics(db_state: State<'_, DbState>) -> Result<DownloadStatistics, String> {
+    db::get_statistics(&db_state.path).map_err(|e| e.to_string())
+}
@@ -1,10 +1,11 @@
 use super::{
-    ensure_unique_path, resolve_download_path, set_and_emit_download_error, DownloadManager,
+    emit_download_update, ensure_unique_path, queue, resolve_download_path,
+    set_and_emit_download_error, DownloadManager,
 };
 use crate::db::{self, DbState, Download, DownloadProtocol, DownloadStatus};
 use crate::torrent::TorrentManager;
 use std::path::Path;
-use tauri::{AppHandle, Emitter, Runtime, State};
+use tauri::{AppHandle, Emitter, Manager, Runtime, State};
 
 fn serialize_torrent_indices_metadata(indices: &Option<Vec<usize>>) -> Option<String> {
     indices
@@ -49,8 +50,9 @@ pub(crate) fn parse_optional_torrent_indices_metadata(
 ///
 /// This command handles:
 /// - Metadata extraction from magnet query parameters.
-/// - Duplicate isolation: If a torrent with the same name exists, it creates
-///   a dedicated sub-folder to prevent file/hash collisions.
+/// - Duplicate isolation: If another download already uses the same path, it
+///   creates a dedicated sub-folder to prevent file/hash collisions. Files left
+///   on disk by an untracked copy are hash-checked and reused instead.
 /// - Registration with the `TorrentManager`.
 #[tauri::command]
 pub async fn add_torrent<R: Runtime>(
@@ -66,6 +68,7 @@ pub async fn add_torrent<R: Runtime>(
     analysis_id: Option<String>,
     total_size: Option<u64>,
     start_paused: Option<bool>,
+    sequential: Option<bool>,
 ) -> Result<Download, String> {
     let is_magnet = url.starts_with("magnet:");
 
@@ -78,10 +81,17 @@ pub async fn add_torrent<R: Runtime>(
         }
     }
 
-    // Finalize resolved path (Smart Duplicate Handling)
+    // Finalize resolved path (Smart Duplicate Handling). Data already on disk
+    // is reused and hash-checked by the engine; only another tracked download
+    // at the same path forces a separate folder.
     let resolved_path =
         resolve_download_path(&app, &db_state.path, &filename, output_folder.clone());
-    let final_resolved_path = ensure_unique_path(&db_state.path, resolved_path.clone());
+    let final_resolved_path =
+        if crate::db::check_filepath_exists(&db_state.path, &resolved_path).unwrap_or(false) {
+            ensure_unique_path(&db_state.path, resolved_path.clone())
+        } else {
+            resolved_path.clone()
+        };
 
     // Extract the final unique filename from the path
     let final_filename = Path::new(&final_resolved_path)
@@ -89,20 +99,22 @@ pub async fn add_torrent<R: Runtime>(
         .map(|n| n.to_string_lossy().to_string())
         .unwrap_or_else(|| filename.clone());
 
-    // Queue enforcement: Check if we can start immediately or must queue
-    let max_simultaneous = db::get_setting(&db_state.path, "max_concurrent")
-        .ok()
-        .flatten()
-        .and_then(|v| v.parse::<usize>().ok())
-        .unwrap_or(3);
-
-    // Count both HTTP and Torrent active downloads
-    let (http_active, _) = manager.get_global_status().await;
-    let (torrent_active, _) = torrent_manager.get_global_status().await;
-    let active_count = http_active + torrent_active;
-    let should_queue = !start_paused.unwrap_or(false) && active_count >= max_simultaneous;
-
+    // Queue enforcement: Check if we can start immediately or must queue.
+    // With `queue_include_torrents` off, torrents bypass the cap entirely.
     let id = uuid::Uuid::new_v4().to_string();
+    let queue_guard = manager.lock_queue().await;
+    let shares_queue = queue::torrents_share_queue(&db_state.path);
+    let should_queue = !start_paused.unwrap_or(false)
+        && shares_queue
+        && queue::slots_in_use(&db_state.path, &manager, &torrent_manager).await
+            >= queue::max_concurrent(&db_state.path);
+    // Starting the engine and resolving a magnet can take a while, so only the slot is
+    // claimed under the lock; it is handed back once the engine counts the torrent.
+    let holds_slot = shares_queue && !should_queue && !start_paused.unwrap_or(false);
+    if holds_slot {
+        manager.occupy_slot(&id);
+    }
+    drop(queue_guard);
     let download = Download {
         id: id.clone(),
         url: url.clone(),
@@ -128,9 +140,26 @@ pub async fn add_torrent<R: Runtime>(
         user_agent: None,
         cookies: None,
         category: "Other".to_string(),
+        priority: db::get_next_priority(&db_state.path).unwrap_or(0),
+        speed_limit: 0,
+        proxy: None,
+        headers: None,
+        etag: None,
+        last_modified: None,
+        mirrors: None,
+        checksum: None,
+        source_page_url: None,
+        username: None,
+        password: None,
     };
 
-    db::insert_download(&db_state.path, &download).map_err(|e| e.to_string())?;
+    if let Err(e) = db::insert_download(&db_state.path, &download) {
+        manager.vacate_slot(&id);
+        return Err(e.to_string());
+    }
+    if sequential.unwrap_or(false) {
+        torrent_manager.set_sequential(&id, true).await;
+    }
     db::log_event(
         &db_state.path,
         &download.id,
@@ -144,6 +173,7 @@ pub async fn add_torrent<R: Runtime>(
         }),
     )
     .ok();
+    emit_download_update(&app, &db_state.path, &download.id);
 
     let is_duplicate = resolved_path != final_resolved_path;
 
@@ -208,11 +238,12 @@ pub async fn add_torrent<R: Runtime>(
         if !torrent_manager.wait_until_ready(30000).await {
             let msg =
                 "Torrent engine is still initializing. Please retry in a few seconds.".to_string();
+            manager.vacate_slot(&id);
             set_and_emit_download_error(&app, &db_state.path, &id, &msg);
             return Err(msg);
         }
 
-        torrent_manager
+        let started = torrent_manager
             .add_magnet(
                 app,
                 id.clone(),
@@ -226,12 +257,198 @@ pub async fn add_torrent<R: Runtime>(
                 start_paused.unwrap_or(false),
                 source_torrent_bytes,
             )
-            .await?;
+            .await;
+        manager.vacate_slot(&id);
+        started?;
     }
 
     Ok(download)
 }
 
+/// Bridge: Adds a `.torrent` file from disk.
+///
+/// The file is parsed before anything is stored, so a corrupt torrent never
+/// leaves an orphan row behind. The download is named after the torrent and
+/// tagged with its info hash straight away instead of once the engine loads it.
+#[tauri::command]
+pub async fn add_torrent_file<R: Runtime>(
+    app: AppHandle<R>,
+    db_state: State<'_, DbState>,
+    path: String,
+    output_folder: Option<String>,
+    start_paused: Option<bool>,
+    sequential: Option<bool>,
+) -> Result<Download, String> {
+    let bytes = std::fs::read(&path).map_err(|e| format!("Failed to read .torrent file: {}", e))?;
+    let meta = librqbit::torrent_from_bytes::<librqbit::ByteBuf>(&bytes)
+        .map_err(|e| format!("Invalid .torrent file: {}", e))?;
+    let info_hash = hex::encode(meta.info_hash.0);
+    let name = meta
+        .info
+        .name
+        .as_ref()
+        .map(|n| String::from_utf8_lossy(n.as_ref()).to_string())
+        .filter(|n| !n.trim().is_empty())
+        .or_else(|| {
+            Path::new(&path)
+                .file_stem()
+                .map(|s| s.to_string_lossy().to_string())
+        })
+        .unwrap_or_else(|| "torrent".to_string());
+
+    let mut download = add_torrent(
+        app.clone(),
+        db_state.clone(),
+        app.state::<DownloadManager>(),
+        app.state::<TorrentManager>(),
+        path,
+        name,
+        String::new(),
+        output_folder,
+        None,
+        None,
+        None,
+        start_paused,
+        sequential,
+    )
+    .await?;
+
+    db::update_download_info_hash(&db_state.path, &download.id, &info_hash)
+        .map_err(|e| e.to_string())?;
+    download.info_hash = Some(info_hash);
+    Ok(download)
+}
+
+/// Bridge: Turns seeding on or off for one torrent, overriding `seed_enabled`.
+///
+/// Takes effect when the torrent completes; switching it off while seeding stops
+/// the upload right away. Once seeding has ended the torrent leaves the session,
+/// so it cannot be switched back on.
+#[tauri::command]
+pub async fn set_seeding(
+    db_state: State<'_, DbState>,
+    torrent_manager: State<'_, TorrentManager>,
+    id: String,
+    enabled: bool,
+) -> Result<(), String> {
+    let downloads = db::get_all_downloads(&db_state.path).map_err(|e| e.to_string())?;
+    let download = downloads
+        .iter()
+        .find(|d| d.id == id)
+        .ok_or("Download not found")?;
+    if download.protocol != DownloadProtocol::Torrent {
+        return Err("Only torrents can seed".to_string());
+    }
+    if enabled
+        && download.status == DownloadStatus::Completed
+        && !torrent_manager.is_seeding(&id).await
+    {
+        return Err("This torrent has already stopped seeding".to_string());
+    }
+
+    torrent_manager.set_seeding(&id, enabled).await;
+    db::log_event(
+        &db_state.path,
+        &id,
+        "seeding",
+        Some(if enabled { "enabled" } else { "disabled" }),
+    )
+    .ok();
+    Ok(())
+}
+
+/// Bridge: Switches a torrent between in-order and the default piece order.
+///
+/// Sequential mode lets a video be played while it downloads, at some cost to
+/// swarm efficiency. It applies to a running torrent immediately and lasts
+/// until Ciel restarts.
+#[tauri::command]
+pub async fn set_sequential(
+    db_state: State<'_, DbState>,
+    torrent_manager: State<'_, TorrentManager>,
+    id: String,
+    enabled: bool,
+) -> Result<(), String> {
+    let downloads = db::get_all_downloads(&db_state.path).map_err(|e| e.to_string())?;
+    let download = downloads
+        .iter()
+        .find(|d| d.id == id)
+        .ok_or("Download not found")?;
+    if download.protocol != DownloadProtocol::Torrent {
+        return Err("Only torrents support sequential download".to_string());
+    }
+
+    torrent_manager.set_sequential(&id, enabled).await;
+    db::log_event(
+        &db_state.path,
+        &id,
+        "sequential",
+        Some(if enabled { "enabled" } else { "disabled" }),
+    )
+    .ok();
+    Ok(())
+}
+
+/// Bridge: Sets the session-wide torrent upload/download caps in bytes/sec (0 = unlimited).
+///
+/// Saved as `torrent_upload_limit` / `torrent_download_limit` and applied to
+/// running torrents immediately.
+#[tauri::command]
+pub async fn set_torrent_limits(
+    db_state: State<'_, DbState>,
+    torrent_manager: State<'_, TorrentManager>,
+    upload: u64,
+    download: u64,
+) -> Result<(), String> {
+    db::set_setting(&db_state.path, "torrent_upload_limit", &upload.to_string())
+        .map_err(|e| e.to_string())?;
+    db::set_setting(
+        &db_state.path,
+        "torrent_download_limit",
+        &download.to_string(),
+    )
+    .map_err(|e| e.to_string())?;
+    torrent_manager.set_rate_limits(upload, download).await
+}
+
+/// Bridge: Returns trackers and connected peers of a loaded torrent.
+#[tauri::command]
+pub async fn get_torrent_details(
+    torrent_manager: State<'_, TorrentManager>,
+    id: String,
+) -> Result<crate::torrent::TorrentDetails, String> {
+    torrent_manager.get_details(&id).await
+}
+
+/// Bridge: Rebuilds the torrent session so network settings take effect.
+///
+/// `torrent_listen_port`, `torrent_enable_dht` and `torrent_enable_pex` are only
+/// read when the session starts. Torrents that were downloading are resumed on
+/// the new session; paused ones rejoin it when resumed.
+#[tauri::command]
+pub async fn restart_torrent_session<R: Runtime>(
+    app: AppHandle<R>,
+    db_state: State<'_, DbState>,
+    torrent_manager: State<'_, TorrentManager>,
+) -> Result<(), String> {
+    let downloading = torrent_manager.restart_session(&db_state.path).await?;
+    for id in downloading {
+        if let Err(e) = super::resume_download(
+            app.clone(),
+            app.state::<DbState>(),
+            app.state::<DownloadManager>(),
+            app.state::<TorrentManager>(),
+            id.clone(),
+            None,
+        )
+        .await
+        {
+            tracing::error!("[Torrent] Failed to re-add {} after restart: {}", id, e);
+        }
+    }
+    Ok(())
+}
+
 /// Bridge: Inspects a torrent source to retrieve its file list and metadata.
 ///
 /// This is used for "Selective Downloads" where the user chooses specific
@@ -249,9 +466,50 @@ pub async fn analyze_torrent(
 #[tauri::command]
 pub async fn start_selective_torrent(
     _app: AppHandle,
+    db_state: State<'_, DbState>,
     torrent_manager: State<'_, TorrentManager>,
     id: String,
     indices: Vec<usize>,
 ) -> Result<(), String> {
-    torrent_manager.start_selective(&id, indices).await
+    let total_bytes = torrent_manager
+        .start_selective(&id, indices.clone())
+        .await?;
+    save_file_selection(&db_state.path, &id, indices, total_bytes)
+}
+
+/// Bridge: Changes which files of a loaded torrent are downloaded.
+///
+/// Newly selected files start downloading; deselected ones stop and no longer
+/// count toward the total. Data already written for them stays on disk.
+#[tauri::command]
+pub async fn update_torrent_file_selection(
+    db_state: State<'_, DbState>,
+    torrent_manager: State<'_, TorrentManager>,
+    id: String,
+    indices: Vec<usize>,
+) -> Result<(), String> {
+    let total_bytes = torrent_manager.update_file_selection(&id, &indices).await?;
+    db::log_event(
+        &db_state.path,
+        &id,
+        "selection_changed",
+        Some(&format!("{} file(s) selected", indices.len())),
+    )
+    .ok();
+    save_file_selection(&db_state.path, &id, indices, total_bytes)
+}
+
+/// Persists a torrent's file selection so it survives a restart.
+fn save_file_selection(
+    db_path: &str,
+    id: &str,
+    indices: Vec<usize>,
+    total_bytes: u64,
+) -> Result<(), String> {
+    let metadata = serialize_torrent_indices_metadata(&Some(indices));
+    db::update_download_metadata(db_path, id, metadata.as_deref()).map_err(|e| e.to_string())?;
+    if total_bytes > 0 {
+        db::update_download_size(db_path, id, total_bytes as i64).map_err(|e| e.to_string())?;
+    }
+    Ok(())
 }
@@ -9,16 +9,81 @@
 
 use rusqlite::{Connection, Result as SqliteResult};
 use serde::{Deserialize, Serialize};
-use std::path::Path;
+use std::ops::{Deref, DerefMut};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
 
 /// Shared state holding the absolute path to the SQLite database file.
 pub struct DbState {
     pub path: String,
 }
 
+/// Idle connections kept per database file for reuse.
+const MAX_IDLE_CONNECTIONS: usize = 8;
+
+/// Idle connections and the database file each belongs to.
+static IDLE_CONNECTIONS: Mutex<Vec<(PathBuf, Connection)>> = Mutex::new(Vec::new());
+
+/// A connection handed out by [`open_db`]; goes back to the idle pool when dropped.
+pub struct PooledConnection {
+    conn: Option<Connection>,
+    path: PathBuf,
+}
+
+impl Deref for PooledConnection {
+    type Target = Connection;
+
+    fn deref(&self) -> &Connection {
+        self.conn
+            .as_ref()
+            .expect("connection is present until drop")
+    }
+}
+
+impl DerefMut for PooledConnection {
+    fn deref_mut(&mut self) -> &mut Connection {
+        self.conn
+            .as_mut()
+            .expect("connection is present until drop")
+    }
+}
+
+impl Drop for PooledConnection {
+    fn drop(&mut self) {
+        let Some(conn) = self.conn.take() else {
+            return;
+        };
+        // A connection left inside a transaction is never handed out again.
+        if !conn.is_autocommit() {
+            return;
+        }
+        if let Ok(mut idle) = IDLE_CONNECTIONS.lock() {
+            let pooled = idle.iter().filter(|(path, _)| *path == self.path).count();
+            if pooled < MAX_IDLE_CONNECTIONS {
+                idle.push((std::mem::take(&mut self.path), conn));
+            }
+        }
+    }
+}
+
 /// Centralized database accessor with a busy timeout to prevent contention hangs.
-pub fn open_db<P: AsRef<Path>>(path: P) -> SqliteResult<Connection> {
-    let conn = Connection::open(path)?;
+///
+/// Connections are reused across calls, so hot paths like progress updates don't
+/// reopen the file and re-run the PRAGMAs every time.
+pub fn open_db<P: AsRef<Path>>(path: P) -> SqliteResult<PooledConnection> {
+    let path = path.as_ref().to_path_buf();
+    let reused = IDLE_CONNECTIONS.lock().ok().and_then(|mut idle| {
+        let index = idle.iter().position(|(p, _)| *p == path)?;
+        Some(idle.swap_remove(index).1)
+    });
+    if let Some(conn) = reused {
+        return Ok(PooledConnection {
+            conn: Some(conn),
+            path,
+        });
+    }
+
+    let conn = Connection::open(&path)?;
     // Wait up to 5 seconds if the database is locked by another thread.
     conn.busy_timeout(std::time::Duration::from_secs(5))?;
     // Enable Foreign Keys to support ON DELETE CASCADE
@@ -27,7 +92,10 @@ pub fn open_db<P: AsRef<Path>>(path: P) -> SqliteResult<Connection> {
     let _ = conn.pragma_update(None, "journal_mode", "WAL");
     // NORMAL synchronous mode is safe with WAL and much faster for sequential updates
     let _ = conn.pragma_update(None, "synchronous", "NORMAL");
-    Ok(conn)
+    Ok(PooledConnection {
+        conn: Some(conn),
+        path,
+    })
 }
 
 /// Represents the current lifecycle stage of a download.
@@ -79,6 +147,8 @@ pub enum DownloadProtocol {
     Torrent,
     /// Extracted media stream (YouTube, etc.).
     Video,
+    /// FTP or FTPS file transfer.
+    Ftp,
 }
 
 impl DownloadProtocol {
@@ -88,6 +158,7 @@ impl DownloadProtocol {
             DownloadProtocol::Http => "http",
             DownloadProtocol::Torrent => "torrent",
             DownloadProtocol::Video => "video",
+            DownloadProtocol::Ftp => "ftp",
         }
     }
 
@@ -96,6 +167,7 @@ impl DownloadProtocol {
         match s {
             "torrent" => DownloadProtocol::Torrent,
             "video" => DownloadProtocol::Video,
+            "ftp" => DownloadProtocol::Ftp,
             _ => DownloadProtocol::Http,
         }
     }
@@ -138,13 +210,160 @@ pub struct Download {
     pub cookies: Option<String>,
     /// Organizational category (Movies, Music, etc.).
     pub category: String,
+    /// Position in the download queue. Lower values are promoted first.
+    pub priority: i64,
+    /// Per-download speed cap in bytes/sec (0 = only the global limit applies).
+    #[serde(default)]
+    pub speed_limit: i64,
+    /// Proxy URL for this download (falls back to the global `proxy_url` setting).
+    #[serde(default)]
+    pub proxy: Option<String>,
+    /// Custom request headers as a JSON array of `[name, value]` pairs.
+    #[serde(default)]
+    pub headers: Option<String>,
+    /// `ETag` seen when the transfer started, used to validate resumes.
+    #[serde(default)]
+    pub etag: Option<String>,
+    /// `Last-Modified` seen when the transfer started, used when no strong ETag exists.
+    #[serde(default)]
+    pub last_modified: Option<String>,
+    /// Extra mirror URLs as a JSON array; chunks are split across them and `url`.
+    #[serde(default)]
+    pub mirrors: Option<String>,
+    /// Expected digest as `algo:hex` (or bare hex), checked before the file is finalized.
+    #[serde(default)]
+    pub checksum: Option<String>,
+    /// Page the download was started from (e.g. the `Referer`), kept to re-fetch an expired link.
+    #[serde(default)]
+    pub source_page_url: Option<String>,
+    /// HTTP Basic auth user; FTP credentials stay in the URL.
+    #[serde(default)]
+    pub username: Option<String>,
+    /// Password for `username`. Never serialized, so it doesn't reach the frontend or backups.
+    #[serde(default, skip_serializing)]
+    pub password: Option<String>,
+}
+
+/// A schema change, run inside the transaction that records its version.
+type Migration = fn(&Connection) -> SqliteResult<()>;
+
+/// Schema migrations in order; migration `n` (1-based) brings the schema to version `n`.
+///
+/// Append new migrations to the end. Never reorder or edit released ones.
+const MIGRATIONS: &[Migration] = &[
+    migrate_pre_versioning_columns,
+    migrate_checksum_column,
+    migrate_source_page_url_column,
+    migrate_credential_columns,
+];
+
+/// Columns added before schema versioning existed, as `(table, column, definition)`.
+const PRE_VERSIONING_COLUMNS: &[(&str, &str, &str)] = &[
+    ("downloads", "metadata", "TEXT"),
+    ("downloads", "category", "TEXT NOT NULL DEFAULT 'Other'"),
+    ("downloads", "priority", "INTEGER NOT NULL DEFAULT 0"),
+    ("downloads", "speed_limit", "INTEGER NOT NULL DEFAULT 0"),
+    ("downloads", "proxy", "TEXT"),
+    ("downloads", "headers", "TEXT"),
+    ("downloads", "etag", "TEXT"),
+    ("downloads", "last_modified", "TEXT"),
+    ("downloads", "mirrors", "TEXT"),
+    ("chunks", "verified_offset", "INTEGER NOT NULL DEFAULT 0"),
+    ("chunks", "verified_hash", "TEXT"),
+];
+
+fn has_column(conn: &Connection, table: &str, column: &str) -> SqliteResult<bool> {
+    let mut stmt = conn.prepare(&format!("PRAGMA table_info({})", table))?;
+    let names = stmt.query_map([], |row| row.get::<_, String>(1))?;
+    for name in names {
+        if name? == column {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// Version 1: databases from before versioning may lack any of the columns that
+/// used to be added one by one at startup.
+fn migrate_pre_versioning_columns(conn: &Connection) -> SqliteResult<()> {
+    for (table, column, definition) in PRE_VERSIONING_COLUMNS {
+        if !has_column(conn, table, column)? {
+            conn.execute(
+                &format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition),
+                [],
+            )?;
+        }
+    }
+    Ok(())
+}
+
+/// Version 2: the expected digest of a download.
+fn migrate_checksum_column(conn: &Connection) -> SqliteResult<()> {
+    // Fresh databases already have it from `CREATE TABLE`.
+    if !has_column(conn, "downloads", "checksum")? {
+        conn.execute("ALTER TABLE downloads ADD COLUMN checksum TEXT", [])?;
+    }
+    Ok(())
+}
+
+/// Version 3: the page a download was started from.
+fn migrate_source_page_url_column(conn: &Connection) -> SqliteResult<()> {
+    // Fresh databases already have it from `CREATE TABLE`.
+    if !has_column(conn, "downloads", "source_page_url")? {
+        conn.execute("ALTER TABLE downloads ADD COLUMN source_page_url TEXT", [])?;
+    }
+    Ok(())
+}
+
+/// Version 4: HTTP Basic auth credentials.
+fn migrate_credential_columns(conn: &Connection) -> SqliteResult<()> {
+    // Fresh databases already have them from `CREATE TABLE`.
+    for column in ["username", "password"] {
+        if !has_column(conn, "downloads", column)? {
+            conn.execute(
+                &format!("ALTER TABLE downloads ADD COLUMN {} TEXT", column),
+                [],
+            )?;
+        }
+    }
+    Ok(())
+}
+
+/// Applies every migration newer than the stored `schema_version`, each in its own transaction.
+fn run_migrations(conn: &mut Connection) -> SqliteResult<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS meta (key TEXT PRIMARY KEY, value TEXT NOT NULL)",
+        [],
+    )?;
+    let current: usize = conn
+        .query_row(
+            "SELECT value FROM meta WHERE key = 'schema_version'",
+            [],
+            |row| row.get::<_, String>(0),
+        )
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(0);
+
+    for (index, migration) in MIGRATIONS.iter().enumerate().skip(current) {
+        let version = index + 1;
+        let tx = conn.transaction()?;
+        migration(&tx)?;
+        tx.execute(
+            "INSERT OR REPLACE INTO meta (key, value) VALUES ('schema_version', ?1)",
+            [version.to_string()],
+        )?;
+        tx.commit()?;
+        tracing::info!("[DB] Migrated schema to version {}", version);
+    }
+    Ok(())
 }
 
 /// Bootstraps the SQLite database, creates tables, and applies schema migrations.
 ///
 /// This is called once during application startup in `lib.rs`.
 pub fn init_db<P: AsRef<Path>>(path: P) -> SqliteResult<()> {
-    let conn = open_db(path)?;
+    let mut conn = open_db(path)?;
 
     conn.execute_batch(
         "
@@ -167,7 +386,18 @@ pub fn init_db<P: AsRef<Path>>(path: P) -> SqliteResult<()> {
             metadata TEXT,
             user_agent TEXT,
             cookies TEXT,
-            category TEXT NOT NULL DEFAULT 'Other'
+            category TEXT NOT NULL DEFAULT 'Other',
+            priority INTEGER NOT NULL DEFAULT 0,
+            speed_limit INTEGER NOT NULL DEFAULT 0,
+            proxy TEXT,
+            headers TEXT,
+            etag TEXT,
+            last_modified TEXT,
+            mirrors TEXT,
+            checksum TEXT,
+            source_page_url TEXT,
+            username TEXT,
+            password TEXT
         );
         ",
     )?;
@@ -190,6 +420,8 @@ pub fn init_db<P: AsRef<Path>>(path: P) -> SqliteResult<()> {
             end_byte INTEGER NOT NULL,
             downloaded INTEGER NOT NULL DEFAULT 0,
             status TEXT NOT NULL DEFAULT 'pending',
+            verified_offset INTEGER NOT NULL DEFAULT 0,
+            verified_hash TEXT,
             FOREIGN KEY (download_id) REFERENCES downloads(id) ON DELETE CASCADE
         );
 
@@ -203,11 +435,18 @@ pub fn init_db<P: AsRef<Path>>(path: P) -> SqliteResult<()> {
             FOREIGN KEY (download_id) REFERENCES downloads(id) ON DELETE CASCADE
         );
 
+        -- Bytes received per local calendar day (YYYY-MM-DD), across all engines
+        CREATE TABLE IF NOT EXISTS bandwidth_usage (
+            date TEXT PRIMARY KEY,
+            bytes INTEGER NOT NULL DEFAULT 0
+        );
+
         -- Indexes for performance
         CREATE INDEX IF NOT EXISTS idx_downloads_status ON downloads(status);
         CREATE INDEX IF NOT EXISTS idx_downloads_created ON downloads(created_at);
         CREATE INDEX IF NOT EXISTS idx_chunks_download ON chunks(download_id);
         CREATE INDEX IF NOT EXISTS idx_history_download ON history(download_id);
+        CREATE INDEX IF NOT EXISTS idx_history_event ON history(event_type);
 
         -- Insert default settings if not exists
         INSERT OR IGNORE INTO settings (key, value) VALUES
@@ -215,10 +454,16 @@ pub fn init_db<P: AsRef<Path>>(path: P) -> SqliteResult<()> {
             ('max_concurrent', '3'),
             ('max_simultaneous_downloads', '3'),
             ('max_connections', '8'),
+            ('default_connections', '0'),
+            ('per_host_connections', '{}'),
             ('auto_start', 'true'),
+            ('auto_resume', 'true'),
             ('notifications', 'true'),
             ('speed_limit', '0'),
             ('autocatch_enabled', 'true'),
+            ('autocatch_allowlist', ''),
+            ('autocatch_blocklist', ''),
+            ('autocatch_rules', '[]'),
             ('torrent_encryption', 'false'),
             ('open_folder_on_finish', 'false'),
             ('shutdown_on_finish', 'false'),
@@ -233,58 +478,45 @@ pub fn init_db<P: AsRef<Path>>(path: P) -> SqliteResult<()> {
             ('cookie_browser', 'none'),
             ('ask_location', 'false'),
             ('auto_organize', 'false'),
-            ('force_multi_http', 'false');
+            ('category_rules', ''),
+            ('organize_on_finish', 'false'),
+            ('post_download_command', ''),
+            ('post_download_webhook', ''),
+            ('finish_action', 'none'),
+            ('auto_cleanup_days', '0'),
+            ('auto_cleanup_remove_files', 'false'),
+            ('force_multi_http', 'false'),
+            ('gaming_mode', 'false'),
+            ('gaming_speed_limit', '524288'),
+            ('max_open_handles', '64'),
+            ('notification_events', 'all'),
+            ('stream_read_timeout', '60'),
+            ('stream_read_timeout_adaptive', 'false'),
+            ('watch_folder_enabled', 'false'),
+            ('watch_folder', ''),
+            ('proxy_url', ''),
+            ('resume_verify', 'false'),
+            ('stall_timeout', '20'),
+            ('write_buffer_kb', '0'),
+            ('durable_writes', 'false'),
+            ('detailed_progress', 'false'),
+            ('log_level', 'info'),
+            ('video_probe', 'false'),
+            ('monthly_cap_bytes', '0'),
+            ('speed_schedule', ''),
+            ('queue_include_torrents', 'true'),
+            ('seed_enabled', 'false'),
+            ('seed_ratio_limit', '0'),
+            ('seed_time_limit', '0'),
+            ('torrent_upload_limit', '0'),
+            ('torrent_download_limit', '0'),
+            ('torrent_listen_port', '0'),
+            ('torrent_enable_dht', 'true'),
+            ('torrent_enable_pex', 'true');
         ",
     )?;
 
-    // Migration: Add metadata column to downloads table if it doesn't exist
-    {
-        let mut stmt = conn.prepare("PRAGMA table_info(downloads)")?;
-        let columns = stmt.query_map([], |row| {
-            let name: String = row.get(1)?;
-            Ok(name)
-        })?;
-
-        let mut has_metadata = false;
-        for col in columns {
-            if let Ok(name) = col {
-                if name == "metadata" {
-                    has_metadata = true;
-                    break;
-                }
-            }
-        }
-
-        if !has_metadata {
-            conn.execute("ALTER TABLE downloads ADD COLUMN metadata TEXT ", [])?;
-        }
-    }
-
-    // Migration: Add category column to downloads table if it doesn't exist
-    {
-        let mut stmt = conn.prepare("PRAGMA table_info(downloads)")?;
-        let columns = stmt.query_map([], |row| {
-            let name: String = row.get(1)?;
-            Ok(name)
-        })?;
-
-        let mut has_category = false;
-        for col in columns {
-            if let Ok(name) = col {
-                if name == "category" {
-                    has_category = true;
-                    break;
-                }
-            }
-        }
-
-        if !has_category {
-            conn.execute(
-                "ALTER TABLE downloads ADD COLUMN category TEXT NOT NULL DEFAULT 'Other'",
-                [],
-            )?;
-        }
-    }
+    run_migrations(&mut conn)?;
 
     Ok(())
 }
@@ -311,6 +543,17 @@ fn row_to_download(row: &rusqlite::Row) -> SqliteResult<Download> {
         user_agent: row.get(15)?,
         cookies: row.get(16)?,
         category: row.get(17)?,
+        priority: row.get(18)?,
+        speed_limit: row.get(19)?,
+        proxy: row.get(20)?,
+        headers: row.get(21)?,
+        etag: row.get(22)?,
+        last_modified: row.get(23)?,
+        mirrors: row.get(24)?,
+        checksum: row.get(25)?,
+        source_page_url: row.get(26)?,
+        username: row.get(27)?,
+        password: row.get(28)?,
     })
 }
 
@@ -318,7 +561,7 @@ fn row_to_download(row: &rusqlite::Row) -> SqliteResult<Download> {
 pub fn get_all_downloads<P: AsRef<Path>>(db_path: P) -> SqliteResult<Vec<Download>> {
     let conn = open_db(db_path)?;
     let mut stmt = conn.prepare(
-        "SELECT id, url, filename, filepath, size, downloaded, status, protocol, speed, connections, created_at, completed_at, error_message, info_hash, metadata, user_agent, cookies, category
+        "SELECT id, url, filename, filepath, size, downloaded, status, protocol, speed, connections, created_at, completed_at, error_message, info_hash, metadata, user_agent, cookies, category, priority, speed_limit, proxy, headers, etag, last_modified, mirrors, checksum, source_page_url, username, password
          FROM downloads
          ORDER BY created_at DESC "
     )?;
@@ -330,11 +573,33 @@ pub fn get_all_downloads<P: AsRef<Path>>(db_path: P) -> SqliteResult<Vec<Downloa
     Ok(downloads)
 }
 
+/// Like [`get_all_downloads`], but queued downloads come first in promotion order.
+///
+/// Everything else keeps the newest-first order.
+pub fn get_all_downloads_queue_order<P: AsRef<Path>>(db_path: P) -> SqliteResult<Vec<Download>> {
+    let conn = open_db(db_path)?;
+    let mut stmt = conn.prepare(
+        "SELECT id, url, filename, filepath, size, downloaded, status, protocol, speed, connections, created_at, completed_at, error_message, info_hash, metadata, user_agent, cookies, category, priority, speed_limit, proxy, headers, etag, last_modified, mirrors, checksum, source_page_url, username, password
+         FROM downloads
+         ORDER BY
+            CASE WHEN status = 'queued' THEN 0 ELSE 1 END,
+            CASE WHEN status = 'queued' THEN priority END ASC,
+            CASE WHEN status = 'queued' THEN created_at END ASC,
+            created_at DESC"
+    )?;
+
+    let downloads = stmt
+        .query_map([], |row| row_to_download(row))?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(downloads)
+}
+
 /// Retrieves all downloads that have successfully reached the 'completed' status.
 pub fn get_history<P: AsRef<Path>>(db_path: P) -> SqliteResult<Vec<Download>> {
     let conn = open_db(db_path)?;
     let mut stmt = conn.prepare(
-        "SELECT id, url, filename, filepath, size, downloaded, status, protocol, speed, connections, created_at, completed_at, error_message, info_hash, metadata, user_agent, cookies, category
+        "SELECT id, url, filename, filepath, size, downloaded, status, protocol, speed, connections, created_at, completed_at, error_message, info_hash, metadata, user_agent, cookies, category, priority, speed_limit, proxy, headers, etag, last_modified, mirrors, checksum, source_page_url, username, password
          FROM downloads
          WHERE status = 'completed'
          ORDER BY completed_at DESC "
@@ -350,9 +615,13 @@ pub fn get_history<P: AsRef<Path>>(db_path: P) -> SqliteResult<Vec<Download>> {
 /// Persists a new download record to the database.
 pub fn insert_download<P: AsRef<Path>>(db_path: P, download: &Download) -> SqliteResult<()> {
     let conn = open_db(db_path)?;
+    insert_download_row(&conn, download)
+}
+
+fn insert_download_row(conn: &Connection, download: &Download) -> SqliteResult<()> {
     conn.execute(
-        "INSERT INTO downloads (id, url, filename, filepath, size, downloaded, status, protocol, speed, connections, created_at, completed_at, error_message, info_hash, metadata, user_agent, cookies, category)
-         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18)",
+        "INSERT INTO downloads (id, url, filename, filepath, size, downloaded, status, protocol, speed, connections, created_at, completed_at, error_message, info_hash, metadata, user_agent, cookies, category, priority, speed_limit, proxy, headers, etag, last_modified, mirrors, checksum, source_page_url, username, password)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19, ?20, ?21, ?22, ?23, ?24, ?25, ?26, ?27, ?28, ?29)",
         rusqlite::params![
             &download.id,
             &download.url,
@@ -372,6 +641,17 @@ pub fn insert_download<P: AsRef<Path>>(db_path: P, download: &Download) -> Sqlit
             &download.user_agent,
             &download.cookies,
             &download.category,
+            download.priority,
+            download.speed_limit,
+            &download.proxy,
+            &download.headers,
+            &download.etag,
+            &download.last_modified,
+            &download.mirrors,
+            &download.checksum,
+            &download.source_page_url,
+            &download.username,
+            &download.password,
         ],
     )?;
     Ok(())
@@ -457,6 +737,103 @@ pub fn update_download_name<P: AsRef<Path>>(db_path: P, id: &str, name: &str) ->
     Ok(())
 }
 
+/// Updates where a download's file lives, e.g. after it was moved on completion.
+pub fn update_download_path<P: AsRef<Path>>(db_path: P, id: &str, path: &str) -> SqliteResult<()> {
+    let conn = open_db(db_path)?;
+    conn.execute(
+        "UPDATE downloads SET filepath = ?1 WHERE id = ?2",
+        (path, id),
+    )?;
+    Ok(())
+}
+
+/// Replaces the URL a download is fetched from, e.g. after its signed link expired.
+pub fn update_download_url<P: AsRef<Path>>(db_path: P, id: &str, url: &str) -> SqliteResult<()> {
+    let conn = open_db(db_path)?;
+    conn.execute("UPDATE downloads SET url = ?1 WHERE id = ?2", (url, id))?;
+    Ok(())
+}
+
+/// Records the BitTorrent info hash (hex) of a torrent download.
+pub fn update_download_info_hash<P: AsRef<Path>>(
+    db_path: P,
+    id: &str,
+    info_hash: &str,
+) -> SqliteResult<()> {
+    let conn = open_db(db_path)?;
+    conn.execute(
+        "UPDATE downloads SET info_hash = ?1 WHERE id = ?2",
+        (info_hash, id),
+    )?;
+    Ok(())
+}
+
+/// Bytes recorded as downloaded for one download.
+pub fn get_download_progress<P: AsRef<Path>>(db_path: P, id: &str) -> SqliteResult<i64> {
+    let conn = open_db(db_path)?;
+    conn.query_row(
+        "SELECT downloaded FROM downloads WHERE id = ?1",
+        [id],
+        |row| row.get(0),
+    )
+}
+
+/// Stores the `ETag` / `Last-Modified` validators of the remote file.
+pub fn update_download_validator<P: AsRef<Path>>(
+    db_path: P,
+    id: &str,
+    etag: Option<&str>,
+    last_modified: Option<&str>,
+) -> SqliteResult<()> {
+    let conn = open_db(db_path)?;
+    conn.execute(
+        "UPDATE downloads SET etag = ?1, last_modified = ?2 WHERE id = ?3",
+        (etag, last_modified, id),
+    )?;
+    Ok(())
+}
+
+/// Sets a download's own speed cap in bytes/sec (0 = no per-download cap).
+pub fn update_download_speed_limit<P: AsRef<Path>>(
+    db_path: P,
+    id: &str,
+    speed_limit: i64,
+) -> SqliteResult<()> {
+    let conn = open_db(db_path)?;
+    conn.execute(
+        "UPDATE downloads SET speed_limit = ?1 WHERE id = ?2",
+        (speed_limit, id),
+    )?;
+    Ok(())
+}
+
+/// Updates the connection count used the next time the download starts.
+pub fn update_download_connections<P: AsRef<Path>>(
+    db_path: P,
+    id: &str,
+    connections: i32,
+) -> SqliteResult<()> {
+    let conn = open_db(db_path)?;
+    conn.execute(
+        "UPDATE downloads SET connections = ?1 WHERE id = ?2",
+        (connections, id),
+    )?;
+    Ok(())
+}
+
+pub fn update_download_category<P: AsRef<Path>>(
+    db_path: P,
+    id: &str,
+    category: &str,
+) -> SqliteResult<()> {
+    let conn = open_db(db_path)?;
+    conn.execute(
+        "UPDATE downloads SET category = ?1 WHERE id = ?2",
+        (category, id),
+    )?;
+    Ok(())
+}
+
 pub fn update_download_cookies<P: AsRef<Path>>(
     db_path: P,
     id: &str,
@@ -470,6 +847,19 @@ pub fn update_download_cookies<P: AsRef<Path>>(
     Ok(())
 }
 
+pub fn update_download_password<P: AsRef<Path>>(
+    db_path: P,
+    id: &str,
+    password: &str,
+) -> SqliteResult<()> {
+    let conn = open_db(db_path)?;
+    conn.execute(
+        "UPDATE downloads SET password = ?1 WHERE id = ?2",
+        (password, id),
+    )?;
+    Ok(())
+}
+
 /// Removes a download record and its associated chunks/history from the database.
 pub fn delete_download_by_id<P: AsRef<Path>>(db_path: P, id: &str) -> SqliteResult<()> {
     let conn = open_db(db_path)?;
@@ -502,7 +892,7 @@ pub fn find_download_by_url<P: AsRef<Path>>(
     url: &str,
 ) -> SqliteResult<Option<Download>> {
     let conn = open_db(db_path)?;
-    let mut stmt = conn.prepare("SELECT id, url, filename, filepath, size, downloaded, status, protocol, speed, connections, created_at, completed_at, error_message, info_hash, metadata, user_agent, cookies, category FROM downloads WHERE url = ?1")?;
+    let mut stmt = conn.prepare("SELECT id, url, filename, filepath, size, downloaded, status, protocol, speed, connections, created_at, completed_at, error_message, info_hash, metadata, user_agent, cookies, category, priority, speed_limit, proxy, headers, etag, last_modified, mirrors, checksum, source_page_url, username, password FROM downloads WHERE url = ?1")?;
 
     let mut rows = stmt.query([url])?;
     if let Some(row) = rows.next()? {
@@ -552,6 +942,48 @@ pub fn update_chunk_progress<P: AsRef<Path>>(
     Ok(())
 }
 
+/// Writes the progress of several chunks in one transaction.
+///
+/// Progress only moves forward here, so a batch flushed after a worker's own
+/// final write cannot roll that chunk back.
+pub fn update_chunk_progress_batch<P: AsRef<Path>>(
+    db_path: P,
+    download_id: &str,
+    updates: Vec<(i64, i64)>,
+) -> SqliteResult<()> {
+    if updates.is_empty() {
+        return Ok(());
+    }
+    let mut conn = open_db(db_path)?;
+    let tx = conn.transaction()?;
+    {
+        let mut stmt = tx.prepare_cached(
+            "UPDATE chunks SET downloaded = MAX(downloaded, ?1) WHERE download_id = ?2 AND start_byte = ?3",
+        )?;
+        for (start_byte, downloaded) in updates {
+            stmt.execute((downloaded, download_id, start_byte))?;
+        }
+    }
+    tx.commit()
+}
+
+/// Records chunk progress that has been flushed to disk, with the checksum of
+/// the bytes just before it, so a later resume can confirm they survived.
+pub fn update_chunk_checkpoint<P: AsRef<Path>>(
+    db_path: P,
+    download_id: &str,
+    start_byte: i64,
+    downloaded: i64,
+    verified_hash: &str,
+) -> SqliteResult<()> {
+    let conn = open_db(db_path)?;
+    conn.execute(
+        "UPDATE chunks SET downloaded = ?1, verified_offset = ?1, verified_hash = ?2 WHERE download_id = ?3 AND start_byte = ?4",
+        (downloaded, verified_hash, download_id, start_byte),
+    )?;
+    Ok(())
+}
+
 pub fn update_download_metadata<P: AsRef<Path>>(
     db_path: P,
     id: &str,
@@ -565,6 +997,40 @@ pub fn update_download_metadata<P: AsRef<Path>>(
     Ok(())
 }
 
+/// Replaces a download's chunk layout in a single transaction.
+pub fn replace_download_chunks<P: AsRef<Path>>(
+    db_path: P,
+    download_id: &str,
+    chunks: Vec<crate::downloader::ChunkRecord>,
+) -> SqliteResult<()> {
+    let mut conn = open_db(db_path)?;
+    let tx = conn.transaction()?;
+    {
+        tx.execute("DELETE FROM chunks WHERE download_id = ?1", [download_id])?;
+        for chunk in chunks {
+            let status = if chunk.downloaded >= chunk.end - chunk.start + 1 {
+                "completed"
+            } else {
+                "pending"
+            };
+            tx.execute(
+                "INSERT INTO chunks (download_id, start_byte, end_byte, downloaded, status, verified_offset, verified_hash) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                (
+                    download_id,
+                    chunk.start,
+                    chunk.end,
+                    chunk.downloaded,
+                    status,
+                    chunk.verified,
+                    &chunk.verified_hash,
+                ),
+            )?;
+        }
+    }
+    tx.commit()?;
+    Ok(())
+}
+
 /// Removes all chunk records for a specific download.
 pub fn delete_download_chunks<P: AsRef<Path>>(db_path: P, download_id: &str) -> SqliteResult<()> {
     let conn = open_db(db_path)?;
@@ -577,8 +1043,9 @@ pub fn get_download_chunks<P: AsRef<Path>>(
     download_id: &str,
 ) -> SqliteResult<Vec<crate::downloader::ChunkRecord>> {
     let conn = open_db(db_path)?;
-    let mut stmt =
-        conn.prepare("SELECT start_byte, end_byte, downloaded FROM chunks WHERE download_id = ?1")?;
+    let mut stmt = conn.prepare(
+        "SELECT start_byte, end_byte, downloaded, verified_offset, verified_hash FROM chunks WHERE download_id = ?1",
+    )?;
     let chunks = stmt
         .query_map([download_id], |row| {
             Ok(crate::downloader::ChunkRecord {
@@ -586,12 +1053,127 @@ pub fn get_download_chunks<P: AsRef<Path>>(
                 start: row.get(0)?,
                 end: row.get(1)?,
                 downloaded: row.get(2)?,
+                verified: row.get(3)?,
+                verified_hash: row.get(4)?,
             })
         })?
         .collect::<Result<Vec<_>, _>>()?;
     Ok(chunks)
 }
 
+/// Adds received bytes to the usage total of `date` (YYYY-MM-DD).
+pub fn add_bandwidth_usage<P: AsRef<Path>>(db_path: P, date: &str, bytes: u64) -> SqliteResult<()> {
+    let conn = open_db(db_path)?;
+    conn.execute(
+        "INSERT INTO bandwidth_usage (date, bytes) VALUES (?1, ?2)
+         ON CONFLICT(date) DO UPDATE SET bytes = bytes + excluded.bytes",
+        (date, bytes as i64),
+    )?;
+    Ok(())
+}
+
+/// Daily usage totals between two dates (inclusive, YYYY-MM-DD), oldest first.
+pub fn get_bandwidth_usage<P: AsRef<Path>>(
+    db_path: P,
+    from: &str,
+    to: &str,
+) -> SqliteResult<Vec<(String, i64)>> {
+    let conn = open_db(db_path)?;
+    let mut stmt = conn.prepare(
+        "SELECT date, bytes FROM bandwidth_usage WHERE date >= ?1 AND date <= ?2 ORDER BY date",
+    )?;
+    let rows = stmt
+        .query_map([from, to], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(rows)
+}
+
+/// Total bytes received during a month, given as `YYYY-MM`.
+pub fn get_monthly_bandwidth_usage<P: AsRef<Path>>(db_path: P, month: &str) -> SqliteResult<i64> {
+    let conn = open_db(db_path)?;
+    conn.query_row(
+        "SELECT COALESCE(SUM(bytes), 0) FROM bandwidth_usage WHERE date LIKE ?1",
+        [format!("{}-%", month)],
+        |row| row.get(0),
+    )
+}
+
+/// Aggregate figures over the whole download registry.
+#[derive(Debug, Serialize)]
+pub struct DownloadStatistics {
+    /// Bytes of all completed downloads.
+    pub total_downloaded: i64,
+    /// Number of downloads per status (`completed`, `paused`, ...).
+    pub by_status: std::collections::HashMap<String, i64>,
+    /// Number of downloads per category.
+    pub by_category: std::collections::HashMap<String, i64>,
+    /// Mean speed of completed downloads in bytes/sec, from when each was added
+    /// until it completed.
+    pub average_speed: Option<f64>,
+    /// Local hour (0-23) in which downloads are most often added or started.
+    pub busiest_hour: Option<u8>,
+}
+
+/// Computes [`DownloadStatistics`] with a few aggregate queries.
+pub fn get_statistics<P: AsRef<Path>>(db_path: P) -> SqliteResult<DownloadStatistics> {
+    let conn = open_db(db_path)?;
+
+    let total_downloaded = conn.query_row(
+        "SELECT COALESCE(SUM(downloaded), 0) FROM downloads WHERE status = 'completed'",
+        [],
+        |row| row.get(0),
+    )?;
+
+    let count_by = |column: &str| -> SqliteResult<std::collections::HashMap<String, i64>> {
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {column}, COUNT(*) FROM downloads GROUP BY {column}"
+        ))?;
+        let counts = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<Result<_, _>>()?;
+        Ok(counts)
+    };
+    let by_status = count_by("status")?;
+    let by_category = count_by("category")?;
+
+    let average_speed = conn.query_row(
+        "SELECT AVG(downloaded / ((julianday(completed_at) - julianday(created_at)) * 86400.0))
+         FROM downloads
+         WHERE status = 'completed' AND completed_at IS NOT NULL
+           AND julianday(completed_at) > julianday(created_at)",
+        [],
+        |row| row.get::<_, Option<f64>>(0),
+    )?;
+
+    let busiest_hour = conn.query_row(
+        "SELECT hour FROM (
+             SELECT strftime('%H', created_at, 'localtime') AS hour FROM downloads
+             UNION ALL
+             SELECT strftime('%H', timestamp, 'localtime') FROM history
+             WHERE event_type IN ('started', 'resumed')
+         )
+         WHERE hour IS NOT NULL
+         GROUP BY hour
+         ORDER BY COUNT(*) DESC, hour ASC
+         LIMIT 1",
+        [],
+        |row| row.get::<_, String>(0),
+    );
+    let busiest_hour = match busiest_hour {
+        Ok(hour) => hour.parse().ok(),
+        Err(rusqlite::Error::QueryReturnedNoRows) => None,
+        Err(e) => return Err(e),
+    };
+
+    Ok(DownloadStatistics {
+        total_downloaded,
+        by_status,
+        by_category,
+        average_speed,
+        busiest_hour,
+    })
+}
+
 /// Get all settings as key-value pairs
 pub fn get_all_settings<P: AsRef<Path>>(
     db_path: P,
@@ -641,13 +1223,120 @@ pub fn get_download_events<P: AsRef<Path>>(
     Ok(events)
 }
 
-/// Delete all finished (completed or error) downloads
-pub fn delete_finished_downloads<P: AsRef<Path>>(db_path: P) -> SqliteResult<()> {
+/// One row of the `history` table.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryEvent {
+    pub download_id: String,
+    pub event_type: String,
+    pub timestamp: String,
+    pub details: Option<String>,
+}
+
+/// Every history event, oldest first.
+pub fn get_all_history<P: AsRef<Path>>(db_path: P) -> SqliteResult<Vec<HistoryEvent>> {
     let conn = open_db(db_path)?;
-    conn.execute(
-        "DELETE FROM downloads WHERE status = 'completed' OR status = 'error'",
-        [],
-    )?;
+    let mut stmt = conn
+        .prepare("SELECT download_id, event_type, timestamp, details FROM history ORDER BY id")?;
+    let events = stmt
+        .query_map([], |row| {
+            Ok(HistoryEvent {
+                download_id: row.get(0)?,
+                event_type: row.get(1)?,
+                timestamp: row.get(2)?,
+                details: row.get(3)?,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(events)
+}
+
+/// Restores a backup in a single transaction and returns how many downloads were added.
+///
+/// With `replace`, all downloads (and their chunks and history) are removed first
+/// and the backup's settings overwrite the current ones. Otherwise downloads whose
+/// ID already exists are skipped and current settings win. History is restored
+/// only for the downloads that were added.
+pub fn import_backup<P: AsRef<Path>>(
+    db_path: P,
+    downloads: &[Download],
+    settings: &std::collections::HashMap<String, String>,
+    history: &[HistoryEvent],
+    replace: bool,
+) -> SqliteResult<usize> {
+    let mut conn = open_db(db_path)?;
+    let tx = conn.transaction()?;
+    if replace {
+        tx.execute("DELETE FROM downloads", [])?;
+    }
+
+    let mut added = std::collections::HashSet::new();
+    for download in downloads {
+        let exists: bool = tx.query_row(
+            "SELECT EXISTS(SELECT 1 FROM downloads WHERE id = ?1)",
+            [&download.id],
+            |row| row.get(0),
+        )?;
+        if !exists {
+            insert_download_row(&tx, download)?;
+            added.insert(download.id.as_str());
+        }
+    }
+
+    let settings_sql = if replace {
+        "INSERT OR REPLACE INTO settings (key, value) VALUES (?1, ?2)"
+    } else {
+        "INSERT OR IGNORE INTO settings (key, value) VALUES (?1, ?2)"
+    };
+    for (key, value) in settings {
+        tx.execute(settings_sql, (key, value))?;
+    }
+
+    for event in history
+        .iter()
+        .filter(|e| added.contains(e.download_id.as_str()))
+    {
+        tx.execute(
+            "INSERT INTO history (download_id, event_type, timestamp, details) VALUES (?1, ?2, ?3, ?4)",
+            (&event.download_id, &event.event_type, &event.timestamp, &event.details),
+        )?;
+    }
+
+    tx.commit()?;
+    Ok(added.len())
+}
+
+/// Which finished downloads [`delete_finished_downloads`] removes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FinishedFilter {
+    Completed,
+    Error,
+    Both,
+}
+
+impl FinishedFilter {
+    /// True if a download in `status` is removed by this filter.
+    pub fn matches(&self, status: &DownloadStatus) -> bool {
+        match self {
+            Self::Completed => *status == DownloadStatus::Completed,
+            Self::Error => *status == DownloadStatus::Error,
+            Self::Both => matches!(status, DownloadStatus::Completed | DownloadStatus::Error),
+        }
+    }
+}
+
+/// Delete finished downloads matching `filter`; returns how many were removed.
+pub fn delete_finished_downloads<P: AsRef<Path>>(
+    db_path: P,
+    filter: FinishedFilter,
+) -> SqliteResult<usize> {
+    let conn = open_db(db_path)?;
+    let condition = match filter {
+        FinishedFilter::Completed => "status = 'completed'",
+        FinishedFilter::Error => "status = 'error'",
+        FinishedFilter::Both => "status = 'completed' OR status = 'error'",
+    };
+    let removed = conn.execute(&format!("DELETE FROM downloads WHERE {}", condition), [])?;
 
     // Also cleanup related chunks and history
     let _ = conn.execute(
@@ -659,17 +1348,47 @@ pub fn delete_finished_downloads<P: AsRef<Path>>(db_path: P) -> SqliteResult<()>
         [],
     );
 
-    Ok(())
+    Ok(removed)
+}
+
+/// Deletes completed downloads that finished before `cutoff` (RFC 3339), in one transaction.
+///
+/// Chunks and history go with them through the cascade. Returns the deleted rows
+/// so the caller can clean up their files.
+pub fn delete_completed_before<P: AsRef<Path>>(
+    db_path: P,
+    cutoff: &str,
+) -> SqliteResult<Vec<Download>> {
+    let mut conn = open_db(db_path)?;
+    let tx = conn.transaction()?;
+    let deleted: Vec<Download> = {
+        let mut stmt = tx.prepare(
+            "SELECT id, url, filename, filepath, size, downloaded, status, protocol, speed, connections, created_at, completed_at, error_message, info_hash, metadata, user_agent, cookies, category, priority, speed_limit, proxy, headers, etag, last_modified, mirrors, checksum, source_page_url, username, password
+             FROM downloads
+             WHERE status = 'completed' AND completed_at IS NOT NULL
+               AND julianday(completed_at) < julianday(?1)",
+        )?;
+        let rows = stmt.query_map([cutoff], row_to_download)?;
+        rows.collect::<Result<_, _>>()?
+    };
+    tx.execute(
+        "DELETE FROM downloads
+         WHERE status = 'completed' AND completed_at IS NOT NULL
+           AND julianday(completed_at) < julianday(?1)",
+        [cutoff],
+    )?;
+    tx.commit()?;
+    Ok(deleted)
 }
 
 /// Retrieves the next queued download (oldest first).
 pub fn get_next_queued_download<P: AsRef<Path>>(db_path: P) -> SqliteResult<Option<Download>> {
     let conn = open_db(db_path)?;
     let mut stmt = conn.prepare(
-        "SELECT id, url, filename, filepath, size, downloaded, status, protocol, speed, connections, created_at, completed_at, error_message, info_hash, metadata, user_agent, cookies, category
+        "SELECT id, url, filename, filepath, size, downloaded, status, protocol, speed, connections, created_at, completed_at, error_message, info_hash, metadata, user_agent, cookies, category, priority, speed_limit, proxy, headers, etag, last_modified, mirrors, checksum, source_page_url, username, password
          FROM downloads
          WHERE status = 'queued'
-         ORDER BY created_at ASC
+         ORDER BY priority ASC, created_at ASC
          LIMIT 1"
     )?;
 
@@ -681,3 +1400,47 @@ pub fn get_next_queued_download<P: AsRef<Path>>(db_path: P) -> SqliteResult<Opti
         Ok(None)
     }
 }
+
+/// Returns the priority value that places a new download at the back of the queue.
+pub fn get_next_priority<P: AsRef<Path>>(db_path: P) -> SqliteResult<i64> {
+    let conn = open_db(db_path)?;
+    conn.query_row(
+        "SELECT COALESCE(MAX(priority), -1) + 1 FROM downloads",
+        [],
+        |row| row.get(0),
+    )
+}
+
+/// Rewrites queue priorities so they follow the given order (first ID = highest priority).
+///
+/// All updates run in a single transaction so the queue is never observed half-sorted.
+pub fn reorder_queue<P: AsRef<Path>>(db_path: P, ordered_ids: &[String]) -> SqliteResult<()> {
+    let mut conn = open_db(db_path)?;
+    let tx = conn.transaction()?;
+    {
+        for (position, id) in ordered_ids.iter().enumerate() {
+            tx.execute(
+                "UPDATE downloads SET priority = ?1 WHERE id = ?2",
+                (position as i64, id),
+            )?;
+        }
+    }
+    tx.commit()?;
+    Ok(())
+}
+
+/// Moves a single download to the front or the back of the queue.
+pub fn move_download_to_queue_edge<P: AsRef<Path>>(
+    db_path: P,
+    id: &str,
+    to_top: bool,
+) -> SqliteResult<()> {
+    let conn = open_db(db_path)?;
+    let sql = if to_top {
+        "UPDATE downloads SET priority = (SELECT COALESCE(MIN(priority), 0) - 1 FROM downloads) WHERE id = ?1"
+    } else {
+        "UPDATE downloads SET priority = (SELECT COALESCE(MAX(priority), 0) + 1 FROM downloads) WHERE id = ?1"
+    };
+    conn.execute(sql, [id])?;
+    Ok(())
+}
@@ -1,20 +1,212 @@
 use futures::StreamExt;
 use reqwest::Client;
-use reqwest::header::{ACCEPT, REFERER};
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue, ACCEPT, ACCEPT_ENCODING, REFERER};
 use sha2::{Digest, Sha256};
 use std::fs::File;
 use std::io::Read;
-use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::atomic::{AtomicU64, AtomicU8, Ordering};
 use std::sync::Arc;
-use tokio::io::{AsyncWriteExt, BufWriter};
+use tokio::io::{AsyncSeekExt, AsyncWriteExt, BufWriter};
 
+mod resolvers;
+#[cfg(test)]
+mod test_server;
+#[cfg(test)]
+mod tests;
 mod types;
 mod workers;
-pub use types::{ChunkRecord, DownloadConfig, DownloadError, DownloadProgress};
-use types::{SharedRateLimiter, WorkChunk};
-use workers::{run_workers, SpeedState, WorkerOrchestrationConfig, WorkerOutcome};
+pub use resolvers::{has_resolver, resolve_direct_url};
+pub use types::{
+    ChecksumAlgo, ChunkRecord, DownloadConfig, DownloadError, DownloadProgress, ResourceValidator,
+    SharedRateLimiter,
+};
+use types::{ReadThroughput, WorkChunk};
+use workers::{
+    flush_file, run_workers, wait_for_cancel, MirrorPool, SpeedState, WorkerOrchestrationConfig,
+    WorkerOutcome, CHUNK_WRITE_BUFFER, MIN_SPEED_PER_WORKER,
+};
 
 const RANGE_PROBE_TIMEOUT_SECS: u64 = 2;
+/// User-Agent sent when a download doesn't specify its own.
+pub const DEFAULT_USER_AGENT: &str = "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36";
+/// Floor for the adaptive stream read timeout.
+const MIN_ADAPTIVE_READ_TIMEOUT_SECS: u64 = 10;
+/// Throughput at or below which the adaptive read timeout uses the full configured value.
+const ADAPTIVE_READ_TIMEOUT_REFERENCE_BPS: u64 = 512 * 1024;
+/// Connect timeout for the first attempt. reqwest already races IPv6 and IPv4
+/// (happy eyeballs), and splits the timeout across resolved addresses, so a short
+/// value keeps a blackholed address family from stalling the start of a download.
+const FIRST_CONNECT_TIMEOUT_SECS: u64 = 4;
+/// Connect timeout for retries, where slow-but-working paths should get more time.
+const RETRY_CONNECT_TIMEOUT_SECS: u64 = 10;
+/// Upper bound for a single chunk so throttled transfers keep cycling requests.
+const MAX_CHUNK_SIZE: u64 = 10 * 1024 * 1024;
+/// Floor for speed-limited chunk sizing, so tiny limits don't turn into a request storm.
+const MIN_LIMITED_CHUNK_SIZE: u64 = 256 * 1024;
+/// Transfer time a chunk should take under a speed limit.
+const LIMITED_CHUNK_SECS: u64 = 5;
+/// Built-in write buffer of a single-connection transfer, larger than a chunk worker's.
+const SINGLE_WRITE_BUFFER: usize = 256 * 1024;
+/// Suffix of the temporary file a download writes to until it completes.
+pub const PART_FILE_SUFFIX: &str = ".ciel-part";
+/// Bytes re-read behind each `resume_verify` checkpoint.
+const RESUME_VERIFY_WINDOW: u64 = 64 * 1024;
+/// Speed samples the reported speed is averaged over.
+const SPEED_WINDOW: usize = 10;
+/// Weight of the newest speed sample; each older one counts `1 - SPEED_SMOOTHING` as much as the next.
+const SPEED_SMOOTHING: f64 = 0.3;
+
+/// Smooths speed samples into the speed and ETA shown to the user.
+///
+/// The last [`SPEED_WINDOW`] samples are weighted exponentially, so a real
+/// change shows up within a few emissions while one noisy interval barely moves it.
+#[derive(Debug, Default)]
+pub struct SpeedAverager {
+    samples: std::collections::VecDeque<f64>,
+}
+
+impl SpeedAverager {
+    /// Adds a sample and returns the smoothed speed.
+    pub fn record(&mut self, bytes_per_sec: f64) -> u64 {
+        if self.samples.len() == SPEED_WINDOW {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(bytes_per_sec);
+
+        let (mut weighted, mut weights, mut weight) = (0.0, 0.0, 1.0);
+        for sample in self.samples.iter().rev() {
+            weighted += sample * weight;
+            weights += weight;
+            weight *= 1.0 - SPEED_SMOOTHING;
+        }
+        (weighted / weights) as u64
+    }
+
+    /// Forgets earlier samples, e.g. after a stall.
+    pub fn reset(&mut self) {
+        self.samples.clear();
+    }
+
+    /// Records the speed over the last `elapsed_secs` and updates `speed`, `instant_speed` and `eta`.
+    pub fn apply(&mut self, progress: &mut DownloadProgress, bytes: u64, elapsed_secs: f64) {
+        let instant = bytes as f64 / elapsed_secs;
+        progress.instant_speed = instant as u64;
+        progress.speed = self.record(instant);
+        if progress.speed > 0 {
+            progress.eta = progress.total.saturating_sub(progress.downloaded) / progress.speed;
+        }
+    }
+}
+
+/// Path of the in-progress `.ciel-part` file for a download's final path.
+pub fn part_file_path(filepath: &std::path::Path) -> std::path::PathBuf {
+    let mut name = filepath.as_os_str().to_os_string();
+    name.push(PART_FILE_SUFFIX);
+    std::path::PathBuf::from(name)
+}
+
+/// CRC32 of the [`RESUME_VERIFY_WINDOW`] bytes ending at `end`, never reaching before `chunk_start`.
+///
+/// The part file is preallocated, so bytes that never left a write buffer read back as zeros.
+fn checkpoint_hash(path: &std::path::Path, chunk_start: u64, end: u64) -> std::io::Result<String> {
+    use std::io::{Seek, SeekFrom};
+    let start = end.saturating_sub(RESUME_VERIFY_WINDOW).max(chunk_start);
+    let mut file = File::open(path)?;
+    file.seek(SeekFrom::Start(start))?;
+    let mut window = vec![0u8; end.saturating_sub(start) as usize];
+    file.read_exact(&mut window)?;
+    Ok(format!("{:08x}", crc32fast::hash(&window)))
+}
+
+/// Re-splits the unfinished byte ranges of a chunk layout for a new connection count.
+///
+/// Finished bytes are kept as (completed) chunks of their own; only the remaining
+/// ranges are subdivided, targeting 8 chunks per connection like a fresh download.
+pub fn split_remaining_chunks(chunks: &[ChunkRecord], connections: u8) -> Vec<ChunkRecord> {
+    let remaining: u64 = chunks
+        .iter()
+        .map(|c| ((c.end - c.start + 1) - c.downloaded).max(0) as u64)
+        .sum();
+    if remaining == 0 {
+        return chunks.to_vec();
+    }
+
+    let desired_chunks = (connections.max(1) as u64 * 8).min(remaining);
+    let piece_size = remaining.div_ceil(desired_chunks).clamp(1, MAX_CHUNK_SIZE) as i64;
+
+    let mut sorted = chunks.to_vec();
+    sorted.sort_by_key(|c| c.start);
+
+    let mut result = Vec::new();
+    for chunk in sorted {
+        let len = chunk.end - chunk.start + 1;
+        let done = chunk.downloaded.clamp(0, len);
+        if done == len {
+            result.push(chunk);
+            continue;
+        }
+        if done > 0 {
+            result.push(ChunkRecord {
+                download_id: chunk.download_id.clone(),
+                start: chunk.start,
+                end: chunk.start + done - 1,
+                downloaded: done,
+                verified: chunk.verified.min(done),
+                verified_hash: chunk.verified_hash.clone(),
+            });
+        }
+
+        let mut cursor = chunk.start + done;
+        while cursor <= chunk.end {
+            let end = (cursor + piece_size - 1).min(chunk.end);
+            result.push(ChunkRecord {
+                download_id: chunk.download_id.clone(),
+                start: cursor,
+                end,
+                downloaded: 0,
+                verified: 0,
+                verified_hash: None,
+            });
+            cursor = end + 1;
+        }
+    }
+    result
+}
+
+/// Largest chunk to hand a worker: [`MAX_CHUNK_SIZE`], or under a speed limit about
+/// [`LIMITED_CHUNK_SECS`] of one worker's share of it, so progress, persistence and
+/// pausing stay responsive.
+fn max_chunk_size(speed_limit: u64, connections: u64) -> u64 {
+    if speed_limit == 0 {
+        return MAX_CHUNK_SIZE;
+    }
+    // The same worker count the speed limit allows when the transfer starts.
+    let workers = connections.min(speed_limit / MIN_SPEED_PER_WORKER).max(1);
+    (speed_limit / workers)
+        .saturating_mul(LIMITED_CHUNK_SECS)
+        .clamp(MIN_LIMITED_CHUNK_SIZE, MAX_CHUNK_SIZE)
+}
+
+/// Decides how long to wait for the next body read before declaring a connection dead.
+///
+/// With `adaptive`, a connection that was recently faster than 512 KB/s gets a
+/// proportionally shorter timeout (never below 10s), since silence on a fast link
+/// means it died; slow or trickling links always get the full `base_secs`.
+pub fn stream_read_timeout(
+    base_secs: u64,
+    adaptive: bool,
+    recent_bytes_per_sec: u64,
+) -> std::time::Duration {
+    let base_secs = base_secs.max(1);
+    if !adaptive || recent_bytes_per_sec <= ADAPTIVE_READ_TIMEOUT_REFERENCE_BPS {
+        return std::time::Duration::from_secs(base_secs);
+    }
+    let scaled =
+        base_secs.saturating_mul(ADAPTIVE_READ_TIMEOUT_REFERENCE_BPS) / recent_bytes_per_sec;
+    std::time::Duration::from_secs(
+        scaled.clamp(MIN_ADAPTIVE_READ_TIMEOUT_SECS.min(base_secs), base_secs),
+    )
+}
 
 /// A sophisticated, multi-threaded HTTP download engine.
 ///
@@ -25,13 +217,23 @@ const RANGE_PROBE_TIMEOUT_SECS: u64 = 2;
 /// - **Cancellable Tasks**: Integrated with `tokio` cancellation signals.
 pub struct Downloader {
     client: Client,
+    /// Same as `client` but with a longer connect timeout, used once an attempt has failed.
+    retry_client: Client,
+    /// Custom headers, re-applied per request so they win over derived defaults.
+    request_headers: HeaderMap,
     config: DownloadConfig,
     progress: Arc<std::sync::Mutex<DownloadProgress>>,
     downloaded_atomic: Arc<AtomicU64>,
     db_path: Option<String>,
     cancel_signal: Option<Arc<std::sync::atomic::AtomicBool>>,
-    last_emit: Arc<AtomicU64>,
     rate_limiter: Option<Arc<SharedRateLimiter>>,
+    handle_permits: Option<Arc<tokio::sync::Semaphore>>,
+    /// Digest hashed inline by a single-connection transfer, sparing a re-read of the file.
+    streamed_digest: std::sync::Mutex<Option<String>>,
+    /// Worker count the server still accepted after repeated 429/503 replies (0 = none).
+    throttle_ceiling: Arc<AtomicU8>,
+    /// Worker ceiling set by the first throttle or reset; kept across retries (0 = none).
+    connection_penalty: Arc<AtomicU8>,
 }
 
 impl Downloader {
@@ -77,13 +279,14 @@ impl Downloader {
             self.remember_single_connection_host();
         }
 
-        let _ = std::fs::remove_file(&self.config.filepath);
+        let _ = std::fs::remove_file(self.part_path());
         self.downloaded_atomic.store(0, Ordering::SeqCst);
 
         {
             let mut p = self.progress.lock().unwrap();
             p.downloaded = 0;
             p.speed = 0;
+            p.instant_speed = 0;
             p.eta = 0;
             p.connections = 1;
             p.status_text = Some(reason.to_string());
@@ -98,12 +301,14 @@ impl Downloader {
             .await
     }
 
-    pub fn new(config: DownloadConfig) -> Self {
+    /// Fails if the configured proxy or custom headers cannot be used.
+    pub fn new(config: DownloadConfig) -> Result<Self, DownloadError> {
         let progress = Arc::new(std::sync::Mutex::new(DownloadProgress {
             id: config.id.clone(),
             total: 0,
             downloaded: 0,
             speed: 0,
+            instant_speed: 0,
             eta: 0,
             connections: config.connections,
             speed_limit: config.speed_limit,
@@ -113,46 +318,44 @@ impl Downloader {
             filename: None,
         }));
 
-        let mut builder = Client::builder()
-            .connect_timeout(std::time::Duration::from_secs(10))
-            .pool_max_idle_per_host(32)
-            .pool_idle_timeout(std::time::Duration::from_secs(90))
-            .tcp_keepalive(Some(std::time::Duration::from_secs(60)))
-            .tcp_nodelay(true);
-
-        if let Some(ref ua) = config.user_agent {
-            builder = builder.user_agent(ua);
-        } else {
-            builder = builder.user_agent("Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36");
-        }
-
+        let mut request_headers = match config.headers {
+            Some(ref headers) => parse_headers(headers)?,
+            None => HeaderMap::new(),
+        };
+        apply_basic_auth(
+            &mut request_headers,
+            &config.url,
+            config.username.as_deref(),
+            config.password.as_deref(),
+        );
         if let Some(ref cookies) = config.cookies {
-            use reqwest::header::{HeaderMap, HeaderValue, COOKIE};
-            let mut headers = HeaderMap::new();
-            if let Ok(v) = HeaderValue::from_str(cookies) {
-                headers.insert(COOKIE, v);
-                builder = builder.default_headers(headers);
+            if !request_headers.contains_key(reqwest::header::COOKIE) {
+                if let Ok(v) = HeaderValue::from_str(cookies) {
+                    request_headers.insert(reqwest::header::COOKIE, v);
+                }
             }
         }
-
-        let client = builder.build().unwrap_or_default();
+        let client = build_client(&config, FIRST_CONNECT_TIMEOUT_SECS)?;
+        let retry_client = build_client(&config, RETRY_CONNECT_TIMEOUT_SECS)?;
 
         let speed_limit = config.speed_limit;
 
-        Self {
+        Ok(Self {
             client,
+            retry_client,
+            request_headers,
             config,
             progress,
             downloaded_atomic: Arc::new(AtomicU64::new(0)),
             db_path: None,
             cancel_signal: None,
-            last_emit: Arc::new(AtomicU64::new(0)),
-            rate_limiter: if speed_limit > 0 {
-                Some(Arc::new(SharedRateLimiter::new(speed_limit)))
-            } else {
-                None
-            },
-        }
+            // Always present (even when unlimited) so the limit can be changed live.
+            rate_limiter: Some(Arc::new(SharedRateLimiter::new(speed_limit))),
+            handle_permits: None,
+            streamed_digest: std::sync::Mutex::new(None),
+            throttle_ceiling: Arc::new(AtomicU8::new(0)),
+            connection_penalty: Arc::new(AtomicU8::new(0)),
+        })
     }
 
     /// Builder: Attaches a database path to the downloader for chunk persistence/resume support.
@@ -165,6 +368,32 @@ impl Downloader {
         self.progress.clone()
     }
 
+    /// Exposes the live rate limiter so callers can adjust the speed cap mid-transfer.
+    pub fn get_rate_limiter(&self) -> Option<Arc<SharedRateLimiter>> {
+        self.rate_limiter.clone()
+    }
+
+    /// Connections the server tolerated after throttling this transfer repeatedly, if it did.
+    pub fn throttled_connection_cap(&self) -> Option<u8> {
+        Some(self.throttle_ceiling.load(Ordering::Relaxed)).filter(|&cap| cap > 0)
+    }
+
+    /// Write buffer per open file: `write_buffer_kb` if set, else `default`.
+    fn write_buffer_bytes(&self, default: usize) -> usize {
+        match self.config.write_buffer_kb {
+            0 => default,
+            kb => (kb as usize).saturating_mul(1024),
+        }
+    }
+
+    /// Builder: Shares a global budget of open file handles/sockets across downloads.
+    ///
+    /// Each worker holds one permit while it has the file open and a request in flight.
+    pub fn with_handle_permits(mut self, permits: Arc<tokio::sync::Semaphore>) -> Self {
+        self.handle_permits = Some(permits);
+        self
+    }
+
     /// Builder: Attaches an external cancellation signal.
     pub fn with_cancel_signal(mut self, signal: Arc<std::sync::atomic::AtomicBool>) -> Self {
         self.cancel_signal = Some(signal);
@@ -173,22 +402,144 @@ impl Downloader {
 
     /// Computes the SHA-256 hash of the downloaded file and compares it with the expected value.
     pub async fn verify_checksum(&self, expected_hash: &str) -> Result<bool, DownloadError> {
-        let filepath = &self.config.filepath;
-        let mut file = File::open(filepath)?;
-        let mut hasher = Sha256::new();
-        let mut buffer = [0u8; 8192];
+        self.verify_checksum_with(ChecksumAlgo::Sha256, expected_hash)
+            .await
+    }
 
-        loop {
-            let count = file.read(&mut buffer)?;
-            if count == 0 {
-                break;
+    /// Hashes the downloaded file with `algo` and compares it with the expected value.
+    pub async fn verify_checksum_with(
+        &self,
+        algo: ChecksumAlgo,
+        expected_hash: &str,
+    ) -> Result<bool, DownloadError> {
+        // Before finalization the data still lives in the part file.
+        let part = self.part_path();
+        let path = if part.exists() {
+            part
+        } else {
+            self.config.filepath.clone()
+        };
+        verify_file_checksum(&path, algo, expected_hash)
+    }
+
+    /// Probes the configured mirrors and returns the usable sources, primary first.
+    ///
+    /// A mirror joins only if it honours ranges and reports the primary's size.
+    async fn usable_mirrors(&self, primary: &str, total_size: u64) -> Vec<String> {
+        let extras: Vec<&String> = self
+            .config
+            .urls
+            .iter()
+            .filter(|u| u.as_str() != primary)
+            .collect();
+        let headers: Vec<HeaderMap> = extras
+            .iter()
+            .map(|u| scoped_headers(&self.request_headers, u, primary))
+            .collect();
+        let probes = extras
+            .iter()
+            .zip(&headers)
+            .map(|(u, headers)| check_range_support(&self.client, u, headers));
+        let results = futures::future::join_all(probes).await;
+
+        let mut usable = vec![primary.to_string()];
+        for (mirror, result) in extras.into_iter().zip(results) {
+            match result {
+                Ok((true, size, _, _)) if size == total_size => usable.push(mirror.clone()),
+                Ok((true, size, _, _)) => tracing::info!(
+                    "[{}] Rejecting mirror {}: size {} differs from {}",
+                    self.config.id,
+                    mirror,
+                    size,
+                    total_size
+                ),
+                Ok(_) => tracing::info!(
+                    "[{}] Rejecting mirror {}: no range support",
+                    self.config.id,
+                    mirror
+                ),
+                Err(e) => tracing::info!("[{}] Rejecting mirror {}: {}", self.config.id, mirror, e),
             }
-            hasher.update(&buffer[..count]);
         }
+        usable
+    }
 
-        let result = hasher.finalize();
-        let hex_result = format!("{:x}", result);
-        Ok(hex_result == expected_hash.to_lowercase())
+    fn part_path(&self) -> std::path::PathBuf {
+        part_file_path(&self.config.filepath)
+    }
+
+    /// Rolls back chunk progress that can't be shown to have reached the disk.
+    ///
+    /// A chunk keeps the progress of its last checkpoint if the bytes before it still
+    /// hash the same; otherwise it restarts from its first byte, the only other
+    /// known-good boundary. Returns the number of chunks rolled back.
+    fn verify_resumed_chunks(
+        &self,
+        db_path: &str,
+        part_path: &std::path::Path,
+        chunks: &mut [ChunkRecord],
+    ) -> usize {
+        let mut rolled_back = 0;
+        for chunk in chunks.iter_mut().filter(|c| c.downloaded > 0) {
+            let verified = chunk.verified.clamp(0, chunk.downloaded);
+            let holds = verified > 0
+                && chunk.verified_hash.as_deref().is_some_and(|expected| {
+                    checkpoint_hash(
+                        part_path,
+                        chunk.start as u64,
+                        (chunk.start + verified) as u64,
+                    )
+                    .map(|actual| actual == expected)
+                    .unwrap_or(false)
+                });
+            let good = if holds { verified } else { 0 };
+            if good != chunk.downloaded {
+                chunk.downloaded = good;
+                if !holds {
+                    chunk.verified = 0;
+                    chunk.verified_hash = None;
+                }
+                rolled_back += 1;
+            }
+        }
+        if rolled_back > 0 {
+            crate::db::replace_download_chunks(db_path, &self.config.id, chunks.to_vec()).ok();
+        }
+        rolled_back
+    }
+
+    /// Moves a partial file written in place by older versions to the part path.
+    fn adopt_legacy_partial(&self) {
+        let part = self.part_path();
+        if part.exists() || !self.config.filepath.exists() {
+            return;
+        }
+        let Some(ref db_path) = self.db_path else {
+            return;
+        };
+        let has_chunks = crate::db::get_download_chunks(db_path, &self.config.id)
+            .map(|chunks| !chunks.is_empty())
+            .unwrap_or(false);
+        if has_chunks {
+            if let Err(e) = std::fs::rename(&self.config.filepath, &part) {
+                tracing::error!(
+                    "[{}] Failed to adopt legacy partial file: {}",
+                    self.config.id,
+                    e
+                );
+            }
+        }
+    }
+
+    /// Renames the finished part file to its final name.
+    fn finalize(&self) -> Result<(), DownloadError> {
+        if let Some(sig) = &self.cancel_signal {
+            if sig.load(Ordering::Relaxed) {
+                return Err(DownloadError::Cancelled);
+            }
+        }
+        std::fs::rename(self.part_path(), &self.config.filepath)?;
+        Ok(())
     }
 
     /// The primary entry point for starting a download.
@@ -198,7 +549,24 @@ impl Downloader {
     /// 2. Chunk calculation and database synchronization.
     /// 3. Worker orchestration (spawning parallel tasks).
     /// 4. Real-time progress reporting.
+    ///
+    /// Data is written to `<filepath>.ciel-part`, which is renamed to `filepath`
+    /// only once the transfer has completed successfully and, if configured,
+    /// matched `expected_checksum`.
+    #[tracing::instrument(name = "download", skip_all, fields(download_id = %self.config.id))]
     pub async fn download<F>(&self, on_progress: F) -> Result<(), DownloadError>
+    where
+        F: Fn(DownloadProgress) + Send + Sync + 'static,
+    {
+        self.adopt_legacy_partial();
+        *self.streamed_digest.lock().unwrap() = None;
+        self.transfer(on_progress).await?;
+        let streamed = self.streamed_digest.lock().unwrap().take();
+        verify_expected_checksum(&self.config, self.part_path(), streamed).await?;
+        self.finalize()
+    }
+
+    async fn transfer<F>(&self, on_progress: F) -> Result<(), DownloadError>
     where
         F: Fn(DownloadProgress) + Send + Sync + 'static,
     {
@@ -220,20 +588,21 @@ impl Downloader {
         }
 
         // 2. Discover metadata and verify segmented download support.
-        let (supports_range, total_size, filename_opt) = if self.config.force_multi {
+        let (supports_range, total_size, filename_opt, validator) = if self.config.force_multi {
             tracing::info!(
                 "[{}] force_multi_http enabled. Probing range support before parallel start.",
                 self.config.id
             );
-            let (supports, probed_total, name) = check_range_support(&self.client, &url).await?;
+            let (supports, probed_total, name, validator) =
+                check_range_support(&self.client, &url, &self.request_headers).await?;
             let resolved_total = if probed_total > 0 {
                 probed_total
             } else {
                 self.config.size_hint.unwrap_or(0)
             };
-            (supports, resolved_total, name)
+            (supports, resolved_total, name, validator)
         } else {
-            check_range_support(&self.client, &url).await?
+            check_range_support(&self.client, &url, &self.request_headers).await?
         };
 
         // 3. Background name resolution: update if discovered from headers.
@@ -273,18 +642,34 @@ impl Downloader {
             return self.download_single_connection(on_progress).await;
         }
 
-        // Prepare File (don't truncate if it exists for resume)
-        let file_exists = self.config.filepath.exists();
+        // Prepare the part file (don't truncate if it exists for resume)
+        let part_path = self.part_path();
+        let file_exists = part_path.exists();
         if !file_exists {
-            let f = std::fs::File::create(&self.config.filepath)?;
+            // Chunk progress is meaningless without the bytes it refers to.
+            if let Some(ref db_path) = self.db_path {
+                crate::db::delete_download_chunks(db_path, &self.config.id).ok();
+            }
+            let f = std::fs::File::create(&part_path)?;
             f.set_len(total_size)?;
         }
 
         // Get chunks from DB if possible
         let mut chunks = Vec::new();
         if let Some(ref db_path) = self.db_path {
-            if let Ok(db_chunks) = crate::db::get_download_chunks(db_path, &self.config.id) {
+            if let Ok(mut db_chunks) = crate::db::get_download_chunks(db_path, &self.config.id) {
                 if !db_chunks.is_empty() {
+                    if self.config.resume_verify && file_exists {
+                        let rolled_back =
+                            self.verify_resumed_chunks(db_path, &part_path, &mut db_chunks);
+                        if rolled_back > 0 {
+                            tracing::info!(
+                                "[{}] Resume verification rolled back {} chunk(s).",
+                                self.config.id,
+                                rolled_back
+                            );
+                        }
+                    }
                     chunks = db_chunks
                         .into_iter()
                         .enumerate()
@@ -293,12 +678,56 @@ impl Downloader {
                             end: c.end as u64,
                             downloaded: c.downloaded as u64,
                             _index: i,
+                            failed_workers: 0,
                         })
                         .collect();
                 }
             }
         }
 
+        // Resume safety: make sure the remote file is the one the chunks were written from.
+        let has_progress = chunks.iter().any(|c| c.downloaded > 0);
+        if has_progress {
+            if let Some(stored) = self.config.validator.if_range() {
+                if !resume_validator_holds(&self.client, &url, &self.request_headers, stored)
+                    .await?
+                {
+                    tracing::info!(
+                        "[{}] Remote file changed since last run; discarding partial data.",
+                        self.config.id
+                    );
+                    chunks.clear();
+                    if let Some(ref db_path) = self.db_path {
+                        crate::db::delete_download_chunks(db_path, &self.config.id).ok();
+                    }
+                    let f = std::fs::OpenOptions::new().write(true).open(&part_path)?;
+                    f.set_len(0)?;
+                    f.set_len(total_size)?;
+
+                    let snapshot = {
+                        let mut p = self.progress.lock().unwrap();
+                        p.downloaded = 0;
+                        p.status_text = Some("File changed, restarting".to_string());
+                        p.status_phase = Some("restarting".to_string());
+                        p.phase_elapsed_secs = Some(0);
+                        p.clone()
+                    };
+                    on_progress(snapshot);
+                }
+            }
+        }
+
+        // Remember the current validators so the next resume can be checked.
+        if let Some(ref db_path) = self.db_path {
+            crate::db::update_download_validator(
+                db_path,
+                &self.config.id,
+                validator.etag.as_deref(),
+                validator.last_modified.as_deref(),
+            )
+            .ok();
+        }
+
         // If no chunks, calculate them
         if chunks.is_empty() {
             let connections = self.config.connections.max(1) as u64;
@@ -322,9 +751,8 @@ impl Downloader {
                 let end = start + this_chunk_size - 1;
                 cursor = end + 1;
 
-                // Cap individual chunks at 10MB to prevent single long requests when throttled.
-                // This ensures that even on slow connections, we keep cycling through requests and updating DB.
-                let max_chunk = 10 * 1024 * 1024;
+                // Cap chunk size so even throttled transfers keep cycling requests and updating the DB.
+                let max_chunk = max_chunk_size(self.config.speed_limit, connections);
                 while (end - start + 1) > max_chunk {
                     let sub_end = start + max_chunk - 1;
                     chunks.push(WorkChunk {
@@ -332,12 +760,15 @@ impl Downloader {
                         end: sub_end,
                         downloaded: 0,
                         _index: chunks.len(),
+                        failed_workers: 0,
                     });
                     db_chunks_to_insert.push(ChunkRecord {
                         download_id: self.config.id.clone(),
                         start: start as i64,
                         end: sub_end as i64,
                         downloaded: 0,
+                        verified: 0,
+                        verified_hash: None,
                     });
                     start += max_chunk;
                 }
@@ -347,12 +778,15 @@ impl Downloader {
                     end,
                     downloaded: 0,
                     _index: chunks.len(),
+                    failed_workers: 0,
                 });
                 db_chunks_to_insert.push(ChunkRecord {
                     download_id: self.config.id.clone(),
                     start: start as i64,
                     end: end as i64,
                     downloaded: 0,
+                    verified: 0,
+                    verified_hash: None,
                 });
             }
 
@@ -372,6 +806,7 @@ impl Downloader {
         let speed_state = Arc::new(std::sync::Mutex::new(SpeedState {
             last_time: std::time::Instant::now(),
             last_bytes: total_downloaded,
+            averager: SpeedAverager::default(),
         }));
 
         let on_progress_arc: Arc<dyn Fn(DownloadProgress) + Send + Sync + 'static> =
@@ -382,8 +817,8 @@ impl Downloader {
         // Rule: Each connection should ideally have ~256 KB/s to prevent "Slow Consumer" resets
         let mut current_target_workers = max_workers;
         if self.config.speed_limit > 0 {
-            let min_speed_per_worker = 512 * 1024; // 512 KB/s
-            let calculated_max = (self.config.speed_limit / min_speed_per_worker) as u8;
+            let calculated_max =
+                (self.config.speed_limit / MIN_SPEED_PER_WORKER).min(u8::MAX as u64) as u8;
             current_target_workers = current_target_workers.min(calculated_max.max(1));
 
             if current_target_workers < max_workers {
@@ -392,22 +827,48 @@ impl Downloader {
             }
         }
 
+        let mirrors = if self.config.urls.len() > 1 {
+            self.usable_mirrors(&url, total_size).await
+        } else {
+            vec![url.clone()]
+        };
+        if mirrors.len() > 1 {
+            tracing::info!(
+                "[{}] Splitting chunks across {} mirrors",
+                self.config.id,
+                mirrors.len()
+            );
+        }
+
         match run_workers(WorkerOrchestrationConfig {
             id: self.config.id.clone(),
-            url: url.clone(),
-            filepath: self.config.filepath.clone(),
+            mirrors: Arc::new(MirrorPool::new(mirrors)),
+            filepath: part_path.clone(),
             client: self.client.clone(),
+            retry_client: self.retry_client.clone(),
+            request_headers: self.request_headers.clone(),
+            if_range: validator.if_range().map(str::to_string),
             db_path: self.db_path.clone(),
             cancel_signal: self.cancel_signal.clone(),
             rate_limiter: self.rate_limiter.clone(),
+            handle_permits: self.handle_permits.clone(),
+            stream_read_timeout_secs: self.config.stream_read_timeout_secs,
+            adaptive_read_timeout: self.config.adaptive_read_timeout,
+            resume_verify: self.config.resume_verify,
+            max_retries: self.config.max_retries,
+            retry_base_ms: self.config.retry_base_ms,
+            stall_timeout_secs: self.config.stall_timeout_secs,
+            write_buffer_bytes: self.write_buffer_bytes(CHUNK_WRITE_BUFFER),
+            durable_writes: self.config.durable_writes,
             progress: self.progress.clone(),
             downloaded_atomic: self.downloaded_atomic.clone(),
-            last_emit: self.last_emit.clone(),
             speed_state,
             on_progress: on_progress_arc.clone(),
             pending_chunks: chunks,
             max_workers,
             current_target_workers,
+            throttle_ceiling: self.throttle_ceiling.clone(),
+            connection_penalty: self.connection_penalty.clone(),
         })
         .await?
         {
@@ -422,6 +883,28 @@ impl Downloader {
         }
     }
 
+    /// Where a single-connection transfer can pick up: the part file's length,
+    /// capped at the progress stored for the download.
+    ///
+    /// A part file written by parallel chunks has holes, so it is never appended to.
+    fn single_connection_resume_offset(&self) -> u64 {
+        let on_disk = std::fs::metadata(self.part_path())
+            .map(|m| m.len())
+            .unwrap_or(0);
+        let Some(ref db_path) = self.db_path else {
+            return on_disk;
+        };
+        let has_chunks = crate::db::get_download_chunks(db_path, &self.config.id)
+            .map(|chunks| !chunks.is_empty())
+            .unwrap_or(true);
+        if has_chunks {
+            return 0;
+        }
+        crate::db::get_download_progress(db_path, &self.config.id)
+            .map(|recorded| on_disk.min(recorded.max(0) as u64))
+            .unwrap_or(0)
+    }
+
     /// Fallback: Downloads a file using a single TCP connection.
     ///
     /// Used when the server lacks `Range` support or for very small files where
@@ -438,9 +921,42 @@ impl Downloader {
         }
         (on_progress)(self.progress.lock().unwrap().clone());
 
-        let mut response = decorate_media_request(self.client.get(&self.config.url), &self.config.url)
+        let _handle_permit = match &self.handle_permits {
+            Some(permits) => Some(
+                permits
+                    .clone()
+                    .acquire_owned()
+                    .await
+                    .map_err(|_| DownloadError::Cancelled)?,
+            ),
+            None => None,
+        };
+
+        // Ask for the rest of an interrupted transfer. `If-Range` makes a changed file come
+        // back whole (200), and a server that ignores ranges does the same; either way the
+        // part file is then rewritten from the start.
+        let resume_offset = self.single_connection_resume_offset();
+        let mut request = decorate_media_request(
+            self.client.get(&self.config.url),
+            &self.config.url,
+            &self.request_headers,
+        );
+        if resume_offset > 0 {
+            request = request.header(reqwest::header::RANGE, format!("bytes={}-", resume_offset));
+            if let Some(validator) = self.config.validator.if_range() {
+                request = request.header(reqwest::header::IF_RANGE, validator);
+            }
+        }
+        let mut response = request.send().await?;
+        if resume_offset > 0 && response.status() == reqwest::StatusCode::RANGE_NOT_SATISFIABLE {
+            response = decorate_media_request(
+                self.client.get(&self.config.url),
+                &self.config.url,
+                &self.request_headers,
+            )
             .send()
             .await?;
+        }
 
         // Safety check: If we're getting HTML but expecting a file, it's a login/warning page
         let mut content_type = response
@@ -458,6 +974,8 @@ impl Downloader {
                     .get(&self.config.url)
                     .header(REFERER, referer)
                     .header(ACCEPT, "image/avif,image/webp,image/apng,image/*,*/*;q=0.8")
+                    .header(ACCEPT_ENCODING, "identity")
+                    .headers(self.request_headers.clone())
                     .send()
                     .await
                 {
@@ -474,6 +992,34 @@ impl Downloader {
                 }
             }
 
+            // Some hosts (Google Drive, MediaFire) put a confirmation page in front of the file.
+            if content_type.contains("text/html") && has_resolver(&self.config.url) {
+                let html = response.text().await.unwrap_or_default();
+                let Some(direct) = resolve_direct_url(&self.config.url, &html) else {
+                    return Err(DownloadError::Network(
+                        "Server returned a webpage instead of a file and no direct link could be found on it."
+                            .to_string(),
+                    ));
+                };
+                tracing::info!(
+                    "[{}] Following the direct link on the host's confirmation page",
+                    self.config.id
+                );
+                response = decorate_media_request(
+                    self.client.get(&direct),
+                    &direct,
+                    &self.request_headers,
+                )
+                .send()
+                .await?;
+                content_type = response
+                    .headers()
+                    .get(reqwest::header::CONTENT_TYPE)
+                    .and_then(|v| v.to_str().ok())
+                    .unwrap_or("")
+                    .to_string();
+            }
+
             if content_type.contains("text/html") {
                 return Err(DownloadError::Network(
                     "Server returned a webpage instead of a file. The host may block direct links (hotlink protection) or require login."
@@ -482,32 +1028,98 @@ impl Downloader {
             }
         }
 
-        let total_size = response.content_length().unwrap_or(0);
+        let content_range = response
+            .headers()
+            .get(reqwest::header::CONTENT_RANGE)
+            .and_then(|v| v.to_str().ok())
+            .and_then(parse_content_range);
+        let resumed = resume_offset > 0
+            && response.status() == reqwest::StatusCode::PARTIAL_CONTENT
+            && content_range.is_some_and(|(first, _, _)| first == resume_offset);
+        let start_offset = if resumed { resume_offset } else { 0 };
+        let total_size = match content_range {
+            Some((_, _, Some(total))) if resumed => total,
+            _ => response
+                .content_length()
+                .map(|len| start_offset + len)
+                .unwrap_or(0),
+        };
 
-        let file_raw = tokio::fs::File::create(&self.config.filepath).await?;
-        let mut file = BufWriter::with_capacity(256 * 1024, file_raw); // Larger buffer for single connection
+        // Only a transfer written from the first byte in order can be hashed on the way.
+        let mut digest = if resumed {
+            None
+        } else {
+            expected_checksum(&self.config)?.map(|(algo, _)| StreamingDigest::new(algo))
+        };
+
+        let file_raw = if resumed {
+            tracing::info!(
+                "[{}] Resuming single connection at byte {}",
+                self.config.id,
+                resume_offset
+            );
+            let mut file = tokio::fs::OpenOptions::new()
+                .write(true)
+                .open(self.part_path())
+                .await?;
+            file.set_len(resume_offset).await?;
+            file.seek(std::io::SeekFrom::Start(resume_offset)).await?;
+            file
+        } else {
+            if let Some(ref db_path) = self.db_path {
+                // The next resume checks these with `If-Range`.
+                let validator = ResourceValidator::from_headers(response.headers());
+                crate::db::update_download_validator(
+                    db_path,
+                    &self.config.id,
+                    validator.etag.as_deref(),
+                    validator.last_modified.as_deref(),
+                )
+                .ok();
+                crate::db::delete_download_chunks(db_path, &self.config.id).ok();
+            }
+            tokio::fs::File::create(self.part_path()).await?
+        };
+        self.downloaded_atomic.store(start_offset, Ordering::SeqCst);
+        self.progress.lock().unwrap().downloaded = start_offset;
+        let mut file =
+            BufWriter::with_capacity(self.write_buffer_bytes(SINGLE_WRITE_BUFFER), file_raw);
         let mut stream = response.bytes_stream();
         let mut last_speed_time = std::time::Instant::now();
-        let start_emit_time = std::time::Instant::now();
         let mut last_speed_bytes = self.downloaded_atomic.load(Ordering::Relaxed);
 
-        let last_emit_clone = self.last_emit.clone();
         let downloaded_atomic = self.downloaded_atomic.clone();
         let progress = self.progress.clone();
-        let global_speed_limit = self.config.speed_limit;
+
+        let mut throughput = ReadThroughput::new();
+        let mut averager = SpeedAverager::default();
+        let mut last_db_update = std::time::Instant::now();
 
         loop {
-            let item = match tokio::time::timeout(
-                std::time::Duration::from_secs(60),
-                stream.next(),
-            )
-            .await
-            {
+            let read_timeout = stream_read_timeout(
+                self.config.stream_read_timeout_secs,
+                self.config.adaptive_read_timeout,
+                throughput.bytes_per_sec(),
+            );
+            let next = tokio::select! {
+                next = tokio::time::timeout(read_timeout, stream.next()) => next,
+                // Flush on pause so the part file ends exactly where a resume picks up.
+                _ = wait_for_cancel(&self.cancel_signal) => {
+                    flush_file(&mut file, self.config.durable_writes).await?;
+                    if let Some(ref db_path) = self.db_path {
+                        let downloaded = downloaded_atomic.load(Ordering::Relaxed);
+                        crate::db::update_download_progress(db_path, &self.config.id, downloaded as i64, 0).ok();
+                    }
+                    return Err(DownloadError::Cancelled);
+                }
+            };
+            let item = match next {
                 Ok(next) => next,
                 Err(_) => {
-                    return Err(DownloadError::Network(
-                        "Connection stalled (no data for 60s)".to_string(),
-                    ));
+                    return Err(DownloadError::Network(format!(
+                        "Connection stalled (no data for {}s)",
+                        read_timeout.as_secs()
+                    )));
                 }
             };
             let item = match item {
@@ -516,67 +1128,360 @@ impl Downloader {
             };
             let chunk = item.map_err(|e| DownloadError::Network(e.to_string()))?;
             file.write_all(&chunk).await?;
+            if let Some(ref mut digest) = digest {
+                digest.update(&chunk);
+            }
 
             let len = chunk.len() as u64;
+            throughput.record(len);
 
             // BANDWIDTH THROTTLING
             if let Some(limiter) = &self.rate_limiter {
                 limiter.acquire(len, &self.cancel_signal).await;
-            } else if global_speed_limit > 0 {
-                // Fallback for when limiter isn't initialized but limit is set
-                let cost_ms = (len * 1000) / global_speed_limit;
-                if cost_ms > 0 {
-                    tokio::time::sleep(std::time::Duration::from_millis(cost_ms)).await;
-                }
             }
 
             let current_total = downloaded_atomic.fetch_add(len, Ordering::Relaxed) + len;
 
-            // Throttled progress emission
-            let now_ms = start_emit_time.elapsed().as_millis() as u64;
-            let last = last_emit_clone.load(std::sync::atomic::Ordering::Relaxed);
-
-            if now_ms - last > 200 {
-                if last_emit_clone
-                    .compare_exchange(last, now_ms, Ordering::SeqCst, Ordering::Relaxed)
-                    .is_ok()
-                {
-                    let mut p = progress.lock().unwrap();
-                    p.downloaded = current_total;
-                    p.total = total_size;
-                    p.connections = 1;
-
-                    let interval_elapsed = last_speed_time.elapsed().as_secs_f64();
-                    if interval_elapsed >= 0.3 {
-                        let diff = current_total.saturating_sub(last_speed_bytes);
-                        p.speed = (diff as f64 / interval_elapsed) as u64;
-
-                        last_speed_bytes = current_total;
-                        last_speed_time = std::time::Instant::now();
+            // Every read is reported; the manager coalesces them into batched events.
+            {
+                let mut p = progress.lock().unwrap();
+                p.downloaded = current_total;
+                p.total = total_size;
+                p.connections = 1;
+
+                let interval_elapsed = last_speed_time.elapsed().as_secs_f64();
+                if interval_elapsed >= 0.3 {
+                    let diff = current_total.saturating_sub(last_speed_bytes);
+                    averager.apply(&mut p, diff, interval_elapsed);
+
+                    last_speed_bytes = current_total;
+                    last_speed_time = std::time::Instant::now();
+                }
+                (on_progress)(p.clone());
+            }
 
-                        if p.speed > 0 {
-                            p.eta = p.total.saturating_sub(p.downloaded) / p.speed;
-                        }
+            // The stored count is the resume offset, capped by what reached the part file.
+            if last_db_update.elapsed().as_secs() >= 1 {
+                if let Some(ref db_path) = self.db_path {
+                    if self.config.durable_writes {
+                        flush_file(&mut file, true).await?;
                     }
-                    (on_progress)(p.clone());
+                    let speed = progress.lock().unwrap().speed;
+                    crate::db::update_download_progress(
+                        db_path,
+                        &self.config.id,
+                        current_total as i64,
+                        speed as i64,
+                    )
+                    .ok();
                 }
+                last_db_update = std::time::Instant::now();
             }
         }
 
-        file.flush().await?;
+        flush_file(&mut file, self.config.durable_writes).await?;
+        *self.streamed_digest.lock().unwrap() = digest.map(StreamingDigest::finish);
         Ok(())
     }
 }
 
+/// Parses a proxy URL, rejecting anything reqwest cannot route through.
+pub fn parse_proxy(proxy: &str) -> Result<reqwest::Proxy, DownloadError> {
+    let trimmed = proxy.trim();
+    let scheme_ok = ["http://", "https://", "socks5://", "socks5h://"]
+        .iter()
+        .any(|scheme| trimmed.to_ascii_lowercase().starts_with(scheme));
+    if !scheme_ok {
+        return Err(DownloadError::InvalidUrl(format!(
+            "Unsupported proxy scheme: {}",
+            trimmed
+        )));
+    }
+    reqwest::Proxy::all(trimmed)
+        .map_err(|e| DownloadError::InvalidUrl(format!("Invalid proxy {}: {}", trimmed, e)))
+}
+
+/// Streams a file through any `Digest` hasher using an 8KB buffer.
+fn digest_file<D: Digest>(path: &std::path::Path) -> Result<String, DownloadError> {
+    let mut file = File::open(path)?;
+    let mut hasher = D::new();
+    let mut buffer = [0u8; 8192];
+
+    loop {
+        let count = file.read(&mut buffer)?;
+        if count == 0 {
+            break;
+        }
+        hasher.update(&buffer[..count]);
+    }
+
+    Ok(hex::encode(hasher.finalize()))
+}
+
+fn crc32_file(path: &std::path::Path) -> Result<String, DownloadError> {
+    let mut file = File::open(path)?;
+    let mut hasher = crc32fast::Hasher::new();
+    let mut buffer = [0u8; 8192];
+
+    loop {
+        let count = file.read(&mut buffer)?;
+        if count == 0 {
+            break;
+        }
+        hasher.update(&buffer[..count]);
+    }
+
+    Ok(format!("{:08x}", hasher.finalize()))
+}
+
+/// Hashes `path` with an already resolved `algo`.
+fn file_digest(path: &std::path::Path, algo: ChecksumAlgo) -> Result<String, DownloadError> {
+    match algo {
+        ChecksumAlgo::Md5 => digest_file::<md5::Md5>(path),
+        ChecksumAlgo::Sha1 => digest_file::<sha1::Sha1>(path),
+        ChecksumAlgo::Sha256 | ChecksumAlgo::Auto => digest_file::<Sha256>(path),
+        ChecksumAlgo::Crc32 => crc32_file(path),
+    }
+}
+
+/// Hashes `path` with `algo` (resolving `Auto` from the digest length) and
+/// compares the result case-insensitively with `expected_hash`.
+pub fn verify_file_checksum(
+    path: &std::path::Path,
+    algo: ChecksumAlgo,
+    expected_hash: &str,
+) -> Result<bool, DownloadError> {
+    let expected = expected_hash.trim().to_lowercase();
+    let algo = algo.resolve(&expected).ok_or_else(|| {
+        DownloadError::Io(format!(
+            "Cannot detect hash algorithm from a {}-character digest",
+            expected.len()
+        ))
+    })?;
+
+    Ok(file_digest(path, algo)? == expected)
+}
+
+/// Incremental hasher fed with the bytes of an in-order transfer.
+enum StreamingDigest {
+    Md5(md5::Md5),
+    Sha1(sha1::Sha1),
+    Sha256(Sha256),
+    Crc32(crc32fast::Hasher),
+}
+
+impl StreamingDigest {
+    fn new(algo: ChecksumAlgo) -> Self {
+        match algo {
+            ChecksumAlgo::Md5 => Self::Md5(md5::Md5::new()),
+            ChecksumAlgo::Sha1 => Self::Sha1(sha1::Sha1::new()),
+            ChecksumAlgo::Sha256 | ChecksumAlgo::Auto => Self::Sha256(Sha256::new()),
+            ChecksumAlgo::Crc32 => Self::Crc32(crc32fast::Hasher::new()),
+        }
+    }
+
+    fn update(&mut self, bytes: &[u8]) {
+        match self {
+            Self::Md5(hasher) => hasher.update(bytes),
+            Self::Sha1(hasher) => hasher.update(bytes),
+            Self::Sha256(hasher) => hasher.update(bytes),
+            Self::Crc32(hasher) => hasher.update(bytes),
+        }
+    }
+
+    fn finish(self) -> String {
+        match self {
+            Self::Md5(hasher) => hex::encode(hasher.finalize()),
+            Self::Sha1(hasher) => hex::encode(hasher.finalize()),
+            Self::Sha256(hasher) => hex::encode(hasher.finalize()),
+            Self::Crc32(hasher) => format!("{:08x}", hasher.finalize()),
+        }
+    }
+}
+
+/// The configured `expected_checksum` with its algorithm resolved, if any.
+fn expected_checksum(
+    config: &DownloadConfig,
+) -> Result<Option<(ChecksumAlgo, String)>, DownloadError> {
+    let Some(ref expected) = config.expected_checksum else {
+        return Ok(None);
+    };
+    let expected = expected.trim().to_lowercase();
+    let algo = config.checksum_algo.resolve(&expected).ok_or_else(|| {
+        DownloadError::Io(format!(
+            "Cannot detect hash algorithm from a {}-character digest",
+            expected.len()
+        ))
+    })?;
+    Ok(Some((algo, expected)))
+}
+
+/// Checks a finished part file against `config.expected_checksum`.
+///
+/// Uses `streamed` when the transfer already hashed its bytes; otherwise the
+/// file is read back on a blocking thread, since parallel chunks land out of order.
+pub(crate) async fn verify_expected_checksum(
+    config: &DownloadConfig,
+    path: std::path::PathBuf,
+    streamed: Option<String>,
+) -> Result<(), DownloadError> {
+    let Some((algo, expected)) = expected_checksum(config)? else {
+        return Ok(());
+    };
+    let actual = match streamed {
+        Some(digest) => digest,
+        None => tokio::task::spawn_blocking(move || file_digest(&path, algo))
+            .await
+            .map_err(|e| DownloadError::Io(e.to_string()))??,
+    };
+    if actual != expected {
+        return Err(DownloadError::ChecksumMismatch {
+            algo: algo.as_str().to_string(),
+            expected,
+            actual,
+        });
+    }
+    tracing::info!("[{}] Checksum verified ({})", config.id, algo.as_str());
+    Ok(())
+}
+
+/// Validates user-supplied headers, rejecting names or values HTTP can't carry.
+pub fn parse_headers(headers: &[(String, String)]) -> Result<HeaderMap, DownloadError> {
+    let mut map = HeaderMap::new();
+    for (name, value) in headers {
+        let header_name = HeaderName::from_bytes(name.trim().as_bytes())
+            .map_err(|_| DownloadError::InvalidHeader(format!("bad name {:?}", name)))?;
+        let header_value = HeaderValue::from_str(value.trim())
+            .map_err(|_| DownloadError::InvalidHeader(format!("bad value for {}", name.trim())))?;
+        map.append(header_name, header_value);
+    }
+    Ok(map)
+}
+
+/// Splits `user:password@` off an HTTP(S) or FTP(S) URL, percent-decoding both parts.
+///
+/// Returns the URL unchanged when it carries no credentials.
+pub fn split_url_credentials(url: &str) -> (String, Option<(String, String)>) {
+    let Ok(mut parsed) = url::Url::parse(url) else {
+        return (url.to_string(), None);
+    };
+    let supported = matches!(parsed.scheme(), "http" | "https" | "ftp" | "ftps");
+    if parsed.username().is_empty() || !supported {
+        return (url.to_string(), None);
+    }
+    let decode = |s: &str| {
+        percent_encoding::percent_decode_str(s)
+            .decode_utf8_lossy()
+            .to_string()
+    };
+    let credentials = (
+        decode(parsed.username()),
+        decode(parsed.password().unwrap_or("")),
+    );
+    let _ = parsed.set_username("");
+    let _ = parsed.set_password(None);
+    (parsed.to_string(), Some(credentials))
+}
+
+/// Adds `Authorization: Basic` for `username`/`password`, or for credentials
+/// embedded in `url` when none are given. A user-supplied `Authorization` header wins.
+pub fn apply_basic_auth(
+    headers: &mut HeaderMap,
+    url: &str,
+    username: Option<&str>,
+    password: Option<&str>,
+) {
+    if headers.contains_key(reqwest::header::AUTHORIZATION) {
+        return;
+    }
+    let (username, password) = match username.filter(|u| !u.is_empty()) {
+        Some(user) => (user.to_string(), password.unwrap_or("").to_string()),
+        None => match split_url_credentials(url).1 {
+            Some(credentials) => credentials,
+            None => return,
+        },
+    };
+    let token = data_encoding::BASE64.encode(format!("{}:{}", username, password).as_bytes());
+    if let Ok(mut value) = HeaderValue::from_str(&format!("Basic {}", token)) {
+        value.set_sensitive(true);
+        headers.insert(reqwest::header::AUTHORIZATION, value);
+    }
+}
+
+/// `headers` without `Authorization` and `Cookie` when `url` is on another host than
+/// `primary_url`, so a download's credentials never reach a mirror on another site.
+pub(super) fn scoped_headers(headers: &HeaderMap, url: &str, primary_url: &str) -> HeaderMap {
+    let host = |u: &str| {
+        url::Url::parse(u)
+            .ok()
+            .and_then(|u| u.host_str().map(str::to_ascii_lowercase))
+    };
+    let mut scoped = headers.clone();
+    if host(url).is_none() || host(url) != host(primary_url) {
+        scoped.remove(reqwest::header::AUTHORIZATION);
+        scoped.remove(reqwest::header::COOKIE);
+    }
+    scoped
+}
+
+/// Builds the HTTP client shared by all workers of a download.
+///
+/// Request headers, credentials included, are attached per request rather than as
+/// client defaults, so each request only carries what its host may see.
+fn build_client(
+    config: &DownloadConfig,
+    connect_timeout_secs: u64,
+) -> Result<Client, DownloadError> {
+    let mut builder = Client::builder()
+        .connect_timeout(std::time::Duration::from_secs(connect_timeout_secs))
+        .pool_max_idle_per_host(32)
+        .pool_idle_timeout(std::time::Duration::from_secs(90))
+        .tcp_keepalive(Some(std::time::Duration::from_secs(60)))
+        .tcp_nodelay(true);
+
+    if let Some(ref ua) = config.user_agent {
+        builder = builder.user_agent(ua);
+    } else {
+        builder = builder.user_agent(DEFAULT_USER_AGENT);
+    }
+
+    if let Some(proxy) = config.proxy.as_deref().filter(|p| !p.trim().is_empty()) {
+        builder = builder.proxy(parse_proxy(proxy)?);
+    }
+
+    // Never fall back to a default (direct) client: that would bypass the proxy.
+    builder
+        .build()
+        .map_err(|e| DownloadError::Network(e.to_string()))
+}
+
+/// Sends a one-byte `If-Range` request; a `206` means the stored validator still matches.
+async fn resume_validator_holds(
+    client: &Client,
+    url: &str,
+    request_headers: &HeaderMap,
+    if_range: &str,
+) -> Result<bool, DownloadError> {
+    let response = decorate_media_request(client.get(url), url, request_headers)
+        .header(reqwest::header::RANGE, "bytes=0-0")
+        .header(reqwest::header::IF_RANGE, if_range)
+        .timeout(std::time::Duration::from_secs(RANGE_PROBE_TIMEOUT_SECS))
+        .send()
+        .await
+        .map_err(|e| DownloadError::Network(e.to_string()))?;
+    Ok(response.status() == reqwest::StatusCode::PARTIAL_CONTENT)
+}
+
 /// Queries a URL using a `HEAD` request to verify if it supports segmented downloads.
-/// Also extracts the content length and suggested filename.
+/// Also extracts the content length, suggested filename and `ETag` / `Last-Modified` validators.
 pub async fn check_range_support(
     client: &Client,
     url: &str,
-) -> Result<(bool, u64, Option<String>), DownloadError> {
+    request_headers: &HeaderMap,
+) -> Result<(bool, u64, Option<String>, ResourceValidator), DownloadError> {
     let mut filename_opt: Option<String> = None;
 
-    let range_response = decorate_media_request(client.get(url), url)
+    let range_response = decorate_media_request(client.get(url), url, request_headers)
         .header(reqwest::header::RANGE, "bytes=0-0")
         .timeout(std::time::Duration::from_secs(RANGE_PROBE_TIMEOUT_SECS))
         .send()
@@ -590,17 +1495,19 @@ pub async fn check_range_support(
         )));
     }
 
+    let validator = ResourceValidator::from_headers(range_response.headers());
     let filename = extract_filename(url, range_response.headers());
     if filename != "download" && filename != "download_file" && filename != "uc" {
         filename_opt = Some(filename);
     }
 
+    // Without `Content-Length` (e.g. chunked responses) this total is the only size we get.
     let content_range = range_response
         .headers()
         .get(reqwest::header::CONTENT_RANGE)
         .and_then(|v| v.to_str().ok())
-        .and_then(|v| v.split('/').last())
-        .and_then(|v| v.parse::<u64>().ok());
+        .and_then(parse_content_range)
+        .and_then(|(_, _, total)| total);
 
     // Strict capability check:
     // Only treat range as supported if server responds with 206 + valid Content-Range total.
@@ -630,7 +1537,7 @@ pub async fn check_range_support(
         let probe_end = (probe_start + 1023).min(total_size.saturating_sub(1));
         let probe_range = format!("bytes={}-{}", probe_start, probe_end);
 
-        match decorate_media_request(client.get(url), url)
+        match decorate_media_request(client.get(url), url, request_headers)
             .header(reqwest::header::RANGE, probe_range.clone())
             .timeout(std::time::Duration::from_secs(RANGE_PROBE_TIMEOUT_SECS))
             .send()
@@ -661,7 +1568,29 @@ pub async fn check_range_support(
         initial_supports_range
     };
 
-    Ok((supports_range, total_size, filename_opt))
+    Ok((supports_range, total_size, filename_opt, validator))
+}
+
+/// Parses `Content-Range: bytes <first>-<last>/<total>`.
+///
+/// `total` is `None` when the server sends `*` because it doesn't know the size.
+/// An inverted range (`first > last`) is invalid and yields `None`.
+pub(super) fn parse_content_range(value: &str) -> Option<(u64, u64, Option<u64>)> {
+    let (range, total) = value
+        .trim()
+        .strip_prefix("bytes")?
+        .trim_start()
+        .split_once('/')?;
+    let (first, last) = range.trim().split_once('-')?;
+    let (first, last): (u64, u64) = (first.trim().parse().ok()?, last.trim().parse().ok()?);
+    if first > last {
+        return None;
+    }
+    let total = match total.trim() {
+        "*" => None,
+        total => Some(total.parse().ok()?),
+    };
+    Some((first, last, total))
 }
 
 /// Heuristic: Extracts a probable filename from the URL or the `Content-Disposition` header.
@@ -725,7 +1654,7 @@ pub fn extract_filename(url: &str, headers: &reqwest::header::HeaderMap) -> Stri
     sanitize_filename(&filename)
 }
 
-fn sanitize_filename(name: &str) -> String {
+pub fn sanitize_filename(name: &str) -> String {
     let sanitized = name.replace(|c: char| c.is_control() || "<>:\"/\\|?*".contains(c), "_");
     if sanitized.is_empty() {
         "download".to_string()
@@ -743,9 +1672,11 @@ fn derive_request_origin(url: &str) -> Option<String> {
     }
 }
 
+/// Adds browser-like media headers, then the user's own headers so those take precedence.
 pub(super) fn decorate_media_request(
     builder: reqwest::RequestBuilder,
     url: &str,
+    request_headers: &HeaderMap,
 ) -> reqwest::RequestBuilder {
     let mut request = builder
         .header(
@@ -759,5 +1690,5 @@ pub(super) fn decorate_media_request(
         request = request.header(REFERER, referer);
     }
 
-    request
+    request.headers(request_headers.clone())
 }
@@ -0,0 +1,89 @@
+//! Direct-link resolvers for hosts that serve an HTML page in front of the file.
+//!
+//! Each handler gets the page the host returned and, if it recognizes it, the URL
+//! to request instead. To support another host, add an entry to [`RESOLVERS`].
+
+use regex::Regex;
+
+/// Turns an interstitial page into the direct file URL, if the page is recognized.
+type Resolver = fn(&url::Url, &str) -> Option<String>;
+
+/// Handlers by host; an entry also matches its subdomains.
+const RESOLVERS: &[(&str, Resolver)] = &[
+    ("drive.google.com", google_drive),
+    ("drive.usercontent.google.com", google_drive),
+    ("mediafire.com", mediafire),
+];
+
+fn resolver_for(url: &str) -> Option<(url::Url, Resolver)> {
+    let parsed = url::Url::parse(url).ok()?;
+    let host = parsed.host_str()?.to_ascii_lowercase();
+    let (_, resolver) = RESOLVERS.iter().find(|(domain, _)| {
+        host == *domain
+            || host
+                .strip_suffix(domain)
+                .is_some_and(|prefix| prefix.ends_with('.'))
+    })?;
+    Some((parsed, *resolver))
+}
+
+/// True if `url` belongs to a host with a resolver, i.e. an HTML reply may be worth parsing.
+pub fn has_resolver(url: &str) -> bool {
+    resolver_for(url).is_some()
+}
+
+/// Extracts the real download link from an interstitial `html` page served for `url`.
+///
+/// Returns `None` for unknown hosts, unrecognized pages, or when the link found is `url` itself.
+pub fn resolve_direct_url(url: &str, html: &str) -> Option<String> {
+    let (parsed, resolver) = resolver_for(url)?;
+    resolver(&parsed, html).filter(|direct| direct != url)
+}
+
+/// Undoes the entity escaping found in attribute values.
+fn unescape_attr(value: &str) -> String {
+    value
+        .replace("&amp;", "&")
+        .replace("&#39;", "'")
+        .replace("&quot;", "\"")
+}
+
+/// Google Drive's "can't scan this file for viruses" page.
+///
+/// Current pages submit a `GET` form with hidden `id`/`confirm`/`uuid` fields;
+/// older ones link to `/uc?...&confirm=<token>` directly.
+fn google_drive(url: &url::Url, html: &str) -> Option<String> {
+    let form =
+        Regex::new(r#"(?s)<form[^>]*id="download-form"[^>]*action="([^"]+)"[^>]*>(.*?)</form>"#)
+            .ok()?;
+    if let Some(caps) = form.captures(html) {
+        let mut action = url.join(&unescape_attr(&caps[1])).ok()?;
+        let input =
+            Regex::new(r#"<input[^>]*type="hidden"[^>]*name="([^"]+)"[^>]*value="([^"]*)""#)
+                .ok()?;
+        let fields: Vec<(String, String)> = input
+            .captures_iter(&caps[2])
+            .map(|c| (c[1].to_string(), unescape_attr(&c[2])))
+            .collect();
+        if fields.is_empty() {
+            return None;
+        }
+        {
+            let mut query = action.query_pairs_mut();
+            for (name, value) in &fields {
+                query.append_pair(name, value);
+            }
+        }
+        return Some(action.to_string());
+    }
+
+    let link = Regex::new(r#"href="(/uc\?export=download[^"]*confirm=[^"]+)""#).ok()?;
+    let path = unescape_attr(&link.captures(html)?[1]);
+    url.join(&path).ok().map(|u| u.to_string())
+}
+
+/// MediaFire's file page, whose download button points at a `download*.mediafire.com` host.
+fn mediafire(_url: &url::Url, html: &str) -> Option<String> {
+    let link = Regex::new(r#"href="(https?://download[^"/]*\.mediafire\.com/[^"]+)""#).ok()?;
+    Some(unescape_attr(&link.captures(html)?[1]))
+}
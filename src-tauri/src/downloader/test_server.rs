@@ -0,0 +1,213 @@
+//! Minimal HTTP/1.1 file server for exercising the downloader against local
+//! range implementations, both well-behaved and broken ones.
+
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+/// Body bytes written per socket write.
+const WRITE_PIECE: usize = 16 * 1024;
+
+/// How range requests are answered once the honest replies are used up.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum RangeReply {
+    /// `206` with the requested bytes.
+    Partial,
+    /// `200` with the whole file, as servers without range support do.
+    Full,
+    /// `206` with the requested bytes gzip-compressed.
+    Gzip,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct ServerOptions {
+    pub(crate) body: Vec<u8>,
+    pub(crate) etag: Option<String>,
+    pub(crate) ranges: RangeReply,
+    /// Range requests answered with `Partial` before `ranges` applies, so the
+    /// capability probes pass and only the transfer itself sees the bad replies.
+    pub(crate) honest_ranges: usize,
+    /// Pause between body writes, to keep a transfer in flight.
+    pub(crate) write_delay: Option<Duration>,
+}
+
+impl ServerOptions {
+    pub(crate) fn new(body: Vec<u8>) -> Self {
+        Self {
+            body,
+            etag: None,
+            ranges: RangeReply::Partial,
+            honest_ranges: 0,
+            write_delay: None,
+        }
+    }
+}
+
+/// The headers of one request, as the server saw them.
+#[derive(Debug, Clone)]
+pub(crate) struct SeenRequest {
+    pub(crate) range: Option<String>,
+    pub(crate) if_range: Option<String>,
+}
+
+pub(crate) struct TestServer {
+    pub(crate) url: String,
+    requests: Arc<Mutex<Vec<SeenRequest>>>,
+}
+
+impl TestServer {
+    /// Serves `options.body` at `<url>` until the runtime shuts down.
+    pub(crate) async fn start(options: ServerOptions) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/file.bin", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let ranges_served = Arc::new(AtomicUsize::new(0));
+        let options = Arc::new(options);
+
+        let seen = requests.clone();
+        tokio::spawn(async move {
+            while let Ok((socket, _)) = listener.accept().await {
+                let options = options.clone();
+                let seen = seen.clone();
+                let ranges_served = ranges_served.clone();
+                tokio::spawn(async move {
+                    // Clients hang up mid-body when they pause or fall back.
+                    let _ = serve(socket, &options, &seen, &ranges_served).await;
+                });
+            }
+        });
+
+        Self { url, requests }
+    }
+
+    /// Every request received so far, in arrival order.
+    pub(crate) fn requests(&self) -> Vec<SeenRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+
+    /// `Range` headers received so far, in arrival order.
+    pub(crate) fn ranges(&self) -> Vec<Option<String>> {
+        self.requests().into_iter().map(|r| r.range).collect()
+    }
+}
+
+async fn serve(
+    mut socket: TcpStream,
+    options: &ServerOptions,
+    seen: &Mutex<Vec<SeenRequest>>,
+    ranges_served: &AtomicUsize,
+) -> std::io::Result<()> {
+    let mut head = Vec::new();
+    let mut buf = [0u8; 1024];
+    while !head.windows(4).any(|w| w == b"\r\n\r\n") {
+        let n = socket.read(&mut buf).await?;
+        if n == 0 {
+            return Ok(());
+        }
+        head.extend_from_slice(&buf[..n]);
+    }
+    let head = String::from_utf8_lossy(&head);
+    let header = |name: &str| {
+        head.lines().skip(1).find_map(|line| {
+            let (key, value) = line.split_once(':')?;
+            key.trim()
+                .eq_ignore_ascii_case(name)
+                .then(|| value.trim().to_string())
+        })
+    };
+    let request = SeenRequest {
+        range: header("range"),
+        if_range: header("if-range"),
+    };
+    seen.lock().unwrap().push(request.clone());
+
+    let body = &options.body;
+    let len = body.len() as u64;
+    // A stale `If-Range` gets the whole current file, as RFC 9110 requires.
+    let range = request
+        .range
+        .as_deref()
+        .filter(|_| match (&request.if_range, &options.etag) {
+            (Some(sent), Some(etag)) => sent == etag,
+            (Some(_), None) => false,
+            (None, _) => true,
+        })
+        .and_then(|value| {
+            let (first, last) = value.strip_prefix("bytes=")?.split_once('-')?;
+            let first: u64 = first.parse().ok()?;
+            let last = match last {
+                "" => len.saturating_sub(1),
+                last => last.parse::<u64>().ok()?.min(len.saturating_sub(1)),
+            };
+            Some((first, last))
+        });
+
+    let mut headers = vec![
+        "Content-Type: application/octet-stream".to_string(),
+        "Accept-Ranges: bytes".to_string(),
+        "Connection: close".to_string(),
+    ];
+    if let Some(ref etag) = options.etag {
+        headers.push(format!("ETag: {}", etag));
+    }
+
+    let (status, payload) = match range {
+        None => ("200 OK", body.clone()),
+        Some((first, _)) if first >= len => {
+            headers.push(format!("Content-Range: bytes */{}", len));
+            ("416 Range Not Satisfiable", Vec::new())
+        }
+        Some((first, last)) => {
+            let honest = ranges_served.fetch_add(1, Ordering::SeqCst) < options.honest_ranges;
+            let reply = if honest {
+                RangeReply::Partial
+            } else {
+                options.ranges
+            };
+            let slice = body[first as usize..=last as usize].to_vec();
+            match reply {
+                RangeReply::Full => ("200 OK", body.clone()),
+                RangeReply::Partial => {
+                    headers.push(format!("Content-Range: bytes {}-{}/{}", first, last, len));
+                    ("206 Partial Content", slice)
+                }
+                RangeReply::Gzip => {
+                    headers.push(format!("Content-Range: bytes {}-{}/{}", first, last, len));
+                    headers.push("Content-Encoding: gzip".to_string());
+                    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+                    encoder.write_all(&slice)?;
+                    ("206 Partial Content", encoder.finish()?)
+                }
+            }
+        }
+    };
+    headers.push(format!("Content-Length: {}", payload.len()));
+
+    let response_head = format!("HTTP/1.1 {}\r\n{}\r\n\r\n", status, headers.join("\r\n"));
+    socket.write_all(response_head.as_bytes()).await?;
+    for piece in payload.chunks(WRITE_PIECE) {
+        socket.write_all(piece).await?;
+        if let Some(delay) = options.write_delay {
+            tokio::time::sleep(delay).await;
+        }
+    }
+    socket.shutdown().await
+}
+
+/// A unique path under the system temp directory; nothing is created there.
+pub(crate) fn scratch_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("ciel-test-{}-{}", uuid::Uuid::new_v4(), name))
+}
+
+/// Deterministic, non-repeating-looking file contents of `len` bytes.
+pub(crate) fn sample_body(len: usize, seed: u8) -> Vec<u8> {
+    (0..len)
+        .map(|i| (i as u32).wrapping_mul(2_654_435_761).to_le_bytes()[1] ^ seed)
+        .collect()
+}
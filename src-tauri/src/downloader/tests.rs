@@ -0,0 +1,358 @@
+use super::test_server::{sample_body, scratch_path, RangeReply, ServerOptions, TestServer};
+use super::*;
+use std::path::Path;
+use std::sync::atomic::AtomicBool;
+use std::time::Duration;
+
+fn config_for(url: &str, filepath: &Path, connections: u8) -> DownloadConfig {
+    DownloadConfig {
+        id: uuid::Uuid::new_v4().to_string(),
+        url: url.to_string(),
+        filepath: filepath.to_path_buf(),
+        connections,
+        retry_base_ms: 10,
+        ..DownloadConfig::default()
+    }
+}
+
+/// A scratch database holding the row the download manager would have created.
+fn seed_db(config: &DownloadConfig) -> String {
+    let path = scratch_path("ciel.db");
+    crate::db::init_db(&path).unwrap();
+    crate::db::open_db(&path)
+        .unwrap()
+        .execute(
+            "INSERT INTO downloads (id, url, filename, filepath, created_at) VALUES (?1, ?2, 'file.bin', ?3, '')",
+            rusqlite::params![config.id, config.url, config.filepath.to_string_lossy()],
+        )
+        .unwrap();
+    path.to_string_lossy().into_owned()
+}
+
+#[test]
+fn if_range_prefers_a_strong_etag() {
+    let validator = ResourceValidator {
+        etag: Some("\"abc\"".to_string()),
+        last_modified: Some("Mon, 01 Jan 2024 00:00:00 GMT".to_string()),
+    };
+    assert_eq!(validator.if_range(), Some("\"abc\""));
+}
+
+#[test]
+fn if_range_falls_back_to_last_modified_for_weak_etags() {
+    let validator = ResourceValidator {
+        etag: Some("W/\"abc\"".to_string()),
+        last_modified: Some("Mon, 01 Jan 2024 00:00:00 GMT".to_string()),
+    };
+    assert_eq!(validator.if_range(), Some("Mon, 01 Jan 2024 00:00:00 GMT"));
+
+    let weak_only = ResourceValidator {
+        etag: Some("W/\"abc\"".to_string()),
+        last_modified: None,
+    };
+    assert_eq!(weak_only.if_range(), None);
+}
+
+#[test]
+fn download_config_round_trips_through_serde() {
+    let config = DownloadConfig {
+        validator: ResourceValidator {
+            etag: Some("\"abc\"".to_string()),
+            last_modified: None,
+        },
+        ..DownloadConfig::default()
+    };
+    let json = serde_json::to_value(&config).unwrap();
+    let back: DownloadConfig = serde_json::from_value(json.clone()).unwrap();
+    assert_eq!(back.validator, config.validator);
+
+    let mut without_validator = json;
+    without_validator
+        .as_object_mut()
+        .unwrap()
+        .remove("validator");
+    let back: DownloadConfig = serde_json::from_value(without_validator).unwrap();
+    assert_eq!(back.validator, ResourceValidator::default());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn resume_sends_if_range_and_appends_when_the_file_is_unchanged() {
+    let body = sample_body(200_000, 1);
+    let server = TestServer::start(ServerOptions {
+        etag: Some("\"v1\"".to_string()),
+        ..ServerOptions::new(body.clone())
+    })
+    .await;
+    let target = scratch_path("unchanged.bin");
+    std::fs::write(part_file_path(&target), &body[..20_000]).unwrap();
+
+    let config = DownloadConfig {
+        validator: ResourceValidator {
+            etag: Some("\"v1\"".to_string()),
+            last_modified: None,
+        },
+        ..config_for(&server.url, &target, 1)
+    };
+    Downloader::new(config)
+        .unwrap()
+        .download(|_| {})
+        .await
+        .unwrap();
+
+    let requests = server.requests();
+    assert_eq!(requests.len(), 1);
+    assert_eq!(requests[0].range.as_deref(), Some("bytes=20000-"));
+    assert_eq!(requests[0].if_range.as_deref(), Some("\"v1\""));
+    assert_eq!(std::fs::read(&target).unwrap(), body);
+    std::fs::remove_file(target).ok();
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn resume_restarts_from_zero_when_if_range_no_longer_matches() {
+    let old_body = sample_body(200_000, 1);
+    let new_body = sample_body(200_000, 2);
+    let server = TestServer::start(ServerOptions {
+        etag: Some("\"v2\"".to_string()),
+        ..ServerOptions::new(new_body.clone())
+    })
+    .await;
+    let target = scratch_path("changed.bin");
+    std::fs::write(part_file_path(&target), &old_body[..20_000]).unwrap();
+
+    let config = DownloadConfig {
+        validator: ResourceValidator {
+            etag: Some("\"v1\"".to_string()),
+            last_modified: None,
+        },
+        ..config_for(&server.url, &target, 1)
+    };
+    Downloader::new(config)
+        .unwrap()
+        .download(|_| {})
+        .await
+        .unwrap();
+
+    let requests = server.requests();
+    assert_eq!(requests[0].if_range.as_deref(), Some("\"v1\""));
+    assert_eq!(std::fs::read(&target).unwrap(), new_body);
+    std::fs::remove_file(target).ok();
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn paused_chunks_resume_at_their_last_flushed_byte() {
+    let body = sample_body(2 * 1024 * 1024, 3);
+    let server = TestServer::start(ServerOptions {
+        write_delay: Some(Duration::from_millis(10)),
+        ..ServerOptions::new(body.clone())
+    })
+    .await;
+    let target = scratch_path("paused.bin");
+    let config = config_for(&server.url, &target, 2);
+    let db_path = seed_db(&config);
+
+    let cancel = Arc::new(AtomicBool::new(false));
+    let pause = {
+        let cancel = cancel.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(300)).await;
+            cancel.store(true, Ordering::SeqCst);
+        })
+    };
+    let result = Downloader::new(config.clone())
+        .unwrap()
+        .with_db(db_path.clone())
+        .with_cancel_signal(cancel)
+        .download(|_| {})
+        .await;
+    pause.await.unwrap();
+    assert!(matches!(result, Err(DownloadError::Cancelled)));
+
+    // Every byte recorded as downloaded must already be in the part file.
+    let part = std::fs::read(part_file_path(&target)).unwrap();
+    let mut expected_ranges = Vec::new();
+    for chunk in crate::db::get_download_chunks(&db_path, &config.id).unwrap() {
+        let start = chunk.start as usize;
+        let flushed = start + chunk.downloaded as usize;
+        assert_eq!(part[start..flushed], body[start..flushed]);
+        if chunk.downloaded > 0 && flushed <= chunk.end as usize {
+            expected_ranges.push(format!("bytes={}-{}", flushed, chunk.end));
+        }
+    }
+    assert!(
+        !expected_ranges.is_empty(),
+        "the pause landed between chunks"
+    );
+
+    let before = server.requests().len();
+    Downloader::new(config)
+        .unwrap()
+        .with_db(db_path.clone())
+        .download(|_| {})
+        .await
+        .unwrap();
+    let resumed: Vec<String> = server.ranges()[before..]
+        .iter()
+        .flatten()
+        .cloned()
+        .collect();
+    for range in expected_ranges {
+        assert!(resumed.contains(&range), "no request for {}", range);
+    }
+    assert_eq!(std::fs::read(&target).unwrap(), body);
+    std::fs::remove_file(target).ok();
+    std::fs::remove_file(db_path).ok();
+}
+
+#[test]
+fn content_range_with_a_known_total() {
+    assert_eq!(
+        parse_content_range("bytes 0-0/12345"),
+        Some((0, 0, Some(12345)))
+    );
+    assert_eq!(
+        parse_content_range(" bytes 100-199/1000 "),
+        Some((100, 199, Some(1000)))
+    );
+}
+
+#[test]
+fn content_range_with_an_unknown_total() {
+    assert_eq!(parse_content_range("bytes 0-0/*"), Some((0, 0, None)));
+}
+
+#[test]
+fn malformed_content_range_is_rejected() {
+    for value in [
+        "",
+        "bytes",
+        "bytes 0-0",
+        "bytes */12345",
+        "items 0-0/12345",
+        "bytes a-b/12345",
+        "bytes 0-0/abc",
+    ] {
+        assert_eq!(parse_content_range(value), None, "{:?}", value);
+    }
+}
+
+#[test]
+fn content_range_with_start_after_end_is_rejected() {
+    assert_eq!(parse_content_range("bytes 200-100/1000"), None);
+}
+
+#[test]
+fn credentials_only_reach_the_primary_host() {
+    use reqwest::header::{AUTHORIZATION, COOKIE};
+
+    let primary = "https://files.example.com/a.iso";
+    let mut headers = HeaderMap::new();
+    apply_basic_auth(&mut headers, primary, Some("user"), Some("pw"));
+    headers.insert(COOKIE, HeaderValue::from_static("session=1"));
+    headers.insert(REFERER, HeaderValue::from_static("https://example.com/"));
+
+    let same = scoped_headers(&headers, "https://FILES.example.com/mirror/a.iso", primary);
+    assert!(same.contains_key(AUTHORIZATION));
+    assert!(same.contains_key(COOKIE));
+
+    let other = scoped_headers(&headers, "https://mirror.example.net/a.iso", primary);
+    assert!(!other.contains_key(AUTHORIZATION));
+    assert!(!other.contains_key(COOKIE));
+    assert!(other.contains_key(REFERER));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn full_reply_to_a_chunk_range_falls_back_to_one_stream() {
+    let body = sample_body(512 * 1024, 4);
+    // Both capability probes get a 206; every chunk request then gets the whole file.
+    let server = TestServer::start(ServerOptions {
+        ranges: RangeReply::Full,
+        honest_ranges: 2,
+        ..ServerOptions::new(body.clone())
+    })
+    .await;
+    let target = scratch_path("ignored-range.bin");
+
+    Downloader::new(config_for(&server.url, &target, 4))
+        .unwrap()
+        .download(|_| {})
+        .await
+        .unwrap();
+
+    // The probes, at least one refused chunk request, then a plain GET for the whole file.
+    let ranges = server.ranges();
+    assert!(
+        ranges[2..].iter().any(Option::is_some),
+        "no chunk was requested"
+    );
+    assert!(
+        ranges[2..].iter().any(Option::is_none),
+        "no single-stream fallback"
+    );
+    assert_eq!(std::fs::read(&target).unwrap(), body);
+    std::fs::remove_file(target).ok();
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn gzipped_chunk_ranges_fall_back_to_one_identity_stream() {
+    let body = sample_body(512 * 1024, 5);
+    let server = TestServer::start(ServerOptions {
+        ranges: RangeReply::Gzip,
+        honest_ranges: 2,
+        ..ServerOptions::new(body.clone())
+    })
+    .await;
+    let target = scratch_path("gzip-range.bin");
+
+    Downloader::new(config_for(&server.url, &target, 4))
+        .unwrap()
+        .download(|_| {})
+        .await
+        .unwrap();
+
+    let ranges = server.ranges();
+    assert!(
+        ranges[2..].iter().any(Option::is_some),
+        "no chunk was requested"
+    );
+    assert!(
+        ranges[2..].iter().any(Option::is_none),
+        "no single-stream fallback"
+    );
+    // Writing the compressed bodies at their offsets would not reproduce the file.
+    assert_eq!(std::fs::read(&target).unwrap(), body);
+    std::fs::remove_file(target).ok();
+}
+
+#[test]
+fn stream_read_timeout_table() {
+    const KIB: u64 = 1024;
+    const MIB: u64 = 1024 * KIB;
+    // (base_secs, adaptive, recent_bytes_per_sec, expected_secs)
+    let cases = [
+        // Fixed: the base always applies, at least one second.
+        (60, false, 0, 60),
+        (60, false, 100 * MIB, 60),
+        (0, false, 0, 1),
+        // Adaptive, at or below the 512 KiB/s reference: still the base.
+        (60, true, 0, 60),
+        (60, true, 512 * KIB, 60),
+        // Adaptive, above it: scaled down with the speed.
+        (60, true, MIB, 30),
+        (60, true, 2 * MIB, 15),
+        // ...but never below the adaptive minimum...
+        (60, true, 100 * MIB, MIN_ADAPTIVE_READ_TIMEOUT_SECS),
+        // ...unless the base itself is lower, which then wins.
+        (5, true, 100 * MIB, 5),
+        (0, true, 100 * MIB, 1),
+    ];
+    for (base, adaptive, bps, expected) in cases {
+        assert_eq!(
+            stream_read_timeout(base, adaptive, bps),
+            Duration::from_secs(expected),
+            "base={} adaptive={} bps={}",
+            base,
+            adaptive,
+            bps
+        );
+    }
+}
@@ -5,8 +5,11 @@ use std::sync::Arc;
 use thiserror::Error;
 
 /// A shared token-bucket rate limiter to coordinate multiple download workers.
+///
+/// The limit is atomic so it can be changed while workers are running; a limit of
+/// `0` means unlimited and takes effect on the next `acquire` iteration.
 pub struct SharedRateLimiter {
-    limit: u64,
+    limit: AtomicU64,
     tokens: AtomicU64,
     last_update: std::sync::Mutex<std::time::Instant>,
 }
@@ -14,19 +17,36 @@ pub struct SharedRateLimiter {
 impl SharedRateLimiter {
     pub fn new(limit: u64) -> Self {
         Self {
-            limit,
+            limit: AtomicU64::new(limit),
             tokens: AtomicU64::new(limit),
             last_update: std::sync::Mutex::new(std::time::Instant::now()),
         }
     }
 
-    pub async fn acquire(&self, amount: u64, cancel_signal: &Option<Arc<AtomicBool>>) {
-        if self.limit == 0 {
-            return;
+    pub fn limit(&self) -> u64 {
+        self.limit.load(Ordering::Relaxed)
+    }
+
+    /// Updates the limit in place. Tokens above the new limit are discarded so a
+    /// lowered limit applies immediately instead of after the bucket drains.
+    pub fn set_limit(&self, limit: u64) {
+        self.limit.store(limit, Ordering::Relaxed);
+        if limit > 0 {
+            let current = self.tokens.load(Ordering::Relaxed);
+            if current > limit {
+                self.tokens.store(limit, Ordering::Relaxed);
+            }
         }
+    }
 
+    pub async fn acquire(&self, amount: u64, cancel_signal: &Option<Arc<AtomicBool>>) {
         let mut remaining = amount;
         while remaining > 0 {
+            let limit = self.limit.load(Ordering::Relaxed);
+            if limit == 0 {
+                return;
+            }
+
             if let Some(sig) = cancel_signal {
                 if sig.load(Ordering::Relaxed) {
                     return;
@@ -39,10 +59,10 @@ impl SharedRateLimiter {
                 let elapsed = now.duration_since(*last_update).as_secs_f64();
 
                 if elapsed >= 0.01 {
-                    let refill = (self.limit as f64 * elapsed) as u64;
+                    let refill = (limit as f64 * elapsed) as u64;
                     if refill > 0 {
                         let current = self.tokens.load(Ordering::Relaxed);
-                        let new_tokens = (current + refill).min(self.limit);
+                        let new_tokens = (current + refill).min(limit);
                         self.tokens.store(new_tokens, Ordering::Relaxed);
                         *last_update = now;
                     }
@@ -79,10 +99,30 @@ pub enum DownloadError {
     Io(String),
     #[error("Server does not support range requests")]
     NoRangeSupport,
+    /// The server applied a `Content-Encoding` to a range response, so its bytes
+    /// don't line up with file offsets.
+    #[error("Server compressed a range response ({0})")]
+    CompressedRange(String),
+    #[error("Chunk {start}-{end} failed after {attempts} attempts (last status: {last_status}): {last_error}")]
+    RetriesExhausted {
+        start: u64,
+        end: u64,
+        attempts: u32,
+        last_status: String,
+        last_error: String,
+    },
+    #[error("Checksum mismatch ({algo}): expected {expected}, got {actual}")]
+    ChecksumMismatch {
+        algo: String,
+        expected: String,
+        actual: String,
+    },
     #[error("Download cancelled")]
     Cancelled,
     #[error("Invalid URL: {0}")]
     InvalidUrl(String),
+    #[error("Invalid header: {0}")]
+    InvalidHeader(String),
 }
 
 impl From<reqwest::Error> for DownloadError {
@@ -101,6 +141,9 @@ impl From<std::io::Error> for DownloadError {
 pub struct DownloadConfig {
     pub id: String,
     pub url: String,
+    /// Every source for this file, `url` first. Extra entries are mirrors that
+    /// must serve the same `Content-Length`; empty means `url` only.
+    pub urls: Vec<String>,
     pub filepath: PathBuf,
     pub connections: u8,
     pub chunk_size: u64,
@@ -109,6 +152,37 @@ pub struct DownloadConfig {
     pub cookies: Option<String>,
     pub force_multi: bool,
     pub size_hint: Option<u64>,
+    /// Seconds to wait for the next body read before treating a connection as dead.
+    pub stream_read_timeout_secs: u64,
+    /// Shorten the read timeout for connections that were recently fast.
+    pub adaptive_read_timeout: bool,
+    /// Checkpoint flushed chunk bytes and re-check them before trusting resumed progress.
+    pub resume_verify: bool,
+    /// Attempts per worker before a chunk is handed to another worker.
+    pub max_retries: u32,
+    /// First retry delay; later retries back off exponentially from it.
+    pub retry_base_ms: u64,
+    /// Seconds without any byte arriving before idle connections are restarted (0 = off).
+    pub stall_timeout_secs: u64,
+    /// Write buffer per open file in KiB; 0 keeps the built-in size.
+    pub write_buffer_kb: u64,
+    /// `fsync` the part file before its progress is recorded, so a crash never
+    /// leaves the database ahead of the disk. Costs throughput on slow disks.
+    pub durable_writes: bool,
+    /// Proxy URL (`http://`, `https://` or `socks5://`, credentials allowed).
+    pub proxy: Option<String>,
+    /// Extra request headers (e.g. `Referer`, `Authorization`), sent on every request.
+    pub headers: Option<Vec<(String, String)>>,
+    /// HTTP Basic auth credentials; without them, credentials in `url` are used.
+    pub username: Option<String>,
+    pub password: Option<String>,
+    /// Validators recorded when the download started; checked before reusing chunks.
+    #[serde(default)]
+    pub validator: ResourceValidator,
+    /// Hex digest the finished file must match; a mismatch fails the download.
+    pub expected_checksum: Option<String>,
+    /// Algorithm of `expected_checksum`; `Auto` picks it from the digest length.
+    pub checksum_algo: ChecksumAlgo,
 }
 
 impl Default for DownloadConfig {
@@ -116,6 +190,7 @@ impl Default for DownloadConfig {
         Self {
             id: "default".to_string(),
             url: String::new(),
+            urls: Vec::new(),
             filepath: PathBuf::new(),
             connections: 8,
             chunk_size: 5 * 1024 * 1024,
@@ -124,16 +199,133 @@ impl Default for DownloadConfig {
             cookies: None,
             force_multi: false,
             size_hint: None,
+            stream_read_timeout_secs: 60,
+            adaptive_read_timeout: false,
+            resume_verify: false,
+            max_retries: 10,
+            retry_base_ms: 1000,
+            stall_timeout_secs: 20,
+            write_buffer_kb: 0,
+            durable_writes: false,
+            proxy: None,
+            headers: None,
+            username: None,
+            password: None,
+            validator: ResourceValidator::default(),
+            expected_checksum: None,
+            checksum_algo: ChecksumAlgo::Auto,
         }
     }
 }
 
+/// Hash algorithm used to verify a finished download.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChecksumAlgo {
+    Md5,
+    Sha1,
+    Sha256,
+    Crc32,
+    /// Picks the algorithm from the length of the expected hex digest.
+    Auto,
+}
+
+impl ChecksumAlgo {
+    /// Resolves `Auto` from the expected digest; other variants are returned as-is.
+    pub fn resolve(self, expected_hash: &str) -> Option<Self> {
+        match self {
+            ChecksumAlgo::Auto => match expected_hash.trim().len() {
+                8 => Some(ChecksumAlgo::Crc32),
+                32 => Some(ChecksumAlgo::Md5),
+                40 => Some(ChecksumAlgo::Sha1),
+                64 => Some(ChecksumAlgo::Sha256),
+                _ => None,
+            },
+            algo => Some(algo),
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "md5" => Some(ChecksumAlgo::Md5),
+            "sha1" => Some(ChecksumAlgo::Sha1),
+            "sha256" => Some(ChecksumAlgo::Sha256),
+            "crc32" => Some(ChecksumAlgo::Crc32),
+            "auto" => Some(ChecksumAlgo::Auto),
+            _ => None,
+        }
+    }
+
+    /// Splits an `algo:digest` value (a bare digest means `Auto`) into its
+    /// algorithm and lowercase digest. Returns `None` if either part is unusable.
+    pub fn parse_spec(spec: &str) -> Option<(Self, String)> {
+        let spec = spec.trim();
+        let (algo, digest) = match spec.split_once(':') {
+            Some((name, digest)) => (Self::from_name(name)?, digest),
+            None => (ChecksumAlgo::Auto, spec),
+        };
+        let digest = digest.trim().to_lowercase();
+        if digest.is_empty() || !digest.chars().all(|c| c.is_ascii_hexdigit()) {
+            return None;
+        }
+        algo.resolve(&digest)?;
+        Some((algo, digest))
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ChecksumAlgo::Md5 => "md5",
+            ChecksumAlgo::Sha1 => "sha1",
+            ChecksumAlgo::Sha256 => "sha256",
+            ChecksumAlgo::Crc32 => "crc32",
+            ChecksumAlgo::Auto => "auto",
+        }
+    }
+}
+
+/// `ETag` / `Last-Modified` pair identifying one version of a remote file.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ResourceValidator {
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+}
+
+impl ResourceValidator {
+    pub fn from_headers(headers: &reqwest::header::HeaderMap) -> Self {
+        let read = |name| {
+            headers
+                .get(name)
+                .and_then(|v| v.to_str().ok())
+                .map(|v| v.trim().to_string())
+                .filter(|v| !v.is_empty())
+        };
+        Self {
+            etag: read(reqwest::header::ETAG),
+            last_modified: read(reqwest::header::LAST_MODIFIED),
+        }
+    }
+
+    /// Value for an `If-Range` header. Weak ETags are not allowed there, so
+    /// `Last-Modified` is used instead when the ETag is weak or missing.
+    pub fn if_range(&self) -> Option<&str> {
+        self.etag
+            .as_deref()
+            .filter(|etag| !etag.starts_with("W/"))
+            .or(self.last_modified.as_deref())
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DownloadProgress {
     pub id: String,
     pub total: u64,
     pub downloaded: u64,
+    /// Smoothed bytes/sec; see [`crate::downloader::SpeedAverager`].
     pub speed: u64,
+    /// Bytes/sec over the last sample interval only.
+    #[serde(default)]
+    pub instant_speed: u64,
+    /// Seconds left at the smoothed speed.
     pub eta: u64,
     pub connections: u8,
     pub speed_limit: u64,
@@ -149,6 +341,43 @@ pub struct ChunkRecord {
     pub start: i64,
     pub end: i64,
     pub downloaded: i64,
+    /// Bytes of this chunk known to be on disk at the last `resume_verify` checkpoint.
+    #[serde(default)]
+    pub verified: i64,
+    /// CRC32 of the bytes just before `start + verified`, re-checked on resume.
+    #[serde(default)]
+    pub verified_hash: Option<String>,
+}
+
+/// Rolling one-second throughput measurement for a single connection.
+pub(super) struct ReadThroughput {
+    window_start: std::time::Instant,
+    window_bytes: u64,
+    bytes_per_sec: u64,
+}
+
+impl ReadThroughput {
+    pub(super) fn new() -> Self {
+        Self {
+            window_start: std::time::Instant::now(),
+            window_bytes: 0,
+            bytes_per_sec: 0,
+        }
+    }
+
+    pub(super) fn record(&mut self, len: u64) {
+        self.window_bytes += len;
+        let elapsed = self.window_start.elapsed().as_secs_f64();
+        if elapsed >= 1.0 {
+            self.bytes_per_sec = (self.window_bytes as f64 / elapsed) as u64;
+            self.window_bytes = 0;
+            self.window_start = std::time::Instant::now();
+        }
+    }
+
+    pub(super) fn bytes_per_sec(&self) -> u64 {
+        self.bytes_per_sec
+    }
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -157,4 +386,6 @@ pub(super) struct WorkChunk {
     pub(super) end: u64,
     pub(super) downloaded: u64,
     pub(super) _index: usize,
+    /// Number of workers that exhausted their retries on this chunk.
+    pub(super) failed_workers: u32,
 }
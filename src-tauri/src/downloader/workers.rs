@@ -1,17 +1,182 @@
 use futures::StreamExt;
 use reqwest::Client;
-use std::path::PathBuf;
-use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, AtomicU8, AtomicUsize, Ordering};
 use std::sync::{Arc, Mutex};
 use tokio::io::{AsyncSeekExt, AsyncWriteExt, BufWriter};
 use tokio::sync::mpsc;
+use tracing::Instrument;
 
-use super::types::{SharedRateLimiter, WorkChunk};
-use super::{decorate_media_request, DownloadError, DownloadProgress};
+use super::types::{ReadThroughput, SharedRateLimiter, WorkChunk};
+use super::{
+    checkpoint_hash, decorate_media_request, parse_content_range, scoped_headers,
+    stream_read_timeout, DownloadError, DownloadProgress, SpeedAverager,
+};
+
+/// How many distinct workers may give up on a chunk before the whole download fails.
+const MAX_WORKERS_PER_CHUNK: u32 = 3;
+/// How often aggregate throughput is sampled to steer the worker count.
+const SCALING_PROBE_INTERVAL: std::time::Duration = std::time::Duration::from_secs(5);
+/// Relative throughput change that counts as a real gain or loss rather than noise.
+const SCALING_MIN_CHANGE: f64 = 0.05;
+/// After holding steady this long, probe one extra worker in case conditions improved.
+const SCALING_EXPLORE_AFTER: std::time::Duration = std::time::Duration::from_secs(30);
+/// After a 429/503, workers are not added back for this long.
+const THROTTLE_COOLDOWN: std::time::Duration = std::time::Duration::from_secs(30);
+/// Under a speed limit, each worker should get at least this much to avoid slow-consumer resets.
+pub(super) const MIN_SPEED_PER_WORKER: u64 = 512 * 1024;
+
+/// 429/503 bursts after which the reduced worker count is reported as the host's cap.
+const HOST_THROTTLE_REPEATS: u32 = 2;
+
+/// Longest pause between two attempts at the same chunk.
+const RETRY_BACKOFF_CAP_MS: u64 = 30_000;
+
+/// Consecutive failures after which a mirror leaves the rotation.
+const MIRROR_MAX_FAILURES: u32 = 5;
+
+/// How often a worker blocked on the network checks whether it was paused.
+const CANCEL_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_millis(100);
+
+/// Whether `err` comes from the server tearing down the connection.
+fn is_connection_reset(err: &reqwest::Error) -> bool {
+    let mut source = std::error::Error::source(err);
+    while let Some(cause) = source {
+        if let Some(io) = cause.downcast_ref::<std::io::Error>() {
+            return matches!(
+                io.kind(),
+                std::io::ErrorKind::ConnectionReset | std::io::ErrorKind::ConnectionAborted
+            );
+        }
+        source = cause.source();
+    }
+    false
+}
+
+/// Resolves once `signal` is raised; never resolves without a signal.
+pub(super) async fn wait_for_cancel(signal: &Option<Arc<AtomicBool>>) {
+    match signal {
+        Some(signal) => {
+            while !signal.load(Ordering::Relaxed) {
+                tokio::time::sleep(CANCEL_POLL_INTERVAL).await;
+            }
+        }
+        None => std::future::pending().await,
+    }
+}
+
+/// Built-in write buffer of a chunk worker.
+pub(super) const CHUNK_WRITE_BUFFER: usize = 128 * 1024;
+
+/// Empties the write buffer and, with `durable`, waits until the data is on disk.
+pub(super) async fn flush_file(
+    file: &mut BufWriter<tokio::fs::File>,
+    durable: bool,
+) -> std::io::Result<()> {
+    file.flush().await?;
+    if durable {
+        file.get_ref().sync_data().await?;
+    }
+    Ok(())
+}
+
+/// Persists flushed chunk progress with a checksum of the bytes before it (`resume_verify`).
+fn checkpoint_chunk(db_path: &str, id: &str, filepath: &Path, chunk_start: u64, downloaded: u64) {
+    match checkpoint_hash(filepath, chunk_start, chunk_start + downloaded) {
+        Ok(hash) => crate::db::update_chunk_checkpoint(
+            db_path,
+            id,
+            chunk_start as i64,
+            downloaded as i64,
+            &hash,
+        ),
+        Err(_) => {
+            crate::db::update_chunk_progress(db_path, id, chunk_start as i64, downloaded as i64)
+        }
+    }
+    .ok();
+}
+
+/// Writes the batched periodic chunk progress in a single transaction.
+fn flush_chunk_progress(
+    db_path: &str,
+    id: &str,
+    pending: &Mutex<std::collections::HashMap<u64, u64>>,
+) {
+    let updates = std::mem::take(&mut *pending.lock().unwrap())
+        .into_iter()
+        .map(|(start, downloaded)| (start as i64, downloaded as i64))
+        .collect::<Vec<_>>();
+    if let Err(e) = crate::db::update_chunk_progress_batch(db_path, id, updates) {
+        tracing::warn!("[{}] Failed to save chunk progress: {}", id, e);
+    }
+}
+
+/// Range-capable sources serving identical bytes. Index 0 is the primary URL.
+pub(super) struct MirrorPool {
+    urls: Vec<String>,
+    failures: Vec<AtomicU32>,
+    dropped: Vec<AtomicBool>,
+    next: AtomicUsize,
+}
+
+impl MirrorPool {
+    pub(super) fn new(urls: Vec<String>) -> Self {
+        Self {
+            failures: urls.iter().map(|_| AtomicU32::new(0)).collect(),
+            dropped: urls.iter().map(|_| AtomicBool::new(false)).collect(),
+            next: AtomicUsize::new(0),
+            urls,
+        }
+    }
+
+    pub(super) fn url(&self, index: usize) -> &str {
+        &self.urls[index]
+    }
+
+    fn live_count(&self) -> usize {
+        self.dropped
+            .iter()
+            .filter(|d| !d.load(Ordering::Relaxed))
+            .count()
+    }
+
+    /// Next live mirror in round-robin order, preferring one other than `avoid`.
+    pub(super) fn pick(&self, avoid: Option<usize>) -> usize {
+        let len = self.urls.len();
+        let start = self.next.fetch_add(1, Ordering::Relaxed);
+        let live = |i: &usize| !self.dropped[*i].load(Ordering::Relaxed);
+        (0..len)
+            .map(|offset| (start + offset) % len)
+            .filter(live)
+            .find(|i| Some(*i) != avoid)
+            .or_else(|| avoid.filter(live))
+            .unwrap_or(0)
+    }
+
+    pub(super) fn record_success(&self, index: usize) {
+        self.failures[index].store(0, Ordering::Relaxed);
+    }
+
+    /// Counts a failed request; returns true if the mirror was dropped as a result.
+    pub(super) fn record_failure(&self, index: usize) -> bool {
+        let failures = self.failures[index].fetch_add(1, Ordering::Relaxed) + 1;
+        failures >= MIRROR_MAX_FAILURES && self.drop_mirror(index)
+    }
+
+    /// Removes a mirror from rotation for this session. The last live mirror is kept.
+    pub(super) fn drop_mirror(&self, index: usize) -> bool {
+        if self.live_count() <= 1 || self.dropped[index].swap(true, Ordering::Relaxed) {
+            return false;
+        }
+        true
+    }
+}
 
 pub(super) struct SpeedState {
     pub(super) last_time: std::time::Instant,
     pub(super) last_bytes: u64,
+    pub(super) averager: SpeedAverager,
 }
 
 pub(super) enum WorkerOutcome {
@@ -24,20 +189,38 @@ pub(super) enum WorkerOutcome {
 
 pub(super) struct WorkerOrchestrationConfig {
     pub(super) id: String,
-    pub(super) url: String,
+    pub(super) mirrors: Arc<MirrorPool>,
     pub(super) filepath: PathBuf,
     pub(super) client: Client,
+    pub(super) retry_client: Client,
+    pub(super) request_headers: reqwest::header::HeaderMap,
+    /// Sent as `If-Range` so a changed file yields `200` instead of mismatched bytes.
+    /// Only used against the primary URL; mirrors carry their own validators.
+    pub(super) if_range: Option<String>,
     pub(super) db_path: Option<String>,
     pub(super) cancel_signal: Option<Arc<AtomicBool>>,
     pub(super) rate_limiter: Option<Arc<SharedRateLimiter>>,
+    pub(super) handle_permits: Option<Arc<tokio::sync::Semaphore>>,
+    pub(super) stream_read_timeout_secs: u64,
+    pub(super) adaptive_read_timeout: bool,
+    pub(super) resume_verify: bool,
+    pub(super) max_retries: u32,
+    pub(super) retry_base_ms: u64,
+    /// Seconds without any byte arriving before idle connections are restarted (0 = off).
+    pub(super) stall_timeout_secs: u64,
+    pub(super) write_buffer_bytes: usize,
+    pub(super) durable_writes: bool,
     pub(super) progress: Arc<Mutex<DownloadProgress>>,
     pub(super) downloaded_atomic: Arc<AtomicU64>,
-    pub(super) last_emit: Arc<AtomicU64>,
     pub(super) speed_state: Arc<Mutex<SpeedState>>,
     pub(super) on_progress: Arc<dyn Fn(DownloadProgress) + Send + Sync + 'static>,
     pub(super) pending_chunks: Vec<WorkChunk>,
     pub(super) max_workers: u8,
     pub(super) current_target_workers: u8,
+    /// Set to the worker count the server tolerated once it keeps throttling (0 = never).
+    pub(super) throttle_ceiling: Arc<AtomicU8>,
+    /// Halved worker ceiling once the server throttled or reset a connection (0 = never).
+    pub(super) connection_penalty: Arc<AtomicU8>,
 }
 
 pub(super) async fn run_workers(
@@ -45,25 +228,39 @@ pub(super) async fn run_workers(
 ) -> Result<WorkerOutcome, DownloadError> {
     let WorkerOrchestrationConfig {
         id,
-        url,
+        mirrors,
         filepath,
         client,
+        retry_client,
+        request_headers,
+        if_range,
         db_path,
         cancel_signal,
         rate_limiter,
+        handle_permits,
+        stream_read_timeout_secs,
+        adaptive_read_timeout,
+        resume_verify,
+        max_retries,
+        retry_base_ms,
+        stall_timeout_secs,
+        write_buffer_bytes,
+        durable_writes,
         progress,
         downloaded_atomic,
-        last_emit,
         speed_state,
         on_progress,
         pending_chunks,
         max_workers,
         current_target_workers,
+        throttle_ceiling,
+        connection_penalty,
     } = cfg;
 
     let error_occurred = Arc::new(Mutex::new(None));
     let abort_workers = Arc::new(AtomicBool::new(false));
     let throttled = Arc::new(Mutex::new(false));
+    let connection_reset = Arc::new(AtomicBool::new(false));
     let failure_count = Arc::new(AtomicUsize::new(0));
     let range_diag_logged = Arc::new(AtomicBool::new(false));
     let multi_start = std::time::Instant::now();
@@ -74,18 +271,73 @@ pub(super) async fn run_workers(
             .collect::<Vec<_>>(),
     ));
     let active_workers = Arc::new(Mutex::new(0u8));
+    // Periodic chunk progress, keyed by chunk start and flushed in one transaction.
+    let chunk_progress = Arc::new(Mutex::new(std::collections::HashMap::<u64, u64>::new()));
     let (worker_tx, mut worker_rx) = mpsc::channel::<()>(32);
     let mut last_global_db_update = std::time::Instant::now();
-    let start_emit_time = std::time::Instant::now();
-    let mut target_workers = current_target_workers.clamp(1, max_workers.max(1));
+    // Connection penalty: once the server throttles or resets us, the worker ceiling
+    // is halved for the rest of this download so scaling never ramps back into it.
+    let worker_ceiling = || match connection_penalty.load(Ordering::Relaxed) {
+        0 => max_workers.max(1),
+        penalty => penalty.min(max_workers.max(1)),
+    };
+    let mut target_workers = current_target_workers.clamp(1, worker_ceiling());
     let mut last_failure_seen = 0usize;
     let mut last_scale_down_at = std::time::Instant::now();
-    let mut last_scale_up_at = std::time::Instant::now();
-    let mut stable_since = std::time::Instant::now();
+    // Hill-climbing state: throughput of the previous sample window and the
+    // worker step (-1, 0, +1) taken after it.
+    let mut last_probe_at = std::time::Instant::now();
+    let mut last_probe_bytes = downloaded_atomic.load(Ordering::Relaxed);
+    let mut last_probe_throughput = 0.0f64;
+    let mut last_step: i8 = 0;
+    let mut last_step_at = std::time::Instant::now();
+    let mut throttle_events = 0u32;
+    let mut throttle_cooldown_until: Option<std::time::Instant> = None;
+    // Stall watchdog: bumping the generation makes every idle worker reconnect.
+    let (stall_tx, _) = tokio::sync::watch::channel(0u64);
+    let mut last_progress_bytes = downloaded_atomic.load(Ordering::Relaxed);
+    let mut last_progress_at = std::time::Instant::now();
 
     loop {
+        if cancel_signal
+            .as_ref()
+            .is_some_and(|sig| sig.load(Ordering::Relaxed))
+        {
+            // Each worker flushes and checkpoints its chunk on the way out; report the
+            // pause only once all of them have, so a resume starts exactly here.
+            loop {
+                let active = *active_workers.lock().unwrap();
+                if active == 0 {
+                    break;
+                }
+                tokio::select! {
+                    _ = worker_rx.recv() => {},
+                    _ = tokio::time::sleep(CANCEL_POLL_INTERVAL) => {},
+                }
+            }
+            if let Some(ref db) = db_path {
+                flush_chunk_progress(db, &id, &chunk_progress);
+                let downloaded = downloaded_atomic.load(Ordering::Relaxed);
+                crate::db::update_download_progress(db, &id, downloaded as i64, 0).ok();
+            }
+            return Err(DownloadError::Cancelled);
+        }
+
         let worker_error = { error_occurred.lock().unwrap().clone() };
         if let Some(err) = worker_error {
+            if let DownloadError::CompressedRange(ref encoding) = err {
+                abort_workers.store(true, Ordering::Relaxed);
+                tracing::info!(
+                    "[{}] Falling back to single connection: range responses are {}-encoded.",
+                    id,
+                    encoding
+                );
+                return Ok(WorkerOutcome::NeedsFallback {
+                    reason:
+                        "Server compresses partial responses. Switching to single connection...",
+                    cache_single_host: false,
+                });
+            }
             if matches!(err, DownloadError::NoRangeSupport) {
                 abort_workers.store(true, Ordering::Relaxed);
                 tracing::info!(
@@ -128,6 +380,35 @@ pub(super) async fn run_workers(
             });
         }
 
+        let bytes_now = downloaded_atomic.load(Ordering::Relaxed);
+        if bytes_now != last_progress_bytes {
+            last_progress_bytes = bytes_now;
+            last_progress_at = std::time::Instant::now();
+        } else if stall_timeout_secs > 0
+            && *active_workers.lock().unwrap() > 0
+            && last_progress_at.elapsed() >= std::time::Duration::from_secs(stall_timeout_secs)
+        {
+            tracing::info!(
+                "[{}] No data for {}s; restarting idle connections",
+                id,
+                stall_timeout_secs
+            );
+            speed_state.lock().unwrap().averager.reset();
+            let snapshot = {
+                let mut p = progress.lock().unwrap();
+                p.speed = 0;
+                p.instant_speed = 0;
+                p.status_text = Some("Stalled \u{2014} retrying".to_string());
+                p.status_phase = Some("stalled".to_string());
+                p.phase_elapsed_secs = Some(0);
+                p.clone()
+            };
+            (on_progress)(snapshot);
+            stall_tx.send_modify(|generation| *generation += 1);
+            // Give the reconnected workers a full window before acting again.
+            last_progress_at = std::time::Instant::now();
+        }
+
         // Adaptive worker scaling (AIMD-style):
         // - Decrease quickly on throttling/repeated chunk failures.
         // - Increase slowly (+1) after sustained stability.
@@ -143,9 +424,26 @@ pub(super) async fn run_workers(
                 false
             }
         };
+        let reset_now = connection_reset.swap(false, Ordering::Relaxed);
 
-        if had_new_failures || throttled_now {
-            stable_since = now;
+        if had_new_failures || throttled_now || reset_now {
+            // Failures invalidate the current throughput sample.
+            last_probe_at = now;
+            last_probe_bytes = downloaded_atomic.load(Ordering::Relaxed);
+            last_probe_throughput = 0.0;
+            last_step = 0;
+            last_step_at = now;
+            if (throttled_now || reset_now) && connection_penalty.load(Ordering::Relaxed) == 0 {
+                let ceiling = (target_workers / 2).max(1);
+                connection_penalty.store(ceiling, Ordering::Relaxed);
+                tracing::info!(
+                    "[{}] Connection safety engaged: capping at {} workers for the rest of this download",
+                    id,
+                    ceiling
+                );
+                target_workers = target_workers.min(ceiling);
+                last_scale_down_at = now;
+            }
             if now.duration_since(last_scale_down_at) >= std::time::Duration::from_secs(2)
                 && target_workers > 1
             {
@@ -164,22 +462,88 @@ pub(super) async fn run_workers(
                 last_scale_down_at = now;
             }
             last_failure_seen = failures_now;
-        } else if now.duration_since(stable_since) >= std::time::Duration::from_secs(8)
-            && now.duration_since(last_scale_up_at) >= std::time::Duration::from_secs(8)
-            && target_workers < max_workers
-            && !pending_chunks.lock().unwrap().is_empty()
-        {
-            let previous = target_workers;
-            target_workers = (target_workers + 1).min(max_workers);
-            if target_workers > previous {
+            if throttled_now {
+                throttle_cooldown_until = Some(now + THROTTLE_COOLDOWN);
+                let snapshot = {
+                    let mut p = progress.lock().unwrap();
+                    p.status_text = Some("Server busy, reducing connections".to_string());
+                    p.status_phase = Some("throttled".to_string());
+                    p.phase_elapsed_secs = Some(0);
+                    p.clone()
+                };
+                (on_progress)(snapshot);
+                throttle_events += 1;
+                if throttle_events >= HOST_THROTTLE_REPEATS {
+                    throttle_ceiling.store(target_workers, Ordering::Relaxed);
+                }
+            }
+        } else if now.duration_since(last_probe_at) >= SCALING_PROBE_INTERVAL {
+            // Hill climbing on aggregate throughput: keep moving while it pays off,
+            // back off when it hurts, and shed workers that add nothing.
+            let bytes_now = downloaded_atomic.load(Ordering::Relaxed);
+            let elapsed = now.duration_since(last_probe_at).as_secs_f64();
+            let throughput = bytes_now.saturating_sub(last_probe_bytes) as f64 / elapsed;
+
+            let limit_ceiling = match rate_limiter.as_ref().map(|l| l.limit()) {
+                Some(limit) if limit > 0 => ((limit / MIN_SPEED_PER_WORKER).min(255) as u8).max(1),
+                _ => u8::MAX,
+            };
+            let ceiling = worker_ceiling().min(limit_ceiling);
+            let has_pending = !pending_chunks.lock().unwrap().is_empty();
+
+            // A reversal returns to the last good count, so settle there afterwards.
+            let mut settle = false;
+            let step: i8 = if last_probe_throughput <= 0.0 {
+                // First clean sample only establishes the baseline.
+                0
+            } else {
+                let change = (throughput - last_probe_throughput) / last_probe_throughput;
+                if change > SCALING_MIN_CHANGE {
+                    last_step
+                } else if change < -SCALING_MIN_CHANGE {
+                    settle = last_step != 0;
+                    if last_step == 0 {
+                        -1
+                    } else {
+                        -last_step
+                    }
+                } else if last_step != 0 {
+                    // Same speed with a different worker count: fewer is cheaper.
+                    -1
+                } else if now.duration_since(last_step_at) >= SCALING_EXPLORE_AFTER {
+                    1
+                } else {
+                    0
+                }
+            };
+
+            let cooling_down = throttle_cooldown_until.is_some_and(|until| now < until);
+            let mut proposed = (target_workers as i16 + step as i16).clamp(1, ceiling as i16) as u8;
+            if proposed > target_workers && (!has_pending || cooling_down) {
+                proposed = target_workers;
+            }
+            if proposed != target_workers {
                 tracing::info!(
-                    "[{}] Adaptive scaling: {} -> {} workers (stable transfer)",
+                    "[{}] Adaptive scaling: {} -> {} workers ({:.0} KB/s)",
                     id,
-                    previous,
-                    target_workers
+                    target_workers,
+                    proposed,
+                    throughput / 1024.0
                 );
+                last_step = match (settle, proposed > target_workers) {
+                    (true, _) => 0,
+                    (false, true) => 1,
+                    (false, false) => -1,
+                };
+                last_step_at = now;
+                target_workers = proposed;
+            } else {
+                last_step = 0;
             }
-            last_scale_up_at = now;
+
+            last_probe_at = now;
+            last_probe_bytes = bytes_now;
+            last_probe_throughput = throughput;
         }
 
         let mut current_active = *active_workers.lock().unwrap();
@@ -200,27 +564,36 @@ pub(super) async fn run_workers(
             let db_path_clone = db_path.clone();
             let id_clone = id.clone();
             let client_clone = client.clone();
-            let url_clone = url.clone();
+            let retry_client_clone = retry_client.clone();
+            let request_headers_clone = request_headers.clone();
+            let if_range_clone = if_range.clone();
+            let mirrors_clone = mirrors.clone();
             let filepath_clone = filepath.clone();
             let tx = worker_tx.clone();
             let error_ptr = error_occurred.clone();
             let throttled_ptr = throttled.clone();
+            let reset_ptr = connection_reset.clone();
             let cancel_signal_clone = cancel_signal.clone();
             let abort_signal = abort_workers.clone();
             let failure_counter = failure_count.clone();
             let range_diag_logged_clone = range_diag_logged.clone();
-            let last_emit_clone = last_emit.clone();
             let speed_state_clone = speed_state.clone();
+            let chunk_progress_clone = chunk_progress.clone();
             let rate_limiter_clone = rate_limiter.clone();
+            let handle_permits_clone = handle_permits.clone();
+            let mut stall_rx = stall_tx.subscribe();
 
             *active_workers.lock().unwrap() += 1;
             current_active += 1;
 
             tokio::spawn(async move {
                 let mut chunk = chunk;
-                let mut attempts = 0;
-                let max_retries = 10;
+                let mut attempts: u32 = 0;
+                let mut last_status: Option<reqwest::StatusCode> = None;
+                let mut stall_restart = false;
                 let mut final_error = None;
+                let mut retries_exhausted = false;
+                let mut mirror = mirrors_clone.pick(None);
 
                 'worker_mission: loop {
                     if abort_signal.load(Ordering::Relaxed) {
@@ -239,12 +612,14 @@ pub(super) async fn run_workers(
                             chunk.start,
                             chunk.end
                         );
+                        retries_exhausted = true;
                         break;
                     }
 
                     if attempts > 0 {
-                        let backoff = 2u64.pow(attempts as u32 - 1) * 1000;
-                        let backoff = backoff.min(30000);
+                        let backoff = retry_base_ms
+                            .saturating_mul(2u64.saturating_pow(attempts - 1))
+                            .min(RETRY_BACKOFF_CAP_MS);
                         tracing::info!(
                             "[{}] Retry #{} for chunk {}-{}. Sleeping {}ms",
                             id_clone,
@@ -270,20 +645,65 @@ pub(super) async fn run_workers(
                         }
                     }
 
+                    // Wait for a slot in the global open-handle budget before touching
+                    // the file or the network. Held until this attempt finishes.
+                    let _handle_permit = match &handle_permits_clone {
+                        Some(permits) => {
+                            let acquire = permits.clone().acquire_owned();
+                            tokio::pin!(acquire);
+                            loop {
+                                tokio::select! {
+                                    permit = &mut acquire => break permit.ok(),
+                                    _ = tokio::time::sleep(std::time::Duration::from_millis(200)) => {
+                                        if abort_signal.load(Ordering::Relaxed) { break 'worker_mission; }
+                                        if let Some(sig) = &cancel_signal_clone {
+                                            if sig.load(Ordering::Relaxed) { break 'worker_mission; }
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                        None => None,
+                    };
+
+                    let url_clone = mirrors_clone.url(mirror).to_string();
+                    let if_range_for_mirror = if_range_clone.as_ref().filter(|_| mirror == 0);
+                    let mirror_headers =
+                        scoped_headers(&request_headers_clone, &url_clone, mirrors_clone.url(0));
+
+                    // First attempt uses the short connect timeout; retries are more patient.
+                    let request_client = if attempts == 0 {
+                        &client_clone
+                    } else {
+                        &retry_client_clone
+                    };
+
                     let res = async {
                         let chunk_file_raw = tokio::fs::OpenOptions::new().write(true).open(&filepath_clone).await?;
-                        let mut chunk_file = BufWriter::with_capacity(128 * 1024, chunk_file_raw);
+                        let mut chunk_file = BufWriter::with_capacity(write_buffer_bytes, chunk_file_raw);
                         let current_start = chunk.start + chunk.downloaded;
                         chunk_file.seek(tokio::io::SeekFrom::Start(current_start)).await?;
 
+                        // Only stalls detected from here on concern this connection.
+                        stall_rx.borrow_and_update();
+
                         let range = format!("bytes={}-{}", current_start, chunk.end);
-                        let response = decorate_media_request(
-                            client_clone.get(url_clone.clone()),
+                        let mut request = decorate_media_request(
+                            request_client.get(url_clone.clone()),
                             &url_clone,
+                            &mirror_headers,
                         )
-                            .header(reqwest::header::RANGE, range.clone())
-                            .send()
-                            .await?;
+                            .header(reqwest::header::RANGE, range.clone());
+                        if let Some(validator) = if_range_for_mirror {
+                            request = request.header(reqwest::header::IF_RANGE, validator.clone());
+                        }
+                        let response = request.send().await.map_err(|e| {
+                            if is_connection_reset(&e) {
+                                reset_ptr.store(true, Ordering::Relaxed);
+                            }
+                            e
+                        })?;
+                        last_status = Some(response.status());
 
                         if response.status() == 429 || response.status() == 503 {
                             *throttled_ptr.lock().unwrap() = true;
@@ -292,7 +712,11 @@ pub(super) async fn run_workers(
 
                         let status = response.status();
                         let headers = response.headers();
-                        let has_content_range = headers.contains_key(reqwest::header::CONTENT_RANGE);
+                        let range_start = headers
+                            .get(reqwest::header::CONTENT_RANGE)
+                            .and_then(|v| v.to_str().ok())
+                            .and_then(parse_content_range)
+                            .map(|(first, _, _)| first);
                         if !status.is_success() {
                             if matches!(
                                 status,
@@ -304,7 +728,11 @@ pub(super) async fn run_workers(
                             }
                             return Err(DownloadError::Network(format!("HTTP {}", status)));
                         }
-                        if status != reqwest::StatusCode::PARTIAL_CONTENT && !has_content_range {
+                        // Bytes are written at `current_start`, so anything but a 206 for that
+                        // offset (typically a 200 with the whole file) would corrupt the other chunks.
+                        if status != reqwest::StatusCode::PARTIAL_CONTENT
+                            || range_start.is_some_and(|start| start != current_start)
+                        {
                             if !range_diag_logged_clone.swap(true, Ordering::Relaxed) {
                                 let content_range = headers
                                     .get(reqwest::header::CONTENT_RANGE)
@@ -336,6 +764,16 @@ pub(super) async fn run_workers(
                             return Err(DownloadError::NoRangeSupport);
                         }
 
+                        // Offsets refer to the file, not to a compressed stream of it.
+                        if let Some(encoding) = headers
+                            .get(reqwest::header::CONTENT_ENCODING)
+                            .and_then(|v| v.to_str().ok())
+                            .map(str::trim)
+                            .filter(|e| !e.is_empty() && !e.eq_ignore_ascii_case("identity"))
+                        {
+                            return Err(DownloadError::CompressedRange(encoding.to_string()));
+                        }
+
                         {
                             let mut p = progress_clone.lock().unwrap();
                             if p.status_phase.as_deref() != Some("downloading") {
@@ -361,66 +799,83 @@ pub(super) async fn run_workers(
                         let mut stream = response.bytes_stream();
                         let mut local_downloaded = chunk.downloaded;
                         let mut last_db_update = std::time::Instant::now();
+                        let mut throughput = ReadThroughput::new();
 
-                        loop {
-                            let item_opt = match tokio::time::timeout(
-                                std::time::Duration::from_secs(60),
-                                stream.next(),
-                            )
-                            .await
-                            {
-                                Ok(v) => v,
-                                Err(_) => {
-                                    return Err(DownloadError::Network(
-                                        "Connection stalled (no data for 60s)".to_string(),
-                                    ));
-                                }
-                            };
-                            if abort_signal.load(Ordering::Relaxed) {
-                                break;
-                            }
-                            if let Some(sig) = &cancel_signal_clone {
-                                if sig.load(Ordering::Relaxed) {
+                        // Buffered bytes already count towards `chunk.downloaded`, so every exit
+                        // (pause, error or stall) must flush and checkpoint them; otherwise a retry or
+                        // resume would continue past a hole in the file.
+                        let streamed = async {
+                            loop {
+                                let read_timeout = stream_read_timeout(
+                                    stream_read_timeout_secs,
+                                    adaptive_read_timeout,
+                                    throughput.bytes_per_sec(),
+                                );
+                                let item_opt = tokio::select! {
+                                    next = tokio::time::timeout(read_timeout, stream.next()) => match next {
+                                        Ok(v) => v,
+                                        Err(_) => {
+                                            return Err(DownloadError::Network(format!(
+                                                "Connection stalled (no data for {}s)",
+                                                read_timeout.as_secs()
+                                            )));
+                                        }
+                                    },
+                                    Ok(()) = stall_rx.changed() => {
+                                        // Keep what was received; the retry resumes right after it.
+                                        stall_restart = true;
+                                        return Err(DownloadError::Network(
+                                            "Download stalled".to_string(),
+                                        ));
+                                    }
+                                    // Pausing must not wait for the next packet to arrive.
+                                    _ = wait_for_cancel(&cancel_signal_clone) => break,
+                                };
+                                if abort_signal.load(Ordering::Relaxed) {
                                     break;
                                 }
-                            }
+                                if let Some(sig) = &cancel_signal_clone {
+                                    if sig.load(Ordering::Relaxed) {
+                                        break;
+                                    }
+                                }
 
-                            let item = match item_opt {
-                                Some(i) => i,
-                                None => break,
-                            };
+                                let item = match item_opt {
+                                    Some(i) => i,
+                                    None => break,
+                                };
 
-                            let bytes = item.map_err(|e| {
-                                tracing::error!(
-                                    "[{}] Stream error (ContentType: {}) on chunk {}-{}: {}",
-                                    id_clone,
-                                    content_type,
-                                    chunk.start,
-                                    chunk.end,
-                                    e
-                                );
-                                DownloadError::Network(e.to_string())
-                            })?;
-                            chunk_file.write_all(&bytes).await?;
-                            let len = bytes.len() as u64;
+                                let bytes = item.map_err(|e| {
+                                    if is_connection_reset(&e) {
+                                        reset_ptr.store(true, Ordering::Relaxed);
+                                    }
+                                    tracing::error!(
+                                        "[{}] Stream error (ContentType: {}) on chunk {}-{}: {}",
+                                        id_clone,
+                                        content_type,
+                                        chunk.start,
+                                        chunk.end,
+                                        e
+                                    );
+                                    DownloadError::Network(e.to_string())
+                                })?;
+                                chunk_file.write_all(&bytes).await?;
+                                let len = bytes.len() as u64;
+                                throughput.record(len);
 
-                            if let Some(limiter) = &rate_limiter_clone {
-                                limiter.acquire(len, &cancel_signal_clone).await;
-                            }
+                                if let Some(limiter) = &rate_limiter_clone {
+                                    limiter.acquire(len, &cancel_signal_clone).await;
+                                }
+
+                                local_downloaded += len;
+                                chunk.downloaded += len;
+                                downloaded_atomic_clone.fetch_add(len, Ordering::Relaxed);
 
-                            local_downloaded += len;
-                            chunk.downloaded += len;
-                            let current_total_downloaded =
-                                downloaded_atomic_clone.fetch_add(len, Ordering::Relaxed) + len;
-
-                            let now_ms = start_emit_time.elapsed().as_millis() as u64;
-                            let last = last_emit_clone.load(Ordering::Relaxed);
-                            if now_ms - last > 200 {
-                                if last_emit_clone
-                                    .compare_exchange(last, now_ms, Ordering::SeqCst, Ordering::Relaxed)
-                                    .is_ok()
+                                // Every read is reported; the manager coalesces them into batched events.
                                 {
                                     let mut p = progress_clone.lock().unwrap();
+                                    // Read under the lock so reports from concurrent workers never go backwards.
+                                    let current_total_downloaded = downloaded_atomic_clone.load(Ordering::Relaxed);
                                     p.downloaded = current_total_downloaded;
                                     p.connections = *active_ptr.lock().unwrap();
 
@@ -429,52 +884,90 @@ pub(super) async fn run_workers(
                                         let interval_elapsed = ss.last_time.elapsed().as_secs_f64();
                                         if interval_elapsed >= 0.5 {
                                             let diff = current_total_downloaded.saturating_sub(ss.last_bytes);
-                                            p.speed = (diff as f64 / interval_elapsed) as u64;
+                                            ss.averager.apply(&mut p, diff, interval_elapsed);
                                             ss.last_bytes = current_total_downloaded;
                                             ss.last_time = std::time::Instant::now();
-                                            if p.speed > 0 {
-                                                p.eta = p.total.saturating_sub(p.downloaded) / p.speed;
-                                            }
                                         }
                                     }
                                     (on_progress_cb)(p.clone());
                                 }
-                            }
 
-                            if last_db_update.elapsed().as_secs() >= 5 {
-                                if let Some(ref db) = db_path_clone {
-                                    crate::db::update_chunk_progress(
-                                        db,
-                                        &id_clone,
-                                        chunk.start as i64,
-                                        local_downloaded as i64,
-                                    )
-                                    .ok();
+                                if last_db_update.elapsed().as_secs() >= 5 {
+                                    if let Some(ref db) = db_path_clone {
+                                        // Only bytes that left the write buffer may be checkpointed;
+                                        // with `durable_writes` they must have reached the disk too.
+                                        if resume_verify || durable_writes {
+                                            flush_file(&mut chunk_file, durable_writes).await?;
+                                        }
+                                        if resume_verify {
+                                            checkpoint_chunk(db, &id_clone, &filepath_clone, chunk.start, local_downloaded);
+                                        } else {
+                                            chunk_progress_clone
+                                                .lock()
+                                                .unwrap()
+                                                .insert(chunk.start, local_downloaded);
+                                        }
+                                    }
+                                    last_db_update = std::time::Instant::now();
                                 }
-                                last_db_update = std::time::Instant::now();
                             }
+                            Ok::<(), DownloadError>(())
                         }
+                        .await;
 
-                        chunk_file.flush().await?;
+                        flush_file(&mut chunk_file, durable_writes).await?;
+                        // The exact position is written right away; drop the stale batched one.
+                        chunk_progress_clone.lock().unwrap().remove(&chunk.start);
                         if let Some(ref db) = db_path_clone {
-                            crate::db::update_chunk_progress(
-                                db,
-                                &id_clone,
-                                chunk.start as i64,
-                                local_downloaded as i64,
-                            )
-                            .ok();
+                            if resume_verify {
+                                checkpoint_chunk(db, &id_clone, &filepath_clone, chunk.start, local_downloaded);
+                            } else {
+                                crate::db::update_chunk_progress(
+                                    db,
+                                    &id_clone,
+                                    chunk.start as i64,
+                                    local_downloaded as i64,
+                                )
+                                .ok();
+                            }
                         }
-                        Ok::<(), DownloadError>(())
+                        streamed
                     }
                     .await;
 
                     match res {
                         Ok(_) => {
+                            mirrors_clone.record_success(mirror);
+                            final_error = None;
                             break;
                         }
+                        Err(_) if stall_restart => {
+                            // Not a failure: reconnect straight away without using up an attempt.
+                            stall_restart = false;
+                            tracing::info!(
+                                "[{}] Reconnecting stalled chunk {}-{}",
+                                id_clone,
+                                chunk.start,
+                                chunk.end
+                            );
+                            continue;
+                        }
                         Err(e) => {
-                            if matches!(e, DownloadError::NoRangeSupport) {
+                            let range_unusable = matches!(
+                                e,
+                                DownloadError::NoRangeSupport | DownloadError::CompressedRange(_)
+                            );
+                            // A mirror that ignores ranges is dropped; the others carry on.
+                            if range_unusable && mirrors_clone.drop_mirror(mirror) {
+                                tracing::info!(
+                                    "[{}] Mirror {} rejected ranges; dropped from rotation",
+                                    id_clone,
+                                    url_clone
+                                );
+                                mirror = mirrors_clone.pick(Some(mirror));
+                                continue;
+                            }
+                            if range_unusable {
                                 abort_signal.store(true, Ordering::Relaxed);
                                 let mut shared_error = error_ptr.lock().unwrap();
                                 if shared_error.is_none() {
@@ -498,6 +991,15 @@ pub(super) async fn run_workers(
                                 e
                             );
                             failure_counter.fetch_add(1, Ordering::Relaxed);
+                            if mirrors_clone.record_failure(mirror) {
+                                tracing::info!(
+                                    "[{}] Mirror {} failed repeatedly; dropped from rotation",
+                                    id_clone,
+                                    url_clone
+                                );
+                            }
+                            // Retry on an alternate mirror when one is available.
+                            mirror = mirrors_clone.pick(Some(mirror));
                             if let Some(sig) = &cancel_signal_clone {
                                 if sig.load(Ordering::Relaxed) {
                                     break;
@@ -506,7 +1008,9 @@ pub(super) async fn run_workers(
                             final_error = Some(e);
                             attempts += 1;
 
-                            let retry_delay = 1000 * attempts as u64;
+                            let retry_delay = retry_base_ms
+                                .saturating_mul(attempts as u64)
+                                .min(RETRY_BACKOFF_CAP_MS);
                             tracing::info!(
                                 "[{}] Error cooldown: retrying after {}ms...",
                                 id_clone,
@@ -530,6 +1034,36 @@ pub(super) async fn run_workers(
                     }
                 }
 
+                // Hand the unfinished remainder to another worker instead of failing
+                // the download, unless this chunk has already worn out several workers.
+                if retries_exhausted && !abort_signal.load(Ordering::Relaxed) {
+                    chunk.failed_workers += 1;
+                    if chunk.failed_workers < MAX_WORKERS_PER_CHUNK {
+                        tracing::info!(
+                            "[{}] Returning chunk {}-{} to the pool ({}/{} workers failed)",
+                            id_clone,
+                            chunk.start,
+                            chunk.end,
+                            chunk.failed_workers,
+                            MAX_WORKERS_PER_CHUNK
+                        );
+                        pending.lock().unwrap().push(chunk);
+                        final_error = None;
+                    } else {
+                        final_error = Some(DownloadError::RetriesExhausted {
+                            start: chunk.start,
+                            end: chunk.end,
+                            attempts,
+                            last_status: last_status
+                                .map(|s| format!("HTTP {}", s.as_u16()))
+                                .unwrap_or_else(|| "no response".to_string()),
+                            last_error: final_error
+                                .map(|e: DownloadError| e.to_string())
+                                .unwrap_or_default(),
+                        });
+                    }
+                }
+
                 if let Some(e) = final_error {
                     let mut shared_error = error_ptr.lock().unwrap();
                     if shared_error.is_none() {
@@ -539,7 +1073,7 @@ pub(super) async fn run_workers(
 
                 *active_ptr.lock().unwrap() -= 1;
                 let _ = tx.send(()).await;
-            });
+            }.in_current_span());
         }
 
         if last_global_db_update.elapsed().as_secs() >= 1 {
@@ -548,6 +1082,7 @@ pub(super) async fn run_workers(
                 (p.downloaded as i64, p.speed as i64)
             };
             if let Some(ref db) = db_path {
+                flush_chunk_progress(db, &id, &chunk_progress);
                 crate::db::update_download_progress(db, &id, total_downloaded_p, current_speed)
                     .ok();
             }
@@ -564,5 +1099,8 @@ pub(super) async fn run_workers(
         }
     }
 
+    if let Some(ref db) = db_path {
+        flush_chunk_progress(db, &id, &chunk_progress);
+    }
     Ok(WorkerOutcome::Completed)
 }
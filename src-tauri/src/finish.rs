@@ -0,0 +1,223 @@
+//! Finish Action Module
+//!
+//! Runs the `finish_action` setting (`none`, `shutdown`, `sleep`, `hibernate`,
+//! `quit`) once the last download finishes. The legacy `shutdown_on_finish`
+//! toggle still means `shutdown` when no action is chosen.
+//!
+//! Before acting, `finish-action-countdown` is emitted and the action waits
+//! [`COUNTDOWN`]; `cancel_finish_action` aborts it.
+
+use crate::commands::DownloadManager;
+use crate::db::{self, DownloadStatus};
+use crate::torrent::TorrentManager;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager, Runtime};
+
+/// Grace period before the action runs.
+const COUNTDOWN: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum FinishAction {
+    Shutdown,
+    Sleep,
+    Hibernate,
+    Quit,
+}
+
+impl FinishAction {
+    fn from_settings(db_path: &str) -> Option<Self> {
+        let settings = db::get_all_settings(db_path).unwrap_or_default();
+        match settings.get("finish_action").map(|v| v.trim()) {
+            Some("shutdown") => Some(Self::Shutdown),
+            Some("sleep") => Some(Self::Sleep),
+            Some("hibernate") => Some(Self::Hibernate),
+            Some("quit") => Some(Self::Quit),
+            _ if settings.get("shutdown_on_finish").map(|v| v == "true") == Some(true) => {
+                Some(Self::Shutdown)
+            }
+            _ => None,
+        }
+    }
+
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Shutdown => "shutdown",
+            Self::Sleep => "sleep",
+            Self::Hibernate => "hibernate",
+            Self::Quit => "quit",
+        }
+    }
+}
+
+/// Cancellation flag of the countdown in progress, if any.
+#[derive(Default)]
+pub struct FinishCountdown {
+    pending: Mutex<Option<Arc<AtomicBool>>>,
+}
+
+/// True if nothing is downloading or waiting in the queue, ignoring `finished_id`.
+async fn all_finished<R: Runtime>(app: &AppHandle<R>, db_path: &str, finished_id: &str) -> bool {
+    let manager = app.state::<DownloadManager>();
+    let (active_http, _) = manager.get_global_status().await;
+    let active_http = active_http.saturating_sub(usize::from(manager.is_active(finished_id).await));
+    let active_torrents = app.state::<TorrentManager>().running_count().await;
+    if active_http > 0 || active_torrents > 0 {
+        return false;
+    }
+
+    let downloads = db::get_all_downloads(db_path).unwrap_or_default();
+    !downloads.iter().any(|d| {
+        d.id != finished_id
+            && matches!(
+                d.status,
+                DownloadStatus::Downloading | DownloadStatus::Queued
+            )
+    })
+}
+
+/// Starts the finish-action countdown if `finish_action` is set and `finished_id`
+/// was the last active download.
+pub async fn schedule_finish_action<R: Runtime>(
+    app: &AppHandle<R>,
+    db_path: &str,
+    finished_id: &str,
+) {
+    let Some(action) = FinishAction::from_settings(db_path) else {
+        return;
+    };
+    if !all_finished(app, db_path, finished_id).await {
+        return;
+    }
+
+    let cancelled = Arc::new(AtomicBool::new(false));
+    {
+        let countdown = app.state::<FinishCountdown>();
+        let mut pending = countdown.pending.lock().unwrap();
+        if pending.is_some() {
+            return;
+        }
+        *pending = Some(cancelled.clone());
+    }
+
+    tracing::info!(
+        "[Finish] All downloads finished; {} in {}s",
+        action.as_str(),
+        COUNTDOWN.as_secs()
+    );
+    let _ = app.emit(
+        "finish-action-countdown",
+        serde_json::json!({ "action": action.as_str(), "seconds": COUNTDOWN.as_secs() }),
+    );
+
+    let app = app.clone();
+    let db_path = db_path.to_string();
+    let finished_id = finished_id.to_string();
+    tauri::async_runtime::spawn(async move {
+        let mut remaining = COUNTDOWN.as_secs();
+        while remaining > 0 && !cancelled.load(Ordering::Relaxed) {
+            tokio::time::sleep(Duration::from_secs(1)).await;
+            remaining -= 1;
+        }
+        {
+            let countdown = app.state::<FinishCountdown>();
+            let mut pending = countdown.pending.lock().unwrap();
+            if pending.as_ref().is_some_and(|p| Arc::ptr_eq(p, &cancelled)) {
+                *pending = None;
+            }
+        }
+        if cancelled.load(Ordering::Relaxed) {
+            return;
+        }
+        // Something may have started while counting down.
+        if !all_finished(&app, &db_path, &finished_id).await {
+            tracing::info!("[Finish] Downloads resumed during countdown; skipping");
+            let _ = app.emit("finish-action-cancelled", ());
+            return;
+        }
+        let _ = app.emit("finish-action-running", action.as_str());
+        run_action(&app, action);
+    });
+}
+
+/// Runs the OS-level action.
+fn run_action<R: Runtime>(app: &AppHandle<R>, action: FinishAction) {
+    tracing::info!("[Finish] Running {}", action.as_str());
+    if action == FinishAction::Quit {
+        app.exit(0);
+        return;
+    }
+
+    #[cfg(target_os = "windows")]
+    if action == FinishAction::Sleep {
+        if let Err(e) = suspend() {
+            tracing::error!("[Finish] Failed to sleep: {}", e);
+        }
+        return;
+    }
+
+    #[cfg(target_os = "windows")]
+    let (program, args): (&str, &[&str]) = match action {
+        FinishAction::Shutdown => ("shutdown", &["/s", "/t", "0"]),
+        FinishAction::Hibernate => ("shutdown", &["/h"]),
+        FinishAction::Sleep | FinishAction::Quit => return,
+    };
+
+    #[cfg(target_os = "macos")]
+    let (program, args): (&str, &[&str]) = match action {
+        FinishAction::Shutdown => (
+            "osascript",
+            &["-e", "tell application \"System Events\" to shut down"],
+        ),
+        // macOS decides between sleep and hibernation through its `hibernatemode`.
+        FinishAction::Sleep | FinishAction::Hibernate => ("pmset", &["sleepnow"]),
+        FinishAction::Quit => return,
+    };
+
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    let (program, args): (&str, &[&str]) = match action {
+        FinishAction::Shutdown => ("systemctl", &["poweroff"]),
+        FinishAction::Sleep => ("systemctl", &["suspend"]),
+        FinishAction::Hibernate => ("systemctl", &["hibernate"]),
+        FinishAction::Quit => return,
+    };
+
+    if let Err(e) = std::process::Command::new(program).args(args).spawn() {
+        tracing::error!("[Finish] Failed to {}: {}", action.as_str(), e);
+    }
+}
+
+/// Suspends to RAM, returning once the machine wakes up.
+///
+/// `rundll32 powrprof.dll,SetSuspendState 0,1,0` can't be used: rundll32 passes
+/// its own arguments, so the call hibernates whenever hibernation is enabled.
+#[cfg(target_os = "windows")]
+fn suspend() -> std::io::Result<()> {
+    #[link(name = "powrprof")]
+    extern "system" {
+        fn SetSuspendState(hibernate: u8, force: u8, wakeup_events_disabled: u8) -> u8;
+    }
+
+    // SAFETY: the API takes three BOOLEAN flags by value and no pointers.
+    if unsafe { SetSuspendState(0, 0, 0) } == 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+/// Bridge: Aborts a pending finish action.
+#[tauri::command]
+pub fn cancel_finish_action<R: Runtime>(app: AppHandle<R>) -> Result<(), String> {
+    let countdown = app.state::<FinishCountdown>();
+    let cancelled = countdown.pending.lock().unwrap().take();
+    match cancelled {
+        Some(flag) => {
+            flag.store(true, Ordering::Relaxed);
+            tracing::info!("[Finish] Countdown cancelled");
+            let _ = app.emit("finish-action-cancelled", ());
+            Ok(())
+        }
+        None => Err("No finish action is pending".to_string()),
+    }
+}
@@ -0,0 +1,373 @@
+//! FTP Module
+//!
+//! Single-connection downloader for `ftp://` and `ftps://` URLs (explicit TLS
+//! via `AUTH TLS`). Shares the HTTP engine's conventions so the rest of the app
+//! can treat it the same way:
+//! - Data is written to `<filepath>.ciel-part` and renamed on completion.
+//! - Resuming continues from the part file's length using `REST`.
+//! - Progress is reported as [`DownloadProgress`] and throttled by a
+//!   [`SharedRateLimiter`], so global and per-download limits apply.
+
+use crate::downloader::{
+    part_file_path, verify_expected_checksum, DownloadConfig, DownloadError, DownloadProgress,
+    SharedRateLimiter, SpeedAverager,
+};
+use percent_encoding::percent_decode_str;
+use std::io::{Read, Write};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use suppaftp::native_tls::TlsConnector;
+use suppaftp::types::FileType;
+use suppaftp::{FtpError, NativeTlsConnector, NativeTlsFtpStream};
+
+const DEFAULT_FTP_PORT: u16 = 21;
+
+/// Returns true for URLs handled by [`FtpDownloader`] rather than the HTTP engine.
+pub fn is_ftp_url(url: &str) -> bool {
+    let lower = url.trim().to_ascii_lowercase();
+    lower.starts_with("ftp://") || lower.starts_with("ftps://")
+}
+
+fn ftp_error(err: FtpError) -> DownloadError {
+    DownloadError::Network(err.to_string())
+}
+
+/// Connection details extracted from an `ftp://` / `ftps://` URL.
+struct FtpTarget {
+    host: String,
+    port: u16,
+    secure: bool,
+    user: String,
+    password: String,
+    path: String,
+}
+
+impl FtpTarget {
+    /// Explicit `username`/`password` win over credentials embedded in `raw`.
+    fn parse(
+        raw: &str,
+        username: Option<&str>,
+        password: Option<&str>,
+    ) -> Result<Self, DownloadError> {
+        let url = url::Url::parse(raw).map_err(|e| DownloadError::InvalidUrl(e.to_string()))?;
+        let secure = match url.scheme() {
+            "ftp" => false,
+            "ftps" => true,
+            other => {
+                return Err(DownloadError::InvalidUrl(format!(
+                    "Unsupported FTP scheme: {}",
+                    other
+                )))
+            }
+        };
+        let host = url
+            .host_str()
+            .ok_or_else(|| DownloadError::InvalidUrl("Missing host".to_string()))?
+            .to_string();
+        let path = percent_decode_str(url.path())
+            .decode_utf8_lossy()
+            .to_string();
+        if path.is_empty() || path.ends_with('/') {
+            return Err(DownloadError::InvalidUrl(
+                "FTP URL must point to a file".to_string(),
+            ));
+        }
+
+        // RFC 1738: no credentials means anonymous login.
+        let (user, password) = if let Some(user) = username.filter(|u| !u.is_empty()) {
+            (user.to_string(), password.unwrap_or("").to_string())
+        } else if url.username().is_empty() {
+            ("anonymous".to_string(), "anonymous@".to_string())
+        } else {
+            (
+                percent_decode_str(url.username())
+                    .decode_utf8_lossy()
+                    .to_string(),
+                percent_decode_str(url.password().unwrap_or(""))
+                    .decode_utf8_lossy()
+                    .to_string(),
+            )
+        };
+
+        Ok(Self {
+            port: url.port().unwrap_or(DEFAULT_FTP_PORT),
+            host,
+            secure,
+            user,
+            password,
+            path,
+        })
+    }
+}
+
+/// Downloads a single file over FTP/FTPS on one control + one data connection.
+pub struct FtpDownloader {
+    config: DownloadConfig,
+    progress: Arc<std::sync::Mutex<DownloadProgress>>,
+    db_path: Option<String>,
+    cancel_signal: Option<Arc<AtomicBool>>,
+    rate_limiter: Arc<SharedRateLimiter>,
+}
+
+impl FtpDownloader {
+    pub fn new(config: DownloadConfig) -> Result<Self, DownloadError> {
+        FtpTarget::parse(
+            &config.url,
+            config.username.as_deref(),
+            config.password.as_deref(),
+        )?;
+        let progress = DownloadProgress {
+            id: config.id.clone(),
+            total: config.size_hint.unwrap_or(0),
+            downloaded: 0,
+            speed: 0,
+            instant_speed: 0,
+            eta: 0,
+            connections: 1,
+            speed_limit: config.speed_limit,
+            status_text: None,
+            status_phase: None,
+            phase_elapsed_secs: None,
+            filename: None,
+        };
+        Ok(Self {
+            rate_limiter: Arc::new(SharedRateLimiter::new(config.speed_limit)),
+            progress: Arc::new(std::sync::Mutex::new(progress)),
+            config,
+            db_path: None,
+            cancel_signal: None,
+        })
+    }
+
+    pub fn get_progress(&self) -> Arc<std::sync::Mutex<DownloadProgress>> {
+        self.progress.clone()
+    }
+
+    pub fn get_rate_limiter(&self) -> Option<Arc<SharedRateLimiter>> {
+        Some(self.rate_limiter.clone())
+    }
+
+    pub fn with_db(mut self, db_path: String) -> Self {
+        self.db_path = Some(db_path);
+        self
+    }
+
+    pub fn with_cancel_signal(mut self, signal: Arc<AtomicBool>) -> Self {
+        self.cancel_signal = Some(signal);
+        self
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.cancel_signal
+            .as_ref()
+            .map(|s| s.load(Ordering::Relaxed))
+            .unwrap_or(false)
+    }
+
+    /// Runs the transfer on a blocking thread and renames the part file when done.
+    #[tracing::instrument(name = "download", skip_all, fields(download_id = %self.config.id))]
+    pub async fn download<F>(&self, on_progress: F) -> Result<(), DownloadError>
+    where
+        F: Fn(DownloadProgress) + Send + Sync + 'static,
+    {
+        let session = FtpSession {
+            target: FtpTarget::parse(
+                &self.config.url,
+                self.config.username.as_deref(),
+                self.config.password.as_deref(),
+            )?,
+            id: self.config.id.clone(),
+            part_path: part_file_path(&self.config.filepath),
+            read_timeout: Duration::from_secs(self.config.stream_read_timeout_secs.max(1)),
+            progress: self.progress.clone(),
+            db_path: self.db_path.clone(),
+            cancel_signal: self.cancel_signal.clone(),
+            rate_limiter: self.rate_limiter.clone(),
+            runtime: tokio::runtime::Handle::current(),
+        };
+        tokio::task::spawn_blocking(move || session.run(on_progress))
+            .await
+            .map_err(|e| DownloadError::Io(e.to_string()))??;
+
+        if self.is_cancelled() {
+            return Err(DownloadError::Cancelled);
+        }
+        // A resumed transfer only sees its tail, so the whole file is hashed afterwards.
+        verify_expected_checksum(&self.config, part_file_path(&self.config.filepath), None).await?;
+        std::fs::rename(part_file_path(&self.config.filepath), &self.config.filepath)?;
+        Ok(())
+    }
+}
+
+/// Everything the blocking transfer needs, detached from the async caller.
+struct FtpSession {
+    target: FtpTarget,
+    id: String,
+    part_path: PathBuf,
+    read_timeout: Duration,
+    progress: Arc<std::sync::Mutex<DownloadProgress>>,
+    db_path: Option<String>,
+    cancel_signal: Option<Arc<AtomicBool>>,
+    rate_limiter: Arc<SharedRateLimiter>,
+    runtime: tokio::runtime::Handle,
+}
+
+impl FtpSession {
+    fn is_cancelled(&self) -> bool {
+        self.cancel_signal
+            .as_ref()
+            .map(|s| s.load(Ordering::Relaxed))
+            .unwrap_or(false)
+    }
+
+    fn connect(&self) -> Result<NativeTlsFtpStream, DownloadError> {
+        let target = &self.target;
+        let mut ftp =
+            NativeTlsFtpStream::connect((target.host.as_str(), target.port)).map_err(ftp_error)?;
+        ftp.get_ref().set_read_timeout(Some(self.read_timeout))?;
+        if target.secure {
+            let connector =
+                TlsConnector::new().map_err(|e| DownloadError::Network(e.to_string()))?;
+            ftp = ftp
+                .into_secure(NativeTlsConnector::from(connector), &target.host)
+                .map_err(ftp_error)?;
+        }
+        ftp.login(&target.user, &target.password)
+            .map_err(ftp_error)?;
+        ftp.transfer_type(FileType::Binary).map_err(ftp_error)?;
+        Ok(ftp)
+    }
+
+    fn run<F>(self, on_progress: F) -> Result<(), DownloadError>
+    where
+        F: Fn(DownloadProgress),
+    {
+        let mut ftp = self.connect()?;
+
+        // SIZE is optional (RFC 3659); without it progress has no total.
+        let total = ftp.size(&self.target.path).ok().map(|s| s as u64);
+        let mut offset = std::fs::metadata(&self.part_path)
+            .map(|m| m.len())
+            .unwrap_or(0);
+        if total.is_some_and(|t| offset > t) {
+            tracing::warn!(
+                "[{}] Partial file is larger than the remote file. Restarting.",
+                self.id
+            );
+            offset = 0;
+        }
+        if offset > 0 {
+            ftp.resume_transfer(offset as usize).map_err(ftp_error)?;
+            tracing::info!("[{}] Resuming FTP transfer at byte {}", self.id, offset);
+        }
+
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .write(true)
+            .truncate(offset == 0)
+            .open(&self.part_path)?;
+        file.set_len(offset)?;
+        let mut file = std::io::BufWriter::with_capacity(256 * 1024, file);
+        std::io::Seek::seek(&mut file, std::io::SeekFrom::Start(offset))?;
+
+        let mut stream = ftp.retr_as_stream(&self.target.path).map_err(ftp_error)?;
+        stream.get_ref().set_read_timeout(Some(self.read_timeout))?;
+
+        let mut downloaded = offset;
+        let mut buffer = vec![0u8; 64 * 1024];
+        let mut last_emit = Instant::now();
+        let mut last_db_update = Instant::now();
+        let mut last_speed_time = Instant::now();
+        let mut last_speed_bytes = downloaded;
+        let mut averager = SpeedAverager::default();
+        let cancel_signal = self.cancel_signal.clone();
+
+        loop {
+            if self.is_cancelled() {
+                return Err(DownloadError::Cancelled);
+            }
+            let count = match stream.read(&mut buffer) {
+                Ok(0) => break,
+                Ok(count) => count,
+                Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+                Err(e)
+                    if matches!(
+                        e.kind(),
+                        std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut
+                    ) =>
+                {
+                    return Err(DownloadError::Network(format!(
+                        "Connection stalled (no data for {}s)",
+                        self.read_timeout.as_secs()
+                    )));
+                }
+                Err(e) => return Err(e.into()),
+            };
+            file.write_all(&buffer[..count])?;
+            downloaded += count as u64;
+
+            self.runtime
+                .block_on(self.rate_limiter.acquire(count as u64, &cancel_signal));
+
+            if last_emit.elapsed() >= Duration::from_millis(200) {
+                last_emit = Instant::now();
+                let snapshot = {
+                    let mut p = self.progress.lock().unwrap();
+                    p.downloaded = downloaded;
+                    p.total = total.unwrap_or(0);
+                    p.speed_limit = self.rate_limiter.limit();
+                    p.status_text = None;
+                    p.status_phase = None;
+
+                    let interval_elapsed = last_speed_time.elapsed().as_secs_f64();
+                    if interval_elapsed >= 0.3 {
+                        let diff = downloaded.saturating_sub(last_speed_bytes);
+                        averager.apply(&mut p, diff, interval_elapsed);
+                        last_speed_bytes = downloaded;
+                        last_speed_time = Instant::now();
+                    }
+                    p.clone()
+                };
+                (on_progress)(snapshot);
+            }
+
+            if last_db_update.elapsed().as_secs() >= 1 {
+                last_db_update = Instant::now();
+                if let Some(ref db) = self.db_path {
+                    let speed = self.progress.lock().unwrap().speed;
+                    crate::db::update_download_progress(
+                        db,
+                        &self.id,
+                        downloaded as i64,
+                        speed as i64,
+                    )
+                    .ok();
+                }
+            }
+        }
+
+        file.flush()?;
+        ftp.finalize_retr_stream(stream).map_err(ftp_error)?;
+        let _ = ftp.quit();
+
+        if let Some(total) = total {
+            if downloaded < total {
+                return Err(DownloadError::Network(format!(
+                    "Transfer ended early ({} of {} bytes)",
+                    downloaded, total
+                )));
+            }
+        }
+
+        let mut p = self.progress.lock().unwrap();
+        p.downloaded = downloaded;
+        p.total = total.unwrap_or(downloaded);
+        p.speed = 0;
+        p.instant_speed = 0;
+        p.eta = 0;
+        (on_progress)(p.clone());
+        Ok(())
+    }
+}
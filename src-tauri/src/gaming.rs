@@ -0,0 +1,123 @@
+//! Gaming Mode Module
+//!
+//! This module throttles HTTP downloads while a fullscreen application (usually
+//! a game) has focus, and restores the configured speed limit once it exits.
+
+use crate::commands::DownloadManager;
+use crate::db;
+use std::time::Duration;
+use tauri::{AppHandle, Manager, Runtime};
+
+/// Starts a background loop that checks for a fullscreen foreground window every 5 seconds.
+///
+/// While `gaming_mode` is enabled and a fullscreen app is detected, the
+/// `gaming_speed_limit` cap is layered over the global speed limit of every
+/// running download. The cap is lifted as soon as the app leaves fullscreen
+/// or the setting is turned off.
+pub fn start_gaming_monitor<R: Runtime>(app: AppHandle<R>) {
+    tauri::async_runtime::spawn(async move {
+        let mut throttling = false;
+
+        loop {
+            tokio::time::sleep(Duration::from_secs(5)).await;
+
+            let db_state = app.state::<db::DbState>();
+            let manager = app.state::<DownloadManager>();
+            let settings = db::get_all_settings(&db_state.path).unwrap_or_default();
+
+            let enabled = settings
+                .get("gaming_mode")
+                .map(|v| v == "true")
+                .unwrap_or(false);
+            let gaming_limit = settings
+                .get("gaming_speed_limit")
+                .and_then(|v| v.parse::<u64>().ok())
+                .unwrap_or(512 * 1024);
+            let configured_limit = settings
+                .get("speed_limit")
+                .and_then(|v| v.parse::<u64>().ok())
+                .unwrap_or(0);
+
+            let fullscreen = enabled
+                && tokio::task::spawn_blocking(is_fullscreen_app_active)
+                    .await
+                    .unwrap_or(false);
+
+            if fullscreen {
+                if !throttling {
+                    tracing::info!(
+                        "[Gaming] Fullscreen app detected; throttling downloads to {} B/s",
+                        gaming_limit
+                    );
+                    throttling = true;
+                }
+                // Re-applied on every tick so downloads started mid-session and
+                // changes to `gaming_speed_limit` are picked up.
+                manager.set_speed_limit_override(Some(gaming_limit));
+            } else if throttling {
+                tracing::info!("[Gaming] Fullscreen app closed; restoring speed limit");
+                manager.set_speed_limit_override(None);
+                throttling = false;
+            } else {
+                continue;
+            }
+
+            manager
+                .apply_speed_limit(manager.effective_speed_limit(configured_limit))
+                .await;
+        }
+    });
+}
+
+/// Returns true if the focused window covers its whole monitor.
+#[cfg(target_os = "windows")]
+fn is_fullscreen_app_active() -> bool {
+    // QUERY_USER_NOTIFICATION_STATE values reported while a fullscreen app owns the screen.
+    const QUNS_BUSY: i32 = 2;
+    const QUNS_RUNNING_D3D_FULL_SCREEN: i32 = 3;
+    const QUNS_PRESENTATION_MODE: i32 = 4;
+
+    #[link(name = "shell32")]
+    extern "system" {
+        fn SHQueryUserNotificationState(state: *mut i32) -> i32;
+    }
+
+    let mut state = 0;
+    // SAFETY: the API only writes a single i32 through the provided pointer.
+    let hr = unsafe { SHQueryUserNotificationState(&mut state) };
+    hr >= 0
+        && matches!(
+            state,
+            QUNS_BUSY | QUNS_RUNNING_D3D_FULL_SCREEN | QUNS_PRESENTATION_MODE
+        )
+}
+
+/// Returns true if the focused window covers its whole monitor.
+#[cfg(target_os = "linux")]
+fn is_fullscreen_app_active() -> bool {
+    // EWMH: read the active window id, then check its state atoms for fullscreen.
+    let active = match std::process::Command::new("xprop")
+        .args(["-root", "_NET_ACTIVE_WINDOW"])
+        .output()
+    {
+        Ok(output) if output.status.success() => output,
+        _ => return false,
+    };
+    let stdout = String::from_utf8_lossy(&active.stdout);
+    let window_id = match stdout.split_whitespace().last() {
+        Some(id) if id.starts_with("0x") && id != "0x0" => id.to_string(),
+        _ => return false,
+    };
+
+    std::process::Command::new("xprop")
+        .args(["-id", &window_id, "_NET_WM_STATE"])
+        .output()
+        .map(|output| String::from_utf8_lossy(&output.stdout).contains("_NET_WM_STATE_FULLSCREEN"))
+        .unwrap_or(false)
+}
+
+/// Fullscreen detection is not available on this platform.
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
+fn is_fullscreen_app_active() -> bool {
+    false
+}
@@ -0,0 +1,139 @@
+//! Post-Download Hooks Module
+//!
+//! Runs the user's hooks once a download completes:
+//! - `post_download_command`: a shell command, given the download through the
+//!   `CIEL_FILE`, `CIEL_URL`, `CIEL_SIZE` and `CIEL_CATEGORY` environment variables.
+//! - `post_download_webhook`: a URL that receives the download as a JSON `POST`.
+//!
+//! Hooks run in the background with a timeout. Failures are recorded in the
+//! download's history as `hook_failed` and never hold up completion.
+
+use crate::db::{self, Download};
+use std::time::Duration;
+
+/// How long a post-download command may run before it is killed.
+const COMMAND_TIMEOUT: Duration = Duration::from_secs(120);
+/// How long to wait for the webhook to answer.
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(15);
+
+fn hook_failed(db_path: &str, id: &str, details: &str) {
+    tracing::error!("[Hooks] {}: {}", id, details);
+    db::log_event(db_path, id, "hook_failed", Some(details)).ok();
+}
+
+/// Builds a command that runs `command_line` through the platform shell.
+fn shell_command(command_line: &str) -> tokio::process::Command {
+    #[cfg(target_os = "windows")]
+    {
+        let mut command = tokio::process::Command::new("cmd");
+        command.arg("/C").arg(command_line);
+        command
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    {
+        let mut command = tokio::process::Command::new("sh");
+        command.arg("-c").arg(command_line);
+        command
+    }
+}
+
+async fn run_command(db_path: &str, download: &Download, command_line: &str) {
+    let mut command = shell_command(command_line);
+    command
+        .env("CIEL_FILE", &download.filepath)
+        .env("CIEL_URL", &download.url)
+        .env("CIEL_SIZE", download.size.max(0).to_string())
+        .env("CIEL_CATEGORY", &download.category)
+        .stdin(std::process::Stdio::null())
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .kill_on_drop(true);
+
+    let mut child = match command.spawn() {
+        Ok(child) => child,
+        Err(e) => {
+            hook_failed(
+                db_path,
+                &download.id,
+                &format!("Command failed to start: {}", e),
+            );
+            return;
+        }
+    };
+    match tokio::time::timeout(COMMAND_TIMEOUT, child.wait()).await {
+        Ok(Ok(status)) if status.success() => {}
+        Ok(Ok(status)) => hook_failed(
+            db_path,
+            &download.id,
+            &format!("Command exited with {}", status),
+        ),
+        Ok(Err(e)) => hook_failed(db_path, &download.id, &format!("Command failed: {}", e)),
+        Err(_) => hook_failed(
+            db_path,
+            &download.id,
+            &format!("Command timed out after {}s", COMMAND_TIMEOUT.as_secs()),
+        ),
+    }
+}
+
+async fn post_webhook(db_path: &str, download: &Download, url: &str) {
+    let body = serde_json::json!({
+        "event": "download_completed",
+        "id": download.id,
+        "filename": download.filename,
+        "filepath": download.filepath,
+        "url": download.url,
+        "size": download.size.max(0),
+        "category": download.category,
+        "protocol": download.protocol,
+        "completed_at": download.completed_at,
+    });
+    let client = match reqwest::Client::builder().timeout(WEBHOOK_TIMEOUT).build() {
+        Ok(client) => client,
+        Err(e) => {
+            hook_failed(db_path, &download.id, &format!("Webhook failed: {}", e));
+            return;
+        }
+    };
+    match client.post(url).json(&body).send().await {
+        Ok(response) if response.status().is_success() => {}
+        Ok(response) => hook_failed(
+            db_path,
+            &download.id,
+            &format!("Webhook returned {}", response.status()),
+        ),
+        Err(e) => hook_failed(db_path, &download.id, &format!("Webhook failed: {}", e)),
+    }
+}
+
+/// Starts the configured hooks for a completed download without waiting for them.
+pub fn spawn_post_download_hooks(db_path: &str, download: &Download) {
+    let settings = db::get_all_settings(db_path).unwrap_or_default();
+    let setting = |key: &str| {
+        settings
+            .get(key)
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty())
+    };
+    let command_line = setting("post_download_command");
+    let webhook = setting("post_download_webhook");
+    if command_line.is_none() && webhook.is_none() {
+        return;
+    }
+
+    let download = download.clone();
+    if let Some(command_line) = command_line {
+        let db_path = db_path.to_string();
+        let download = download.clone();
+        tauri::async_runtime::spawn(async move {
+            run_command(&db_path, &download, &command_line).await;
+        });
+    }
+    if let Some(webhook) = webhook {
+        let db_path = db_path.to_string();
+        tauri::async_runtime::spawn(async move {
+            post_webhook(&db_path, &download, &webhook).await;
+        });
+    }
+}
@@ -8,17 +8,36 @@
 //! - **Database (`db`)**: SQLite-based persistence for downloads and settings.
 //! - **Commands (`commands`)**: The bridge between the Frontend and Backend logic.
 //! - **Downloader (`downloader`)**: Multi-connection HTTP download engine.
+//! - **FTP (`ftp`)**: Single-connection FTP/FTPS downloads with `REST` resume.
 //! - **Torrent (`torrent`)**: BitTorrent protocol support via `librqbit`.
 //! - **Video (`video`)**: Specialized handling for YouTube and other video platforms.
 //! - **Tray (`tray`) & Clipboard (`clipboard`)**: OS-level integrations for better UX.
+//! - **Gaming Mode (`gaming`)**: Throttles downloads while a fullscreen app is focused.
+//! - **Watch Folder (`watch_folder`)**: Imports `.torrent` files and link lists dropped into a folder.
+//! - **Bandwidth (`bandwidth`)**: Daily usage accounting and the monthly data cap.
+//! - **Hooks (`hooks`)**: Runs a user command or webhook when a download completes.
+//! - **Finish Action (`finish`)**: Shuts down, sleeps or quits after the last download.
+//! - **Shutdown (`shutdown`)**: Persists in-flight progress before the app exits.
+//! - **Secrets (`secrets`)**: Keeps cookies and passwords in the OS keyring.
+//! - **Logging (`logging`)**: Leveled log output to stderr and a rotating file.
 
+mod bandwidth;
 pub mod clipboard;
 pub mod commands;
 pub mod db;
 pub mod downloader;
+mod finish;
+mod ftp;
+mod gaming;
+mod hooks;
+mod logging;
+mod notify;
 mod scheduler;
+mod secrets;
+mod shutdown;
 mod torrent;
 pub mod tray;
+mod watch_folder;
 
 use tauri::Listener;
 use tauri::Manager;
@@ -42,15 +61,6 @@ impl CrashMarkerState {
 /// 4. Registers all IPC commands accessible from the frontend.
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
-    let _ = tracing_subscriber::fmt()
-        .with_env_filter(
-            tracing_subscriber::EnvFilter::from_default_env()
-                .add_directive(tracing::Level::INFO.into()),
-        )
-        .with_target(false)
-        .compact()
-        .try_init();
-
     let context = tauri::generate_context!();
     let app = tauri::Builder::default()
         .plugin(tauri_plugin_shell::init())
@@ -75,6 +85,11 @@ pub fn run() {
             let crash_marker_path = app_data_path.join("unclean_shutdown.flag");
             let had_unclean = crash_marker_path.exists();
             let _ = std::fs::create_dir_all(&app_data_path);
+            let log_level = db::get_setting(&db_path, "log_level")
+                .ok()
+                .flatten()
+                .unwrap_or_default();
+            app.manage(logging::init(&app_data_path.join("logs"), &log_level));
             let _ = std::fs::write(&crash_marker_path, b"1");
             app.manage(CrashMarkerState {
                 path: crash_marker_path,
@@ -89,12 +104,24 @@ pub fn run() {
                 path: db_path.to_string_lossy().to_string(),
             });
             app.manage(commands::DownloadManager::new());
+            app.manage(notify::NotificationBatch::default());
+            app.manage(commands::FolderReveal::default());
+            app.manage(bandwidth::BandwidthMeter::default());
+            app.manage(finish::FinishCountdown::default());
+            app.manage(shutdown::ShutdownState::default());
 
             // Start TorrentManager with "Optimistic" defaults.
             // It will warm up its engine in its own background task.
             let fastresume_enabled = !had_unclean;
-            let torrent_manager =
-                torrent::TorrentManager::new(torrent_session_dir, false, fastresume_enabled);
+            let torrent_limits = torrent::limits_from_settings(&db_path.to_string_lossy());
+            let torrent_network = torrent::NetworkConfig::from_settings(&db_path.to_string_lossy());
+            let torrent_manager = torrent::TorrentManager::new(
+                torrent_session_dir,
+                false,
+                fastresume_enabled,
+                torrent_limits,
+                torrent_network,
+            );
             app.manage(torrent_manager);
 
             // 3. WINDOW DECORATION (Sync - Cheap Win32 calls)
@@ -117,30 +144,35 @@ pub fn run() {
 
                 // Database migrations and Tray/Clipboard/Scheduler
                 let _ = db::init_db(&db_path_clone);
+                secrets::migrate_plaintext(&db_path_clone.to_string_lossy());
                 let _ = tray::create_tray(&handle);
                 clipboard::start_clipboard_monitor(handle.clone());
                 scheduler::start_scheduler(handle.clone());
+                gaming::start_gaming_monitor(handle.clone());
+                watch_folder::start_folder_watcher(handle.clone());
+                bandwidth::start_bandwidth_flusher(handle.clone());
+                commands::http::start_progress_emitter(handle.clone());
+                commands::resume_interrupted(handle.clone()).await;
 
                 // Note: The torrent engine has its own background init in TorrentManager::new
             });
 
             // QUEUE MANAGEMENT
-            // Listen for completion/error events to trigger the queue processor
-            let handle = app.handle().clone();
-            app.listen("download-completed", move |_| {
-                let handle_clone = handle.clone();
-                tauri::async_runtime::spawn(async move {
-                    commands::process_queue(handle_clone).await;
-                });
-            });
-
-            let handle = app.handle().clone();
-            app.listen("download-error", move |_| {
-                let handle_clone = handle.clone();
-                tauri::async_runtime::spawn(async move {
-                    commands::process_queue(handle_clone).await;
+            // Listen for events that free a slot to trigger the queue processor
+            for event in [
+                "download-completed",
+                "download-error",
+                "download-paused",
+                "queue-slot-freed",
+            ] {
+                let handle = app.handle().clone();
+                app.listen(event, move |_| {
+                    let handle_clone = handle.clone();
+                    tauri::async_runtime::spawn(async move {
+                        commands::process_queue(handle_clone).await;
+                    });
                 });
-            });
+            }
 
             Ok(())
         })
@@ -157,23 +189,69 @@ pub fn run() {
             // Registration of all commands exposed via tauri.invoke()
             commands::get_downloads,
             commands::http::add_download,
+            commands::http::add_download_with_headers,
+            commands::checksum::verify_download,
+            commands::chunks::get_chunk_progress,
+            logging::get_log_path,
             commands::torrent::add_torrent,
+            commands::torrent::add_torrent_file,
+            commands::torrent::set_seeding,
+            commands::torrent::set_sequential,
+            commands::torrent::update_torrent_file_selection,
+            commands::torrent::set_torrent_limits,
+            commands::torrent::get_torrent_details,
+            commands::torrent::restart_torrent_session,
             commands::torrent::analyze_torrent,
             commands::http::validate_url_type,
+            commands::http::get_effective_config,
+            commands::http::set_download_speed_limit,
+            commands::http::refresh_download_url,
             commands::torrent::start_selective_torrent,
             commands::pause_download,
             commands::resume_download,
             commands::delete_download,
+            commands::rename_download,
             commands::get_history,
             commands::get_download_events,
             commands::get_settings,
             commands::update_setting,
             commands::show_in_folder,
             commands::clear_finished,
+            commands::cleanup_old_downloads,
+            commands::import::import_url_list,
+            commands::queue::reorder_queue,
+            commands::queue::move_in_queue,
+            commands::queue::get_queue_summary,
+            commands::health::get_health,
+            commands::health::check_dependencies,
+            commands::category::get_default_category_rules,
+            finish::cancel_finish_action,
+            scheduler::pause_downloads_by,
+            scheduler::resume_downloads_by,
+            commands::backup::export_data,
+            commands::backup::import_data,
+            commands::resume_state::export_resume_state,
+            commands::resume_state::import_resume_state,
+            commands::stats::get_statistics,
+            notify::test_notification,
+            bandwidth::get_bandwidth_stats,
             clipboard::get_clipboard,
         ])
         .build(context)
         .expect("error while running tauri application");
 
-    app.run(|_, _| {});
+    app.run(|app_handle, event| {
+        // Explicit exits (tray Quit, finish action) wait for transfers to persist.
+        // Exit requests without a code come from closing windows and are left alone.
+        if let tauri::RunEvent::ExitRequested {
+            code: Some(code),
+            api,
+            ..
+        } = event
+        {
+            if !shutdown::on_exit_requested(app_handle, code) {
+                api.prevent_exit();
+            }
+        }
+    });
 }
@@ -0,0 +1,118 @@
+//! Logging Module
+//!
+//! Sends `tracing` output to stderr and to a daily log file in `<app data>/logs`,
+//! keeping the newest [`MAX_LOG_FILES`] files. The `log_level` setting (`error`,
+//! `warn`, `info`, `debug` or `trace`) applies at startup and whenever it changes.
+
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Manager, Runtime, State};
+use tracing_appender::non_blocking::WorkerGuard;
+use tracing_appender::rolling::{RollingFileAppender, Rotation};
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{reload, EnvFilter, Registry};
+
+/// Daily log files kept before the oldest is deleted.
+const MAX_LOG_FILES: usize = 7;
+/// Level used when `log_level` is missing or invalid.
+const DEFAULT_LOG_LEVEL: &str = "info";
+
+/// Where logs go and how to change their level at runtime.
+pub struct LogState {
+    dir: PathBuf,
+    filter: reload::Handle<EnvFilter, Registry>,
+    /// Flushes the background file writer when the app exits.
+    _guard: Option<WorkerGuard>,
+}
+
+/// Validates a `log_level` value.
+pub fn parse_log_level(value: &str) -> Result<String, String> {
+    match value.trim().to_lowercase().as_str() {
+        l @ ("error" | "warn" | "info" | "debug" | "trace") => Ok(l.to_string()),
+        _ => Err(format!(
+            "Unknown log level '{}': use error, warn, info, debug or trace",
+            value.trim()
+        )),
+    }
+}
+
+fn level_filter(level: &str) -> EnvFilter {
+    let level = parse_log_level(level).unwrap_or_else(|_| DEFAULT_LOG_LEVEL.to_string());
+    // Dependencies stay at `warn` so `debug` shows Ciel's own detail, not theirs.
+    EnvFilter::new(format!("warn,ciel={0},ciel_lib={0}", level))
+}
+
+/// Installs the global subscriber. Without a usable `log_dir`, logs go to stderr only.
+pub fn init(log_dir: &Path, level: &str) -> LogState {
+    let (filter, handle) = reload::Layer::new(level_filter(level));
+    let stderr = tracing_subscriber::fmt::layer()
+        .with_target(false)
+        .compact();
+
+    let appender = RollingFileAppender::builder()
+        .rotation(Rotation::DAILY)
+        .filename_prefix("ciel")
+        .filename_suffix("log")
+        .max_log_files(MAX_LOG_FILES)
+        .build(log_dir);
+    let (file, guard) = match appender {
+        Ok(appender) => {
+            let (writer, guard) = tracing_appender::non_blocking(appender);
+            let layer = tracing_subscriber::fmt::layer()
+                .with_writer(writer)
+                .with_ansi(false)
+                .with_target(false);
+            (Some(layer), Some(guard))
+        }
+        Err(e) => {
+            eprintln!("Cannot write logs to {}: {}", log_dir.display(), e);
+            (None, None)
+        }
+    };
+
+    let _ = tracing_subscriber::registry()
+        .with(filter)
+        .with(stderr)
+        .with(file)
+        .try_init();
+
+    LogState {
+        dir: log_dir.to_path_buf(),
+        filter: handle,
+        _guard: guard,
+    }
+}
+
+/// Applies a new `log_level` value to the running subscriber.
+pub fn set_level<R: Runtime>(app: &AppHandle<R>, level: &str) {
+    if let Some(state) = app.try_state::<LogState>() {
+        if let Err(e) = state.filter.reload(level_filter(level)) {
+            tracing::error!("[Logging] Cannot change level: {}", e);
+        }
+    }
+}
+
+/// Opens the log folder in the platform file manager.
+pub fn open_log_dir<R: Runtime>(app: &AppHandle<R>) {
+    let Some(state) = app.try_state::<LogState>() else {
+        return;
+    };
+    let _ = std::fs::create_dir_all(&state.dir);
+
+    #[cfg(target_os = "windows")]
+    let program = "explorer.exe";
+    #[cfg(target_os = "macos")]
+    let program = "open";
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    let program = "xdg-open";
+
+    if let Err(e) = std::process::Command::new(program).arg(&state.dir).spawn() {
+        tracing::error!("[Logging] Cannot open {}: {}", state.dir.display(), e);
+    }
+}
+
+/// Bridge: Returns the folder holding the log files, for attaching them to bug reports.
+#[tauri::command]
+pub fn get_log_path(state: State<'_, LogState>) -> Result<String, String> {
+    Ok(state.dir.to_string_lossy().to_string())
+}
@@ -0,0 +1,187 @@
+//! Native Notification Module
+//!
+//! Central place for desktop notifications so every engine respects the same
+//! settings:
+//! - `notifications`: master switch.
+//! - `notification_events`: which events notify (`all`, `complete`, `error`, `none`).
+//! - `sound_on_finish`: plays the system sound with completion notifications.
+//!
+//! Completions that land close together are batched into one summary toast.
+
+use crate::db;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Manager, Runtime};
+use tauri_plugin_notification::NotificationExt;
+
+/// How long to wait for further completions before flushing a batch.
+const BATCH_WINDOW: Duration = Duration::from_secs(3);
+
+/// Filenames and sizes of completed downloads waiting to be announced.
+#[derive(Default)]
+pub struct NotificationBatch {
+    pending: Mutex<Vec<(String, u64)>>,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum NotifyEvent {
+    Complete,
+    Error,
+}
+
+fn is_enabled(db_path: &str, event: NotifyEvent) -> bool {
+    let settings = db::get_all_settings(db_path).unwrap_or_default();
+    let master = settings
+        .get("notifications")
+        .map(|v| v == "true")
+        .unwrap_or(true);
+    if !master {
+        return false;
+    }
+    match settings
+        .get("notification_events")
+        .map(|v| v.as_str())
+        .unwrap_or("all")
+    {
+        "none" => false,
+        "complete" => event == NotifyEvent::Complete,
+        "error" => event == NotifyEvent::Error,
+        _ => true,
+    }
+}
+
+/// Formats a byte count for display, e.g. `1.4 GB`.
+fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KB", "MB", "GB", "TB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} B", bytes)
+    } else {
+        format!("{:.1} {}", value, UNITS[unit])
+    }
+}
+
+fn sound_enabled(db_path: &str) -> bool {
+    db::get_setting(db_path, "sound_on_finish")
+        .ok()
+        .flatten()
+        .map(|v| v == "true")
+        .unwrap_or(false)
+}
+
+fn show<R: Runtime>(
+    app: &AppHandle<R>,
+    title: &str,
+    body: &str,
+    with_sound: bool,
+) -> Result<(), String> {
+    let mut builder = app.notification().builder().title(title).body(body);
+    if with_sound {
+        #[cfg(target_os = "windows")]
+        {
+            builder = builder.sound("Default");
+        }
+        #[cfg(not(target_os = "windows"))]
+        {
+            builder = builder.sound("default");
+        }
+    }
+    builder.show().map_err(|e| e.to_string())
+}
+
+fn show_or_log<R: Runtime>(app: &AppHandle<R>, title: &str, body: &str, with_sound: bool) {
+    if let Err(e) = show(app, title, body, with_sound) {
+        tracing::error!("Failed to show notification: {}", e);
+    }
+}
+
+/// Queues a completion notification, merging completions within [`BATCH_WINDOW`].
+///
+/// `size` is the final size in bytes; 0 leaves it out of the message.
+pub fn notify_completed<R: Runtime>(app: &AppHandle<R>, db_path: &str, filename: &str, size: u64) {
+    if !is_enabled(db_path, NotifyEvent::Complete) {
+        return;
+    }
+
+    let batch = app.state::<NotificationBatch>();
+    let first_in_batch = {
+        let mut pending = batch.pending.lock().unwrap();
+        pending.push((filename.to_string(), size));
+        pending.len() == 1
+    };
+    if !first_in_batch {
+        return;
+    }
+
+    let app = app.clone();
+    let db_path = db_path.to_string();
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(BATCH_WINDOW).await;
+        let finished =
+            std::mem::take(&mut *app.state::<NotificationBatch>().pending.lock().unwrap());
+        let with_sound = sound_enabled(&db_path);
+        match finished.as_slice() {
+            [] => {}
+            [(name, 0)] => show_or_log(
+                &app,
+                "Download Completed",
+                &format!("{} has finished downloading successfully.", name),
+                with_sound,
+            ),
+            [(name, size)] => show_or_log(
+                &app,
+                "Download Completed",
+                &format!(
+                    "{} ({}) has finished downloading.",
+                    name,
+                    format_size(*size)
+                ),
+                with_sound,
+            ),
+            many => show_or_log(
+                &app,
+                "Downloads Completed",
+                &format!(
+                    "{} downloads finished ({}).",
+                    many.len(),
+                    format_size(many.iter().map(|(_, size)| size).sum())
+                ),
+                with_sound,
+            ),
+        }
+    });
+}
+
+/// Shows a failure notification immediately.
+pub fn notify_error<R: Runtime>(app: &AppHandle<R>, db_path: &str, filename: &str, message: &str) {
+    if !is_enabled(db_path, NotifyEvent::Error) {
+        return;
+    }
+    show_or_log(
+        app,
+        "Download Failed",
+        &format!("Failed to download {}: {}", filename, message),
+        false,
+    );
+}
+
+/// Bridge: Sends a sample notification so users can verify OS permissions.
+///
+/// Ignores the event filter but honours `sound_on_finish` so the sound can be previewed.
+#[tauri::command]
+pub fn test_notification<R: Runtime>(
+    app: AppHandle<R>,
+    db_state: tauri::State<db::DbState>,
+) -> Result<(), String> {
+    show(
+        &app,
+        "Ciel",
+        "Notifications are working.",
+        sound_enabled(&db_state.path),
+    )
+}
@@ -2,21 +2,154 @@
 //!
 //! This module implements time-based automation, allowing users to schedule
 //! when downloads should start or pause (e.g., to take advantage of off-peak
-//! ISP bandwidth).
+//! ISP bandwidth), and time-windowed speed limits via `speed_schedule`.
+//! It also prunes old completed downloads when `auto_cleanup_days` is set.
 
+use crate::commands::http::combine_speed_limits;
 use crate::commands::{self, DownloadManager};
 use crate::db;
 use crate::torrent::TorrentManager;
 use chrono::{Local, Timelike};
-use std::time::Duration;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
 use tauri::{AppHandle, Manager, Runtime};
 
+/// How often old completed downloads are pruned.
+const CLEANUP_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// Applies `auto_cleanup_days` (0 = off) and `auto_cleanup_remove_files`.
+async fn run_auto_cleanup<R: Runtime>(
+    app: &AppHandle<R>,
+    db_path: &str,
+    settings: &HashMap<String, String>,
+) {
+    let days = settings
+        .get("auto_cleanup_days")
+        .and_then(|v| v.trim().parse::<u32>().ok())
+        .unwrap_or(0);
+    if days == 0 {
+        return;
+    }
+    let remove_files = settings
+        .get("auto_cleanup_remove_files")
+        .map(|v| v == "true")
+        .unwrap_or(false);
+    if let Err(e) = commands::cleanup_completed_older_than(app, db_path, days, remove_files).await {
+        tracing::error!("[Cleanup] Failed: {}", e);
+    }
+}
+
+/// One `speed_schedule` entry: `limit_bytes` (bytes/sec, 0 = unlimited) applies
+/// from `start` until `end` (`HH:MM`). A window ending before it starts wraps
+/// past midnight; equal times cover the whole day.
+#[derive(serde::Deserialize)]
+pub(crate) struct SpeedWindow {
+    start: String,
+    end: String,
+    limit_bytes: u64,
+}
+
+fn minutes_of_day(time: &str) -> Option<u32> {
+    let (hours, minutes) = time.trim().split_once(':')?;
+    let hours = hours.parse::<u32>().ok().filter(|h| *h < 24)?;
+    let minutes = minutes.parse::<u32>().ok().filter(|m| *m < 60)?;
+    Some(hours * 60 + minutes)
+}
+
+/// True if `minute` falls in the daily window from `start` to `end` (`HH:MM`).
+/// A window ending before it starts wraps past midnight; equal times cover the whole day.
+fn in_daily_window(start: &str, end: &str, minute: u32) -> Option<bool> {
+    let (start, end) = (minutes_of_day(start)?, minutes_of_day(end)?);
+    Some(match start.cmp(&end) {
+        std::cmp::Ordering::Less => minute >= start && minute < end,
+        std::cmp::Ordering::Greater => minute >= start || minute < end,
+        std::cmp::Ordering::Equal => true,
+    })
+}
+
+impl SpeedWindow {
+    fn contains(&self, minute: u32) -> bool {
+        in_daily_window(&self.start, &self.end, minute).unwrap_or(false)
+    }
+}
+
+/// Parses and validates the JSON list stored in the `speed_schedule` setting.
+pub(crate) fn parse_speed_schedule(json: &str) -> Result<Vec<SpeedWindow>, String> {
+    let windows: Vec<SpeedWindow> =
+        serde_json::from_str(json).map_err(|e| format!("Invalid speed schedule: {}", e))?;
+    for window in &windows {
+        for time in [&window.start, &window.end] {
+            if minutes_of_day(time).is_none() {
+                return Err(format!("Invalid time in speed schedule: {}", time));
+            }
+        }
+    }
+    Ok(windows)
+}
+
+/// Limit of the windows covering `minute`, the most restrictive one winning.
+/// `None` when no window is active.
+fn scheduled_speed_limit(windows: &[SpeedWindow], minute: u32) -> Option<u64> {
+    windows
+        .iter()
+        .filter(|w| w.contains(minute))
+        .map(|w| w.limit_bytes)
+        .reduce(combine_speed_limits)
+}
+
+/// Pushes the current `speed_schedule` window into running downloads when it changes.
+async fn apply_speed_schedule<R: Runtime>(
+    app: &AppHandle<R>,
+    settings: &HashMap<String, String>,
+    active_limit: &mut Option<u64>,
+) {
+    let now = Local::now();
+    let minute = now.hour() * 60 + now.minute();
+    let limit = match settings.get("speed_schedule").map(|s| s.trim()) {
+        Some(json) if !json.is_empty() => match parse_speed_schedule(json) {
+            Ok(windows) => scheduled_speed_limit(&windows, minute),
+            Err(e) => {
+                tracing::error!("[Scheduler] {}", e);
+                None
+            }
+        },
+        _ => None,
+    };
+    if limit == *active_limit {
+        return;
+    }
+
+    match limit {
+        Some(bytes) => tracing::info!("[Scheduler] Speed window active: {} B/s", bytes),
+        None => tracing::info!("[Scheduler] Speed window ended; using configured limit"),
+    }
+    *active_limit = limit;
+
+    let manager = app.state::<DownloadManager>();
+    manager.set_scheduled_speed_limit(limit);
+    let configured = settings
+        .get("speed_limit")
+        .and_then(|v| v.parse::<u64>().ok())
+        .unwrap_or(0);
+    manager
+        .apply_speed_limit(manager.effective_speed_limit(configured))
+        .await;
+}
+
 /// Starts a background loop that checks the current time every 30 seconds.
 ///
-/// It trigger bulk actions when the system clock matches the user-defined
-/// `start_time` or `pause_time`.
+/// `scheduler_start_time`..`scheduler_pause_time` is a daily window (wrapping past
+/// midnight when pause comes first). Each tick works out whether now is inside it,
+/// and when that changes, or on the first tick after the schedule is enabled or
+/// edited, resumes or pauses everything. Acting on the state rather than on an exact
+/// minute means a late tick, a clock change or starting the app mid-window is not
+/// missed, while downloads the user starts or pauses by hand are left alone.
 pub fn start_scheduler(app: AppHandle) {
     tauri::async_runtime::spawn(async move {
+        let mut active_speed_window = None;
+        let mut last_cleanup: Option<Instant> = None;
+        // Schedule last acted on, and whether now was inside its window.
+        let mut schedule_state: Option<((String, String), bool)> = None;
         loop {
             // Check every 30 seconds to ensure we don't miss the minute transition.
             tokio::time::sleep(Duration::from_secs(30)).await;
@@ -24,12 +157,21 @@ pub fn start_scheduler(app: AppHandle) {
             let db_state = app.state::<db::DbState>();
             let settings = db::get_all_settings(&db_state.path).unwrap_or_default();
 
+            // Speed windows apply whether or not start/pause scheduling is enabled.
+            apply_speed_schedule(&app, &settings, &mut active_speed_window).await;
+
+            if last_cleanup.map_or(true, |at| at.elapsed() >= CLEANUP_INTERVAL) {
+                last_cleanup = Some(Instant::now());
+                run_auto_cleanup(&app, &db_state.path, &settings).await;
+            }
+
             let enabled = settings
                 .get("scheduler_enabled")
                 .map(|v| v == "true")
                 .unwrap_or(false);
 
             if !enabled {
+                schedule_state = None;
                 continue;
             }
 
@@ -43,55 +185,130 @@ pub fn start_scheduler(app: AppHandle) {
                 .unwrap_or_else(|| "08:00".to_string());
 
             let now = Local::now();
-            let current_time_str = format!("{:02}:{:02}", now.hour(), now.minute());
+            let minute = now.hour() * 60 + now.minute();
+            let Some(inside) = in_daily_window(&start_time_str, &pause_time_str, minute) else {
+                tracing::error!(
+                    "[Scheduler] Invalid schedule {}-{}",
+                    start_time_str,
+                    pause_time_str
+                );
+                continue;
+            };
+
+            let schedule = (start_time_str, pause_time_str);
+            let changed = match &schedule_state {
+                Some((last_schedule, was_inside)) => {
+                    *last_schedule != schedule || *was_inside != inside
+                }
+                None => true,
+            };
+            if !changed {
+                continue;
+            }
+            schedule_state = Some((schedule, inside));
 
-            if current_time_str == start_time_str {
+            if inside {
+                tracing::info!("[Scheduler] Inside the download window; resuming");
                 resume_all_downloads(&app).await;
-                // Protection: Sleep for 61 seconds to avoid triggering multiple times
-                // within the same minute.
-                tokio::time::sleep(Duration::from_secs(61)).await;
-            } else if current_time_str == pause_time_str {
+            } else {
+                tracing::info!("[Scheduler] Outside the download window; pausing");
                 pause_all_downloads(&app).await;
-                tokio::time::sleep(Duration::from_secs(61)).await;
             }
         }
     });
 }
 
+/// Selects downloads for [`pause_downloads_by`]/[`resume_downloads_by`].
+///
+/// Every field that is set must match; at least one is required.
+#[derive(Debug, Default, serde::Deserialize)]
+pub struct DownloadFilter {
+    /// Compared with the stored `category` column as-is.
+    pub category: Option<String>,
+    pub protocol: Option<db::DownloadProtocol>,
+    pub ids: Option<Vec<String>>,
+}
+
+impl DownloadFilter {
+    fn is_empty(&self) -> bool {
+        self.category.is_none() && self.protocol.is_none() && self.ids.is_none()
+    }
+
+    fn matches(&self, download: &db::Download) -> bool {
+        self.category
+            .as_ref()
+            .map_or(true, |category| &download.category == category)
+            && self
+                .protocol
+                .as_ref()
+                .map_or(true, |protocol| &download.protocol == protocol)
+            && self
+                .ids
+                .as_ref()
+                .map_or(true, |ids| ids.contains(&download.id))
+    }
+}
+
 /// Helper: Resumes all Paused or Queued downloads in the database.
 pub async fn resume_all_downloads<R: Runtime>(app: &AppHandle<R>) {
+    resume_downloads_matching(app, |_| true).await;
+}
+
+/// Helper: Pauses all currently active transfers.
+pub async fn pause_all_downloads<R: Runtime>(app: &AppHandle<R>) {
+    pause_downloads_matching(app, |_| true).await;
+}
+
+/// Resumes the Paused or Queued downloads accepted by `predicate`; returns how many were resumed.
+async fn resume_downloads_matching<R: Runtime>(
+    app: &AppHandle<R>,
+    predicate: impl Fn(&db::Download) -> bool,
+) -> usize {
     let db_state = app.state::<db::DbState>();
     let manager = app.state::<DownloadManager>();
     let torrent_manager = app.state::<TorrentManager>();
 
+    if crate::bandwidth::cap_reached(app, &db_state.path) {
+        return 0;
+    }
+
+    let mut resumed = 0;
     if let Ok(downloads) = db::get_all_downloads(&db_state.path) {
         for download in downloads {
-            if download.status == db::DownloadStatus::Paused
-                || download.status == db::DownloadStatus::Queued
+            if (download.status == db::DownloadStatus::Paused
+                || download.status == db::DownloadStatus::Queued)
+                && predicate(&download)
             {
-                let _ = commands::resume_download(
+                let result = commands::resume_download(
                     app.clone(),
                     db_state.clone(),
                     manager.clone(),
                     torrent_manager.clone(),
                     download.id,
+                    None,
                 )
                 .await;
+                resumed += usize::from(result.is_ok());
             }
         }
     }
+    resumed
 }
 
-/// Helper: Pauses all currently active transfers.
-pub async fn pause_all_downloads<R: Runtime>(app: &AppHandle<R>) {
+/// Pauses the active transfers accepted by `predicate`; returns how many were paused.
+async fn pause_downloads_matching<R: Runtime>(
+    app: &AppHandle<R>,
+    predicate: impl Fn(&db::Download) -> bool,
+) -> usize {
     let db_state = app.state::<db::DbState>();
     let manager = app.state::<DownloadManager>();
     let torrent_manager = app.state::<TorrentManager>();
 
+    let mut paused = 0;
     if let Ok(downloads) = db::get_all_downloads(&db_state.path) {
         for download in downloads {
-            if download.status == db::DownloadStatus::Downloading {
-                let _ = commands::pause_download(
+            if download.status == db::DownloadStatus::Downloading && predicate(&download) {
+                let result = commands::pause_download(
                     app.clone(),
                     db_state.clone(),
                     manager.clone(),
@@ -99,7 +316,33 @@ pub async fn pause_all_downloads<R: Runtime>(app: &AppHandle<R>) {
                     download.id,
                 )
                 .await;
+                paused += usize::from(result.is_ok());
             }
         }
     }
+    paused
+}
+
+/// Bridge: Pauses the active downloads matching `filter`; returns how many were paused.
+#[tauri::command]
+pub async fn pause_downloads_by<R: Runtime>(
+    app: AppHandle<R>,
+    filter: DownloadFilter,
+) -> Result<usize, String> {
+    if filter.is_empty() {
+        return Err("Filter needs a category, protocol or ID list".to_string());
+    }
+    Ok(pause_downloads_matching(&app, |d| filter.matches(d)).await)
+}
+
+/// Bridge: Resumes the paused or queued downloads matching `filter`; returns how many were resumed.
+#[tauri::command]
+pub async fn resume_downloads_by<R: Runtime>(
+    app: AppHandle<R>,
+    filter: DownloadFilter,
+) -> Result<usize, String> {
+    if filter.is_empty() {
+        return Err("Filter needs a category, protocol or ID list".to_string());
+    }
+    Ok(resume_downloads_matching(&app, |d| filter.matches(d)).await)
 }
@@ -0,0 +1,129 @@
+//! Secrets Module
+//!
+//! Keeps download secrets (cookies and HTTP passwords) in the OS keyring
+//! (Windows Credential Manager, macOS Keychain, Secret Service on Linux) instead
+//! of `ciel.db`. The database column holds only a reference such as
+//! `keyring:<download id>:cookies`; the secret is read back when a transfer needs it.
+//!
+//! When no keyring is available the value is stored in the database as before
+//! and a warning is logged. Plaintext rows are moved over on the next start.
+
+use crate::db::{self, Download};
+
+/// Keyring service all entries are filed under.
+const SERVICE: &str = "ciel";
+/// Prefix of a database value that points into the keyring.
+const REFERENCE_PREFIX: &str = "keyring:";
+
+/// Which secret of a download an entry holds.
+#[derive(Debug, Clone, Copy)]
+pub enum Secret {
+    Cookies,
+    Password,
+}
+
+impl Secret {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Cookies => "cookies",
+            Self::Password => "password",
+        }
+    }
+}
+
+fn is_reference(stored: &str) -> bool {
+    stored.starts_with(REFERENCE_PREFIX)
+}
+
+fn entry(account: &str) -> keyring::Result<keyring::Entry> {
+    keyring::Entry::new(SERVICE, account)
+}
+
+/// Moves `value` into the keyring and returns what the database should store:
+/// the reference, or `value` itself if the keyring is unavailable.
+pub fn protect(download_id: &str, secret: Secret, value: Option<String>) -> Option<String> {
+    let value = value.filter(|v| !v.is_empty())?;
+    if is_reference(&value) {
+        return Some(value);
+    }
+    let account = format!("{}:{}", download_id, secret.as_str());
+    match entry(&account).and_then(|e| e.set_password(&value)) {
+        Ok(()) => Some(format!("{}{}", REFERENCE_PREFIX, account)),
+        Err(e) => {
+            tracing::warn!(
+                "[Secrets] Keyring unavailable ({}); storing {} of {} in the database",
+                e,
+                secret.as_str(),
+                download_id
+            );
+            Some(value)
+        }
+    }
+}
+
+/// Resolves a stored column value to the secret itself.
+///
+/// Plaintext values pass through; a reference whose entry cannot be read yields `None`.
+pub fn reveal(stored: Option<String>) -> Option<String> {
+    let stored = stored?;
+    let Some(account) = stored.strip_prefix(REFERENCE_PREFIX) else {
+        return Some(stored);
+    };
+    match entry(account).and_then(|e| e.get_password()) {
+        Ok(value) => Some(value),
+        Err(e) => {
+            tracing::warn!("[Secrets] Cannot read {} from the keyring: {}", account, e);
+            None
+        }
+    }
+}
+
+/// Removes the keyring entries of a deleted download.
+pub fn forget(download: &Download) {
+    for stored in [&download.cookies, &download.password]
+        .into_iter()
+        .flatten()
+    {
+        if let Some(account) = stored.strip_prefix(REFERENCE_PREFIX) {
+            if let Ok(e) = entry(account) {
+                let _ = e.delete_credential();
+            }
+        }
+    }
+}
+
+/// Moves cookies and passwords still stored in plaintext into the keyring.
+///
+/// Rows whose secret cannot be moved keep it, and are tried again on the next start.
+pub fn migrate_plaintext(db_path: &str) {
+    let downloads = db::get_all_downloads(db_path).unwrap_or_default();
+    let mut moved = 0;
+    for download in &downloads {
+        let stored = [
+            (Secret::Cookies, &download.cookies),
+            (Secret::Password, &download.password),
+        ];
+        for (secret, value) in stored {
+            let Some(value) = value.as_ref().filter(|v| !v.is_empty() && !is_reference(v)) else {
+                continue;
+            };
+            let Some(reference) =
+                protect(&download.id, secret, Some(value.clone())).filter(|s| is_reference(s))
+            else {
+                // Without a keyring every other row would fail with the same warning.
+                return;
+            };
+            let saved = match secret {
+                Secret::Cookies => db::update_download_cookies(db_path, &download.id, &reference),
+                Secret::Password => db::update_download_password(db_path, &download.id, &reference),
+            };
+            moved += usize::from(saved.is_ok());
+        }
+    }
+    if moved > 0 {
+        tracing::info!(
+            "[Secrets] Moved {} stored secret(s) into the OS keyring",
+            moved
+        );
+    }
+}
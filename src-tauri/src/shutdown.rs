@@ -0,0 +1,102 @@
+//! Shutdown Module
+//!
+//! Stops transfers cleanly before Ciel exits, so the last seconds of progress
+//! survive: HTTP/FTP workers checkpoint their chunks and torrents write their
+//! progress before the `librqbit` session persists its state. Each step is
+//! bounded so a stuck download cannot keep the app from quitting.
+//!
+//! Downloads that were running stay `downloading` in the database, so
+//! `resume_interrupted` picks them up on the next start.
+
+use crate::commands::DownloadManager;
+use crate::db::{self, DbState, DownloadStatus};
+use crate::torrent::TorrentManager;
+use crate::CrashMarkerState;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager, Runtime};
+
+/// Longest wait for HTTP/FTP workers to checkpoint their chunks.
+const TRANSFER_FLUSH_TIMEOUT: Duration = Duration::from_secs(5);
+/// Longest wait for the torrent session to stop.
+const TORRENT_STOP_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Tracks whether the graceful shutdown has started and finished.
+#[derive(Default)]
+pub struct ShutdownState {
+    started: AtomicBool,
+    finished: AtomicBool,
+}
+
+impl ShutdownState {
+    /// True once the app is on its way out; no new downloads should start.
+    pub fn is_started(&self) -> bool {
+        self.started.load(Ordering::Relaxed)
+    }
+}
+
+/// Handles an exit request: the first one is held back until transfers are
+/// persisted, then the exit is requested again with the same `code`.
+///
+/// Returns true if the caller should let the exit proceed.
+pub fn on_exit_requested<R: Runtime>(app: &AppHandle<R>, code: i32) -> bool {
+    let state = app.state::<ShutdownState>();
+    if state.finished.load(Ordering::Relaxed) {
+        return true;
+    }
+    if !state.started.swap(true, Ordering::Relaxed) {
+        let app = app.clone();
+        tauri::async_runtime::spawn(async move {
+            persist_transfers(&app).await;
+            app.state::<ShutdownState>()
+                .finished
+                .store(true, Ordering::Relaxed);
+            app.exit(code);
+        });
+    }
+    false
+}
+
+/// Stops every transfer, waiting a bounded time for each engine to persist its state.
+async fn persist_transfers<R: Runtime>(app: &AppHandle<R>) {
+    let db_path = app.state::<DbState>().path.clone();
+    let manager = app.state::<DownloadManager>();
+
+    // Pausing writes the final chunk checkpoints; afterwards the downloads are
+    // put back to `downloading` so they count as interrupted, not paused.
+    let running = manager.active_ids().await;
+    for id in &running {
+        manager.cancel(id).await;
+    }
+    let deadline = Instant::now() + TRANSFER_FLUSH_TIMEOUT;
+    while manager.running_count() > 0 && Instant::now() < deadline {
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    if manager.running_count() > 0 {
+        tracing::warn!(
+            "[Shutdown] {} transfer(s) did not stop within {}s",
+            manager.running_count(),
+            TRANSFER_FLUSH_TIMEOUT.as_secs()
+        );
+    }
+    for id in &running {
+        let _ = db::update_download_status(&db_path, id, DownloadStatus::Downloading);
+    }
+
+    let torrent_manager = app.state::<TorrentManager>();
+    if tokio::time::timeout(TORRENT_STOP_TIMEOUT, torrent_manager.shutdown(&db_path))
+        .await
+        .is_err()
+    {
+        tracing::warn!(
+            "[Shutdown] Torrent session did not stop within {}s",
+            TORRENT_STOP_TIMEOUT.as_secs()
+        );
+    }
+
+    app.state::<CrashMarkerState>().clear();
+    tracing::info!(
+        "[Shutdown] Persisted {} HTTP/FTP transfer(s); exiting",
+        running.len()
+    );
+}
@@ -0,0 +1,86 @@
+use super::types::{TorrentDetails, TorrentPeer};
+use super::TorrentManager;
+use std::collections::HashMap;
+use std::time::Instant;
+
+/// When a torrent's peers were last sampled and the bytes received from each.
+pub(super) type PeerSample = (Instant, HashMap<String, u64>);
+
+impl TorrentManager {
+    /// Collects trackers and peers of a loaded torrent for the details panel.
+    ///
+    /// librqbit only keeps cumulative per-peer counters, so per-peer speeds are
+    /// measured against the sample taken by the previous call.
+    pub async fn get_details(&self, id: &str) -> Result<TorrentDetails, String> {
+        let handle = self
+            .active_torrents
+            .lock()
+            .await
+            .get(id)
+            .cloned()
+            .ok_or("Torrent is not loaded")?;
+
+        let mut trackers: Vec<String> = handle
+            .shared()
+            .trackers
+            .iter()
+            .map(|url| url.to_string())
+            .collect();
+        trackers.sort();
+
+        let stats = handle.stats();
+        let upload_speed = stats
+            .live
+            .as_ref()
+            .map(|l| (l.upload_speed.mbps.max(0.0) * 1024.0 * 1024.0) as u64)
+            .unwrap_or(0);
+
+        let mut samples = self.peer_samples.lock().await;
+        let previous = samples.remove(id);
+        let peers = match handle.live() {
+            Some(live) => {
+                let now = Instant::now();
+                let snapshot = live.per_peer_stats_snapshot(Default::default());
+                let current: HashMap<String, u64> = snapshot
+                    .peers
+                    .iter()
+                    .map(|(address, peer)| (address.clone(), peer.counters.fetched_bytes))
+                    .collect();
+
+                let mut peers: Vec<TorrentPeer> = current
+                    .iter()
+                    .map(|(address, &downloaded)| {
+                        let download_speed = previous
+                            .as_ref()
+                            .and_then(|(sampled_at, bytes)| {
+                                let elapsed = now.duration_since(*sampled_at).as_secs_f64();
+                                let before = *bytes.get(address)?;
+                                (elapsed > 0.0).then(|| {
+                                    (downloaded.saturating_sub(before) as f64 / elapsed) as u64
+                                })
+                            })
+                            .unwrap_or(0);
+                        TorrentPeer {
+                            address: address.clone(),
+                            downloaded,
+                            download_speed,
+                        }
+                    })
+                    .collect();
+                peers.sort_by(|a, b| b.download_speed.cmp(&a.download_speed));
+
+                samples.insert(id.to_string(), (now, current));
+                peers
+            }
+            None => Vec::new(),
+        };
+
+        Ok(TorrentDetails {
+            info_hash: hex::encode(handle.info_hash().0),
+            trackers,
+            peers,
+            uploaded: stats.uploaded_bytes,
+            upload_speed,
+        })
+    }
+}
@@ -0,0 +1,49 @@
+//! Session-wide torrent rate limits (`torrent_upload_limit` / `torrent_download_limit`).
+//!
+//! librqbit fixes a torrent's own limits when it starts, so only the session
+//! limiter can be changed while torrents are running.
+
+use super::TorrentManager;
+use librqbit::limits::LimitsConfig;
+use std::num::NonZeroU32;
+
+/// Bytes/sec as a librqbit limit; 0 means unlimited.
+fn bps(limit: u64) -> Option<NonZeroU32> {
+    NonZeroU32::new(limit.min(u32::MAX as u64) as u32)
+}
+
+/// Builds the session limits from upload/download caps in bytes/sec (0 = unlimited).
+fn limits_config(upload: u64, download: u64) -> LimitsConfig {
+    LimitsConfig {
+        upload_bps: bps(upload),
+        download_bps: bps(download),
+    }
+}
+
+/// Reads the torrent limits from settings; missing or invalid values mean unlimited.
+pub fn limits_from_settings(db_path: &str) -> LimitsConfig {
+    let setting = |key: &str| {
+        crate::db::get_setting(db_path, key)
+            .ok()
+            .flatten()
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(0)
+    };
+    limits_config(
+        setting("torrent_upload_limit"),
+        setting("torrent_download_limit"),
+    )
+}
+
+impl TorrentManager {
+    /// Applies new upload/download caps (bytes/sec, 0 = unlimited) to the running session.
+    pub async fn set_rate_limits(&self, upload: u64, download: u64) -> Result<(), String> {
+        let session_guard = self.session.lock().await;
+        let session = session_guard
+            .as_ref()
+            .ok_or("Torrent session is not yet initialized")?;
+        session.ratelimits.set_upload_bps(bps(upload));
+        session.ratelimits.set_download_bps(bps(download));
+        Ok(())
+    }
+}
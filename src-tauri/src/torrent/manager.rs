@@ -1,7 +1,9 @@
+use super::details::PeerSample;
+use super::network::NetworkConfig;
 use crate::torrent::types::{TorrentFile, TorrentInfo};
 use librqbit::{ManagedTorrent, Session};
 use std::collections::{HashMap, HashSet};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use tokio::sync::Mutex;
 
@@ -13,12 +15,22 @@ use tokio::sync::Mutex;
 pub struct TorrentManager {
     /// The underlying BitTorrent session (initialized in background).
     pub(super) session: Arc<Mutex<Option<Arc<Session>>>>,
+    /// Where the session persists its state; kept so the session can be rebuilt.
+    pub(super) session_dir: PathBuf,
     /// Tracks handles for active torrents, indexed by Ciel's internal UUID.
     pub(super) active_torrents: Arc<Mutex<HashMap<String, Arc<ManagedTorrent>>>>,
     /// Short-lived cache of analyzed torrent bytes keyed by analysis token.
     pub(super) analyzed_torrents: Arc<Mutex<HashMap<String, Vec<u8>>>>,
     /// Memory cache for paused states to avoid constant DB polling.
     pub(super) paused_downloads: Arc<Mutex<HashSet<String>>>,
+    /// Completed torrents that are still uploading; they no longer hold a queue slot.
+    pub(super) seeding_torrents: Arc<Mutex<HashSet<String>>>,
+    /// Per-torrent `set_seeding` choices overriding the `seed_enabled` setting.
+    pub(super) seeding_overrides: Arc<Mutex<HashMap<String, bool>>>,
+    /// Torrents switched to in-order piece picking via `set_sequential`.
+    pub(super) sequential_torrents: Arc<Mutex<HashSet<String>>>,
+    /// Last per-peer byte counters seen by `get_details`, used to derive peer speeds.
+    pub(super) peer_samples: Arc<Mutex<HashMap<String, PeerSample>>>,
 }
 
 #[derive(Clone, Debug)]
@@ -71,9 +83,11 @@ impl TorrentManager {
 
     /// Creates a new `TorrentManager` and spawns a background task to initialize the `librqbit` session.
     pub fn new(
-        session_dir: std::path::PathBuf,
+        session_dir: PathBuf,
         _force_encryption: bool,
         fastresume_enabled: bool,
+        ratelimits: librqbit::limits::LimitsConfig,
+        network: NetworkConfig,
     ) -> Self {
         let session = Arc::new(Mutex::new(None));
         let session_clone = session.clone();
@@ -81,24 +95,9 @@ impl TorrentManager {
 
         // Spawn background initialization to prevent UI freeze during startup
         tauri::async_runtime::spawn(async move {
-            // Ensure directory exists in background
-            if !session_dir_clone.exists() {
-                let _ = std::fs::create_dir_all(&session_dir_clone);
-            }
-
-            let options = librqbit::SessionOptions {
-                disable_dht: false,
-                disable_dht_persistence: false,
-                // Persist session and bitfield state to enable fast resume across restarts.
-                // Without fastresume, restored torrents can still trigger long local verification.
-                fastresume: fastresume_enabled,
-                persistence: Some(librqbit::SessionPersistenceConfig::Json {
-                    folder: Some(session_dir_clone.clone()),
-                }),
-                ..Default::default()
-            };
-
-            match Session::new_with_opts(session_dir_clone, options).await {
+            match Self::start_session(session_dir_clone, fastresume_enabled, ratelimits, network)
+                .await
+            {
                 Ok(s) => {
                     let mut sess = session_clone.lock().await;
                     *sess = Some(s);
@@ -115,19 +114,96 @@ impl TorrentManager {
 
         Self {
             session,
+            session_dir,
             active_torrents: Arc::new(Mutex::new(HashMap::new())),
             analyzed_torrents: Arc::new(Mutex::new(HashMap::new())),
             paused_downloads: Arc::new(Mutex::new(HashSet::new())),
+            seeding_torrents: Arc::new(Mutex::new(HashSet::new())),
+            seeding_overrides: Arc::new(Mutex::new(HashMap::new())),
+            sequential_torrents: Arc::new(Mutex::new(HashSet::new())),
+            peer_samples: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Builds a `librqbit` session persisting its state in `session_dir`.
+    pub(super) async fn start_session(
+        session_dir: PathBuf,
+        fastresume_enabled: bool,
+        ratelimits: librqbit::limits::LimitsConfig,
+        network: NetworkConfig,
+    ) -> Result<Arc<Session>, String> {
+        // Ensure directory exists in background
+        if !session_dir.exists() {
+            let _ = std::fs::create_dir_all(&session_dir);
+        }
+        if !network.enable_pex {
+            tracing::warn!("[Torrent] PEX cannot be disabled in this engine version; ignoring.");
         }
+
+        let options = librqbit::SessionOptions {
+            disable_dht: !network.enable_dht,
+            disable_dht_persistence: false,
+            // Persist session and bitfield state to enable fast resume across restarts.
+            // Without fastresume, restored torrents can still trigger long local verification.
+            fastresume: fastresume_enabled,
+            persistence: Some(librqbit::SessionPersistenceConfig::Json {
+                folder: Some(session_dir.clone()),
+            }),
+            listen_port_range: Some(network.listen_port_range()),
+            ratelimits,
+            ..Default::default()
+        };
+
+        Session::new_with_opts(session_dir, options)
+            .await
+            .map_err(|e| e.to_string())
     }
 
-    /// Calculates aggregate torrent statistics for the system tray (count only for now).
+    /// Calculates aggregate torrent statistics for the system tray.
+    ///
+    /// Returns the number of downloading torrents and their combined download
+    /// speed in bytes/sec, as reported by librqbit's live stats.
     pub async fn get_global_status(&self) -> (usize, u64) {
+        let handles = {
+            let active = self.active_torrents.lock().await;
+            let paused = self.paused_downloads.lock().await;
+            let seeding = self.seeding_torrents.lock().await;
+            active
+                .iter()
+                .filter(|(id, _)| !paused.contains(*id) && !seeding.contains(*id))
+                .map(|(_, handle)| handle.clone())
+                .collect::<Vec<_>>()
+        };
+
+        let total_speed = handles
+            .iter()
+            .filter_map(|handle| {
+                let stats = handle.stats();
+                let live = stats.live.as_ref()?;
+                Some((live.download_speed.mbps.max(0.0) * 1024.0 * 1024.0) as u64)
+            })
+            .sum();
+
+        (handles.len(), total_speed)
+    }
+
+    /// Number of torrents that are downloading, i.e. registered, not paused and not seeding.
+    pub async fn running_count(&self) -> usize {
         let active = self.active_torrents.lock().await;
-        let count = active.len();
-        // Speed calculation for torrents is complex to aggregate here without a cache.
-        // We'll return 0 for now to fix the build, and I'll add real tracking in a follow-up.
-        (count, 0)
+        let paused = self.paused_downloads.lock().await;
+        let seeding = self.seeding_torrents.lock().await;
+        active
+            .keys()
+            .filter(|id| !paused.contains(*id) && !seeding.contains(*id))
+            .count()
+    }
+
+    /// Reports whether the session finished initializing and which TCP port it listens on.
+    pub async fn session_status(&self) -> (bool, Option<u16>) {
+        match self.session.lock().await.as_ref() {
+            Some(session) => (true, session.tcp_listen_port()),
+            None => (false, None),
+        }
     }
 
     pub async fn get_stats_snapshot(&self, id: &str) -> Option<TorrentStatsSnapshot> {
@@ -269,7 +345,9 @@ impl TorrentManager {
     }
 
     /// Internal: Transitions a selectively-configured torrent from Paused to active.
-    pub async fn start_selective(&self, id: &str, _indices: Vec<usize>) -> Result<(), String> {
+    pub async fn start_selective(&self, id: &str, indices: Vec<usize>) -> Result<u64, String> {
+        let total_bytes = self.update_file_selection(id, &indices).await?;
+
         let session_guard = self.session.lock().await;
         let session = session_guard
             .as_ref()
@@ -279,7 +357,33 @@ impl TorrentManager {
         if let Some(handle) = active.get(id) {
             session.unpause(handle).await.map_err(|e| e.to_string())?;
         }
-        Ok(())
+        Ok(total_bytes)
+    }
+
+    /// Restricts a loaded torrent to the files at `indices` (librqbit `only_files`).
+    ///
+    /// Deselected files stop downloading and drop out of the progress total.
+    /// Returns the new total size in bytes (0 while metadata is still unknown).
+    pub async fn update_file_selection(&self, id: &str, indices: &[usize]) -> Result<u64, String> {
+        if indices.is_empty() {
+            return Err("Select at least one file".to_string());
+        }
+        let session = self
+            .session
+            .lock()
+            .await
+            .as_ref()
+            .ok_or("Torrent session is not yet initialized")?
+            .clone();
+
+        let active = self.active_torrents.lock().await;
+        let handle = active.get(id).ok_or("Torrent is not loaded")?;
+        let selected: HashSet<usize> = indices.iter().copied().collect();
+        session
+            .update_only_files(handle, &selected)
+            .await
+            .map_err(|e| e.to_string())?;
+        Ok(handle.stats().total_bytes)
     }
 
     /// Pauses an active torrent in the `librqbit` session.
@@ -358,6 +462,7 @@ impl TorrentManager {
             let mut active = self.active_torrents.lock().await;
             active.remove(id)
         };
+        self.sequential_torrents.lock().await.remove(id);
 
         if let Some(handle) = handle_opt {
             let info_hash = handle.info_hash();
@@ -403,6 +508,27 @@ impl TorrentManager {
         Ok(())
     }
 
+    /// Writes each torrent's progress and stops the session so it persists its state.
+    ///
+    /// Used when Ciel exits; torrents keep their DB status and are restored on the
+    /// next start.
+    pub async fn shutdown(&self, db_path: &str) {
+        let old_session = self.session.lock().await.take();
+        let handles = std::mem::take(&mut *self.active_torrents.lock().await);
+        let seeding = self.seeding_torrents.lock().await.clone();
+        for (id, handle) in handles.iter().filter(|(id, _)| !seeding.contains(*id)) {
+            let progress = handle.stats().progress_bytes;
+            if let Err(e) = crate::db::update_download_progress(db_path, id, progress as i64, 0) {
+                tracing::error!("[Torrent][{}] Failed to save progress on exit: {}", id, e);
+            }
+        }
+
+        if let Some(session) = old_session {
+            session.stop().await;
+            tracing::info!("[Torrent] Session stopped for exit.");
+        }
+    }
+
     /// Checks if a torrent with the given ID is currently active in the manager.
     pub async fn is_active(&self, id: &str) -> bool {
         self.active_torrents.lock().await.contains_key(id)
@@ -1,10 +1,18 @@
+mod details;
 mod files;
+mod limits;
 mod manager;
+mod network;
 mod phases;
 mod progress;
+mod seeding;
+mod sequential;
 mod telemetry;
 mod types;
 
+pub use limits::limits_from_settings;
 pub use manager::TorrentManager;
+pub(crate) use network::parse_listen_port;
+pub use network::NetworkConfig;
 #[allow(unused_imports)]
-pub use types::{TorrentFile, TorrentInfo};
+pub use types::{TorrentDetails, TorrentFile, TorrentInfo, TorrentPeer};
@@ -0,0 +1,108 @@
+//! Torrent network settings (`torrent_listen_port`, `torrent_enable_dht`, `torrent_enable_pex`).
+//!
+//! librqbit reads these only when a session is created, so changing them
+//! takes a [`TorrentManager::restart_session`].
+
+use super::TorrentManager;
+
+/// Network options applied when the torrent session is built.
+#[derive(Clone, Debug)]
+pub struct NetworkConfig {
+    /// TCP port for incoming peers; 0 lets the OS pick a random free port.
+    pub listen_port: u16,
+    pub enable_dht: bool,
+    /// librqbit 8 always exchanges peers (PEX) on public torrents, so this is
+    /// recorded but cannot switch it off.
+    pub enable_pex: bool,
+}
+
+impl Default for NetworkConfig {
+    fn default() -> Self {
+        Self {
+            listen_port: 0,
+            enable_dht: true,
+            enable_pex: true,
+        }
+    }
+}
+
+/// Checks a `torrent_listen_port` value: 0 (random) or a port below 65535.
+pub(crate) fn parse_listen_port(value: &str) -> Result<u16, String> {
+    value
+        .trim()
+        .parse::<u16>()
+        .ok()
+        .filter(|port| *port < u16::MAX)
+        .ok_or_else(|| format!("Invalid listen port: {}", value))
+}
+
+impl NetworkConfig {
+    /// Reads the network settings; missing or invalid values fall back to the defaults.
+    pub fn from_settings(db_path: &str) -> Self {
+        let setting = |key: &str| crate::db::get_setting(db_path, key).ok().flatten();
+        let defaults = Self::default();
+        Self {
+            listen_port: setting("torrent_listen_port")
+                .and_then(|v| parse_listen_port(&v).ok())
+                .unwrap_or(defaults.listen_port),
+            enable_dht: setting("torrent_enable_dht")
+                .map(|v| v == "true")
+                .unwrap_or(defaults.enable_dht),
+            enable_pex: setting("torrent_enable_pex")
+                .map(|v| v == "true")
+                .unwrap_or(defaults.enable_pex),
+        }
+    }
+
+    /// The single-port range librqbit listens on (binding port 0 picks a random one).
+    pub(super) fn listen_port_range(&self) -> std::ops::Range<u16> {
+        self.listen_port..self.listen_port + 1
+    }
+}
+
+impl TorrentManager {
+    /// Stops the session and starts a new one with the current settings.
+    ///
+    /// Returns the IDs of torrents that were downloading; the caller re-adds them.
+    /// Paused torrents rejoin the session when resumed, and seeding stops.
+    pub async fn restart_session(&self, db_path: &str) -> Result<Vec<String>, String> {
+        // A session that failed to start (e.g. port in use) can be rebuilt too.
+        let old_session = self.session.lock().await.take();
+
+        let downloading = {
+            let mut active = self.active_torrents.lock().await;
+            let paused = self.paused_downloads.lock().await;
+            let seeding = self.seeding_torrents.lock().await;
+            let ids = active
+                .keys()
+                .filter(|id| !paused.contains(*id) && !seeding.contains(*id))
+                .cloned()
+                .collect::<Vec<_>>();
+            // Monitor loops exit once their torrent leaves the active map.
+            active.clear();
+            ids
+        };
+        self.paused_downloads.lock().await.clear();
+        self.seeding_torrents.lock().await.clear();
+        self.peer_samples.lock().await.clear();
+
+        if let Some(old_session) = old_session {
+            old_session.stop().await;
+            tracing::info!("[Torrent] Session stopped for restart.");
+        }
+
+        let session = Self::start_session(
+            self.session_dir.clone(),
+            true,
+            super::limits_from_settings(db_path),
+            NetworkConfig::from_settings(db_path),
+        )
+        .await?;
+        *self.session.lock().await = Some(session);
+        tracing::info!(
+            "[Torrent] Session restarted; re-adding {} torrent(s).",
+            downloading.len()
+        );
+        Ok(downloading)
+    }
+}
@@ -11,6 +11,7 @@ pub(super) struct PhaseInput {
     pub(super) total_bytes: u64,
     pub(super) progress_bytes: u64,
     pub(super) has_live: bool,
+    pub(super) is_checking: bool,
     pub(super) connections: u64,
     pub(super) is_cached_paused: bool,
     pub(super) is_resume: bool,
@@ -58,6 +59,7 @@ impl PhaseState {
             total_bytes,
             progress_bytes,
             has_live,
+            is_checking,
             connections,
             is_cached_paused,
             is_resume,
@@ -74,6 +76,14 @@ impl PhaseState {
                 Some(format!("Fetching Metadata... ({} peers)", connections)),
                 "fetching_metadata",
             )
+        } else if is_checking {
+            self.was_live = false;
+            reset_speed_baseline = true;
+            let pct = (progress_bytes as f64 / total_bytes as f64) * 100.0;
+            (
+                Some(format!("Checking... {:.1}%", pct.min(100.0))),
+                "checking",
+            )
         } else if is_cached_paused {
             self.paused_counter = 50;
             self.was_live = false;
@@ -1,10 +1,14 @@
 use super::files;
 use super::phases::{PhaseInput, PhaseState};
+use super::seeding::{share_ratio, SeedPolicy};
+use super::sequential;
 use super::telemetry;
 use super::TorrentManager;
+use crate::bandwidth::BandwidthMeter;
+use librqbit::TorrentStatsState;
 use std::collections::HashSet;
 use std::path::Path;
-use tauri::{AppHandle, Emitter, Runtime};
+use tauri::{AppHandle, Emitter, Manager, Runtime};
 
 impl TorrentManager {
     /// Adds a new magnet link or torrent file to the active session.
@@ -35,6 +39,7 @@ impl TorrentManager {
         } else {
             Some(initial_peers.clone())
         };
+        let sequential = self.is_sequential(&id).await;
         if let Some(peers) = initial_peers_opt.as_ref() {
             tracing::info!(
                 "[Torrent] {}: seeding {} initial peer(s) from magnet x.pe",
@@ -54,12 +59,17 @@ impl TorrentManager {
             None
         };
 
+        // Files already on disk are opened rather than rejected, so the engine
+        // hash-checks them during initialization and only fetches missing pieces.
+        // Adding paused still runs that check before the torrent stops.
         let response = match source_torrent_bytes.or(local_torrent_bytes) {
             Some(torrent_bytes) => {
                 let options = librqbit::AddTorrentOptions {
                     only_files: indices.clone(),
                     output_folder: Some(output_folder.clone()),
-                    overwrite: is_resume,
+                    paused: start_paused,
+                    sequential,
+                    overwrite: true,
                     initial_peers: initial_peers_opt.clone(),
                     ..Default::default()
                 };
@@ -74,7 +84,9 @@ impl TorrentManager {
                 let options = librqbit::AddTorrentOptions {
                     only_files: indices.clone(),
                     output_folder: Some(output_folder.clone()),
-                    overwrite: is_resume,
+                    paused: start_paused,
+                    sequential,
+                    overwrite: true,
                     initial_peers: initial_peers_opt.clone(),
                     ..Default::default()
                 };
@@ -88,8 +100,12 @@ impl TorrentManager {
         let handle = response
             .into_handle()
             .ok_or("Failed to get torrent handle")?;
+        // A handle the session already managed keeps its old piece order otherwise.
+        handle.set_sequential(sequential);
 
         if start_paused {
+            // Only matters for handles the session already managed; new ones
+            // were added paused and are still initializing.
             let _ = session.pause(&handle).await;
         } else if is_resume {
             // Restart resume path: ensure handle is actively unpaused.
@@ -133,6 +149,7 @@ impl TorrentManager {
         let selected_indices_for_cleanup = indices;
         let active_torrents = self.active_torrents.clone();
         let paused_downloads = self.paused_downloads.clone();
+        let manager = self.clone();
         let initial_peers_count = initial_peers.len();
         tokio::spawn(async move {
             let mut name_updated = false;
@@ -142,12 +159,14 @@ impl TorrentManager {
             let mut verified_speed_u64 = 0u64;
             let mut smoothed_speed = 0.0f64;
             let mut phase_state = PhaseState::new(is_resume);
-            let completion_handled = false;
+            let mut completion_handled = false;
+            let mut seeding: Option<(std::time::Instant, SeedPolicy)> = None;
             let mut stalled_since: Option<std::time::Instant> = None;
             let mut live_stalled_since: Option<std::time::Instant> = None;
             let mut last_recovery_poke: Option<std::time::Instant> = None;
             let mut last_progress_seen = handle.stats().progress_bytes;
             let mut last_db_flush = std::time::Instant::now();
+            let usage = app.state::<BandwidthMeter>().recorder();
             let mut last_db_bytes = handle.stats().progress_bytes;
             let startup_started_at = std::time::Instant::now();
             let startup_baseline_bytes = if is_resume {
@@ -208,6 +227,8 @@ impl TorrentManager {
                 {
                     let active = active_torrents.lock().await;
                     if !active.contains_key(&id_clone) {
+                        drop(active);
+                        manager.clear_seeding(&id_clone).await;
                         break;
                     }
                 }
@@ -228,6 +249,10 @@ impl TorrentManager {
                     .as_ref()
                     .map(|l| l.snapshot.fetched_bytes)
                     .unwrap_or(startup_baseline_fetched);
+                // Only live network counters count as usage, never bytes re-verified from disk.
+                if let Some(live) = stats.live.as_ref() {
+                    usage.observe(live.snapshot.fetched_bytes);
+                }
 
                 if startup_metadata_at.is_none() && stats.total_bytes > 0 {
                     startup_metadata_at = Some(startup_elapsed);
@@ -247,8 +272,10 @@ impl TorrentManager {
                 {
                     startup_first_byte_at = Some(startup_elapsed);
                 }
-                let is_restore_verifying =
-                    is_resume && stats.live.is_none() && downloaded_now > startup_baseline_bytes;
+                // While initializing, progress_bytes counts checked bytes, not data we have.
+                let is_checking = matches!(stats.state, TorrentStatsState::Initializing);
+                let is_restore_verifying = is_checking
+                    || is_resume && stats.live.is_none() && downloaded_now > startup_baseline_bytes;
                 let display_downloaded = if is_restore_verifying {
                     startup_baseline_bytes
                 } else {
@@ -353,26 +380,25 @@ impl TorrentManager {
                         );
 
                         tokio::task::spawn_blocking(move || {
-                            if let Ok(conn) = crate::db::open_db(db_p) {
-                                let _ = conn.execute(
-                                    "UPDATE downloads SET info_hash = ?1 WHERE id = ?2",
-                                    (info_hash_hex, id_p),
-                                );
-                            }
+                            let _ =
+                                crate::db::update_download_info_hash(db_p, &id_p, &info_hash_hex);
                         });
 
                         name_updated = true;
                     }
                 }
 
-                if !stats.finished {
+                let ratio = share_ratio(stats.uploaded_bytes, stats.total_bytes);
+
+                if !stats.finished && !completion_handled {
                     let is_cached_paused = {
                         let paused = paused_downloads.lock().await;
                         paused.contains(&id_clone)
                     };
-                    let is_verifying = stats.live.is_none()
-                        && startup_first_byte_at.is_none()
-                        && stats.progress_bytes > startup_baseline_bytes;
+                    let is_verifying = is_checking
+                        || stats.live.is_none()
+                            && startup_first_byte_at.is_none()
+                            && stats.progress_bytes > startup_baseline_bytes;
                     let bytes_delta = stats.progress_bytes.saturating_sub(last_db_bytes);
                     let should_flush_db = !is_verifying
                         && (stats.total_bytes > 0 && stats.progress_bytes >= stats.total_bytes
@@ -496,6 +522,7 @@ impl TorrentManager {
                         total_bytes: stats.total_bytes,
                         progress_bytes: stats.progress_bytes,
                         has_live: stats.live.is_some(),
+                        is_checking,
                         connections,
                         is_cached_paused,
                         is_resume,
@@ -524,6 +551,14 @@ impl TorrentManager {
                         );
                     }
 
+                    // Only sequential torrents fill files from the start, so only
+                    // they report how much is playable.
+                    let playable = if handle.is_sequential() {
+                        sequential::playable_progress(&handle)
+                    } else {
+                        None
+                    };
+
                     let _ = app.emit(
                         "download-progress",
                         serde_json::json!({
@@ -535,9 +570,38 @@ impl TorrentManager {
                             "speed": speed_u64,
                             "eta": eta,
                             "connections": connections,
+                            "uploaded": stats.uploaded_bytes,
+                            "ratio": ratio,
                             "status_text": phase_update.status_text,
                             "status_phase": phase_update.phase_key,
                             "phase_elapsed_secs": phase_update.phase_elapsed_secs,
+                            "playable_file": playable.map(|(file, _)| file),
+                            "playable_bytes": playable.map(|(_, bytes)| bytes),
+                        }),
+                    );
+                } else if let Some((seeding_started, _)) = seeding.as_ref() {
+                    let upload_speed = stats
+                        .live
+                        .as_ref()
+                        .map(|l| (l.upload_speed.mbps.max(0.0) * 1024.0 * 1024.0) as u64)
+                        .unwrap_or(0);
+                    let _ = app.emit(
+                        "download-progress",
+                        serde_json::json!({
+                            "id": id_clone,
+                            "total": stats.total_bytes,
+                            "downloaded": stats.total_bytes,
+                            "network_received": stats.total_bytes,
+                            "verified_speed": 0u64,
+                            "speed": 0u64,
+                            "eta": 0u64,
+                            "connections": connections,
+                            "uploaded": stats.uploaded_bytes,
+                            "upload_speed": upload_speed,
+                            "ratio": ratio,
+                            "status_text": "Seeding",
+                            "status_phase": "seeding",
+                            "phase_elapsed_secs": seeding_started.elapsed().as_secs(),
                         }),
                     );
                 }
@@ -547,7 +611,78 @@ impl TorrentManager {
                     && stats.total_bytes > 0
                     && stats.progress_bytes >= stats.total_bytes;
 
-                if (complete_by_stats || complete_by_bytes) && !completion_handled {
+                // Seeding ends at the ratio/time limit or when switched off for this torrent.
+                let seeding_finished = match seeding.as_ref() {
+                    Some((seeding_started, policy)) => {
+                        policy.reached(ratio, seeding_started.elapsed())
+                            || !manager.wants_seeding(&id_clone, &db_path_clone).await
+                    }
+                    None => false,
+                };
+
+                if ((complete_by_stats || complete_by_bytes) && !completion_handled)
+                    || seeding_finished
+                {
+                    if !completion_handled {
+                        // 1. Update status to Completed in DB (Block until done to prevent race with frontend)
+                        let db_p = db_path_clone.clone();
+                        let id_p = id_clone.clone();
+                        let total_bytes_final = stats.total_bytes; // Capture explicit current size
+                        let _ = tokio::task::spawn_blocking(move || {
+                            if let Err(e) = crate::db::mark_download_completed(&db_p, &id_p) {
+                                tracing::error!(
+                                    "CRITICAL DB ERROR: Failed to mark as completed: {}",
+                                    e
+                                );
+                            }
+
+                            // Also ensure progress is capped at 100%
+                            let _ = crate::db::update_download_progress(
+                                &db_p,
+                                &id_p,
+                                total_bytes_final as i64,
+                                0,
+                            );
+                        })
+                        .await;
+
+                        // 2. Emit completion event only AFTER DB is updated
+                        crate::commands::emit_download_update(&app, &db_path_clone, &id_clone);
+                        let _ = app.emit("download-completed", id_clone.clone());
+                        completion_handled = true;
+
+                        // Seeding keeps the torrent in the session but frees its queue slot.
+                        if manager.wants_seeding(&id_clone, &db_path_clone).await {
+                            manager.mark_seeding(&id_clone).await;
+                            let _ = app.emit("queue-slot-freed", id_clone.clone());
+                            tracing::info!(
+                                "[Torrent][Seed][{}] Download complete; seeding",
+                                id_clone
+                            );
+                            seeding = Some((now, SeedPolicy::from_settings(&db_path_clone)));
+                            run_post_download_actions(&app, &db_path_clone, &id_clone).await;
+                            tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+                            continue;
+                        }
+                    } else if let Some((seeding_started, _)) = seeding.as_ref() {
+                        if let Err(e) = session_for_monitor.pause(&handle).await {
+                            tracing::error!("[Torrent] Failed to stop seeding {}: {}", id_clone, e);
+                        }
+                        let detail = format!(
+                            "ratio={:.2}, uploaded={}, seeded_secs={}",
+                            ratio,
+                            stats.uploaded_bytes,
+                            seeding_started.elapsed().as_secs()
+                        );
+                        tracing::info!("[Torrent][Seed][{}] Stopped: {}", id_clone, detail);
+                        let _ = crate::db::log_event(
+                            &db_path_clone,
+                            &id_clone,
+                            "seeding_complete",
+                            Some(detail.as_str()),
+                        );
+                    }
+
                     let file_entries_for_cleanup = if selected_indices_for_cleanup.is_some() {
                         handle
                             .with_metadata(|m| {
@@ -564,31 +699,6 @@ impl TorrentManager {
                         None
                     };
 
-                    // 1. Update status to Completed in DB (Block until done to prevent race with frontend)
-                    let db_p = db_path_clone.clone();
-                    let id_p = id_clone.clone();
-                    let total_bytes_final = stats.total_bytes; // Capture explicit current size
-                    let _ = tokio::task::spawn_blocking(move || {
-                        if let Err(e) = crate::db::mark_download_completed(&db_p, &id_p) {
-                            tracing::error!(
-                                "CRITICAL DB ERROR: Failed to mark as completed: {}",
-                                e
-                            );
-                        }
-
-                        // Also ensure progress is capped at 100%
-                        let _ = crate::db::update_download_progress(
-                            &db_p,
-                            &id_p,
-                            total_bytes_final as i64,
-                            0,
-                        );
-                    })
-                    .await;
-
-                    // 2. Emit completion event only AFTER DB is updated
-                    let _ = app.emit("download-completed", id_clone.clone());
-
                     // 3. Remove the torrent from in-memory/session state to release file handles.
                     {
                         let mut active = active_torrents.lock().await;
@@ -598,6 +708,8 @@ impl TorrentManager {
                         let mut paused = paused_downloads.lock().await;
                         paused.remove(&id_clone);
                     }
+                    manager.clear_seeding(&id_clone).await;
+                    let _ = app.emit("queue-slot-freed", id_clone.clone());
                     let info_hash = handle.info_hash();
                     if let Err(e) = session_for_monitor
                         .delete(librqbit::api::TorrentIdOrHash::Hash(info_hash), false)
@@ -639,27 +751,34 @@ impl TorrentManager {
                         }
                     }
 
-                    // completion_handled = true; // Unused as we break immediately
-
-                    // 5. Post-Download Actions
-                    // We need the full Download record to know the filepath
-                    if let Ok(downloads) = crate::db::get_all_downloads(&db_path_clone) {
-                        if let Some(download) = downloads.into_iter().find(|d| d.id == id_clone) {
-                            crate::commands::execute_post_download_actions(
-                                app.clone(),
-                                db_path_clone.clone(),
-                                download,
-                            )
-                            .await;
-                        }
+                    // 5. Post-Download Actions (already run when seeding started)
+                    if seeding.is_none() {
+                        run_post_download_actions(&app, &db_path_clone, &id_clone).await;
                     }
                     break;
                 }
 
-                tokio::time::sleep(std::time::Duration::from_millis(300)).await;
+                // Seeding only needs to watch the ratio/time limits.
+                let tick = if seeding.is_some() { 1000 } else { 300 };
+                tokio::time::sleep(std::time::Duration::from_millis(tick)).await;
             }
         });
 
         Ok(())
     }
 }
+
+/// Runs the user's post-download actions for a finished torrent.
+async fn run_post_download_actions<R: Runtime>(app: &AppHandle<R>, db_path: &str, id: &str) {
+    // We need the full Download record to know the filepath
+    if let Ok(downloads) = crate::db::get_all_downloads(db_path) {
+        if let Some(download) = downloads.into_iter().find(|d| d.id == id) {
+            crate::commands::execute_post_download_actions(
+                app.clone(),
+                db_path.to_string(),
+                download,
+            )
+            .await;
+        }
+    }
+}
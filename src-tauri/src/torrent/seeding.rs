@@ -0,0 +1,85 @@
+//! Seeding after completion: the per-torrent `set_seeding` choice and the
+//! `seed_ratio_limit` / `seed_time_limit` stop conditions.
+
+use super::TorrentManager;
+use std::time::Duration;
+
+/// Stop conditions for one seeding torrent, read from settings when it completes.
+pub(super) struct SeedPolicy {
+    /// Uploaded/size ratio at which seeding stops (0 = no limit).
+    ratio_limit: f64,
+    /// How long to seed before stopping (`None` = no limit).
+    time_limit: Option<Duration>,
+}
+
+impl SeedPolicy {
+    /// Reads `seed_ratio_limit` and `seed_time_limit` (minutes); 0 disables either.
+    pub(super) fn from_settings(db_path: &str) -> Self {
+        let setting = |key: &str| {
+            crate::db::get_setting(db_path, key)
+                .ok()
+                .flatten()
+                .and_then(|v| v.trim().parse::<f64>().ok())
+                .filter(|v| v.is_finite() && *v > 0.0)
+        };
+        Self {
+            ratio_limit: setting("seed_ratio_limit").unwrap_or(0.0),
+            time_limit: setting("seed_time_limit").map(|m| Duration::from_secs_f64(m * 60.0)),
+        }
+    }
+
+    /// Whether either limit has been reached.
+    pub(super) fn reached(&self, ratio: f64, seeded_for: Duration) -> bool {
+        (self.ratio_limit > 0.0 && ratio >= self.ratio_limit)
+            || self.time_limit.is_some_and(|limit| seeded_for >= limit)
+    }
+}
+
+/// Uploaded/size ratio; 0 until the size is known.
+pub(super) fn share_ratio(uploaded: u64, total: u64) -> f64 {
+    if total == 0 {
+        0.0
+    } else {
+        uploaded as f64 / total as f64
+    }
+}
+
+impl TorrentManager {
+    /// Whether `id` should seed: its `set_seeding` choice, else the `seed_enabled` setting.
+    pub(super) async fn wants_seeding(&self, id: &str, db_path: &str) -> bool {
+        if let Some(enabled) = self.seeding_overrides.lock().await.get(id) {
+            return *enabled;
+        }
+        crate::db::get_setting(db_path, "seed_enabled")
+            .ok()
+            .flatten()
+            .map(|v| v == "true")
+            .unwrap_or(false)
+    }
+
+    /// Records a per-torrent seeding choice.
+    ///
+    /// It applies when the torrent completes; turning it off while seeding stops
+    /// the torrent on the monitor's next tick.
+    pub async fn set_seeding(&self, id: &str, enabled: bool) {
+        self.seeding_overrides
+            .lock()
+            .await
+            .insert(id.to_string(), enabled);
+    }
+
+    /// Whether the torrent finished downloading and is still uploading.
+    pub async fn is_seeding(&self, id: &str) -> bool {
+        self.seeding_torrents.lock().await.contains(id)
+    }
+
+    pub(super) async fn mark_seeding(&self, id: &str) {
+        self.seeding_torrents.lock().await.insert(id.to_string());
+    }
+
+    /// Forgets seeding state once the torrent leaves the session.
+    pub(super) async fn clear_seeding(&self, id: &str) {
+        self.seeding_torrents.lock().await.remove(id);
+        self.seeding_overrides.lock().await.remove(id);
+    }
+}
@@ -0,0 +1,51 @@
+//! Sequential mode: pieces are fetched in order so a video can be played while
+//! the torrent downloads, plus how much of it is already playable.
+
+use super::TorrentManager;
+use librqbit::ManagedTorrent;
+
+impl TorrentManager {
+    /// Switches a torrent between in-order and the engine's default piece order.
+    ///
+    /// Applies to a loaded torrent right away and is remembered for this run, so
+    /// a queued or re-added torrent picks it up when it joins the session.
+    pub async fn set_sequential(&self, id: &str, enabled: bool) {
+        {
+            let mut sequential = self.sequential_torrents.lock().await;
+            if enabled {
+                sequential.insert(id.to_string());
+            } else {
+                sequential.remove(id);
+            }
+        }
+        if let Some(handle) = self.active_torrents.lock().await.get(id) {
+            handle.set_sequential(enabled);
+        }
+    }
+
+    pub(super) async fn is_sequential(&self, id: &str) -> bool {
+        self.sequential_torrents.lock().await.contains(id)
+    }
+}
+
+/// The file a player would open and how many bytes of it are downloaded without
+/// a gap from its start.
+///
+/// That is the largest selected file, usually the video next to subtitles or an
+/// `.nfo`. `None` until metadata and the piece map are available.
+pub(super) fn playable_progress(handle: &ManagedTorrent) -> Option<(usize, u64)> {
+    let selected = handle.only_files();
+    let file_id = handle
+        .with_metadata(|m| {
+            m.file_infos
+                .iter()
+                .enumerate()
+                .filter(|(idx, _)| selected.as_ref().is_none_or(|s| s.contains(idx)))
+                .max_by_key(|(_, fi)| fi.len)
+                .map(|(idx, _)| idx)
+        })
+        .ok()
+        .flatten()?;
+    let bytes = handle.contiguous_have_bytes(file_id).ok()?;
+    Some((file_id, bytes))
+}
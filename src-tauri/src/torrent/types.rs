@@ -19,3 +19,27 @@ pub struct TorrentInfo {
     /// Flattened list of all files available in the torrent.
     pub files: Vec<TorrentFile>,
 }
+
+/// A peer connected to a live torrent.
+#[derive(Serialize, Clone)]
+pub struct TorrentPeer {
+    /// `ip:port` of the peer.
+    pub address: String,
+    /// Bytes received from this peer since it connected.
+    pub downloaded: u64,
+    /// Receive rate in bytes/sec since the previous details request (0 on the first one).
+    pub download_speed: u64,
+}
+
+/// Read-only snapshot backing the torrent details panel.
+#[derive(Serialize, Clone)]
+pub struct TorrentDetails {
+    pub info_hash: String,
+    /// Announce URLs known for the torrent. The engine does not report per-tracker status.
+    pub trackers: Vec<String>,
+    /// Connected peers, fastest first. Empty while paused or initializing.
+    pub peers: Vec<TorrentPeer>,
+    /// Bytes uploaded since the torrent was last started.
+    pub uploaded: u64,
+    pub upload_speed: u64,
+}
@@ -4,13 +4,298 @@
 //! It allows the application to remain active and accessible even when
 //! the main window is hidden.
 
-use crate::{scheduler, CrashMarkerState};
+use crate::commands::{self, DownloadManager};
+use crate::db::{self, DownloadStatus};
+use crate::scheduler;
+use crate::torrent::TorrentManager;
+use std::time::{Duration, Instant};
 use tauri::{
-    menu::{Menu, MenuItem, PredefinedMenuItem},
-    tray::{MouseButton, TrayIconBuilder, TrayIconEvent},
-    AppHandle, Manager, Runtime, WebviewUrl, WebviewWindowBuilder,
+    image::Image,
+    menu::{Menu, MenuItem, PredefinedMenuItem, Submenu},
+    tray::{MouseButton, TrayIcon, TrayIconBuilder, TrayIconEvent},
+    window::{ProgressBarState, ProgressBarStatus},
+    AppHandle, Emitter, Manager, Runtime, WebviewUrl, WebviewWindowBuilder,
 };
 
+/// Most downloads listed in the tray's "Downloads" submenu.
+const TRAY_DOWNLOAD_LIMIT: usize = 10;
+
+/// Menu item ID prefixes; the download ID follows the prefix.
+const PAUSE_PREFIX: &str = "dl_pause:";
+const RESUME_PREFIX: &str = "dl_resume:";
+const SHOW_PREFIX: &str = "dl_show:";
+
+/// How long the checkmark stays after the last download completes.
+const DONE_HOLD: Duration = Duration::from_secs(5);
+/// How long the error badge stays after a download fails.
+const ERROR_HOLD: Duration = Duration::from_secs(10);
+
+/// Aggregate transfer state shown by the tray icon.
+#[derive(Clone, Copy, PartialEq)]
+enum TrayState {
+    Idle,
+    Active,
+    Done,
+    Error,
+}
+
+/// Picks the tray icon from the aggregate status on each tick of the update loop.
+struct TrayIndicator {
+    state: TrayState,
+    /// Alternates the two active frames so the badge animates.
+    frame: bool,
+    hold_until: Option<Instant>,
+    was_active: bool,
+    /// Failed downloads seen on the previous tick; `None` until the first one.
+    errors: Option<usize>,
+}
+
+impl TrayIndicator {
+    fn new() -> Self {
+        Self {
+            state: TrayState::Idle,
+            frame: false,
+            hold_until: None,
+            was_active: false,
+            errors: None,
+        }
+    }
+
+    /// Advances the indicator; returns the state to show.
+    fn update(&mut self, active: bool, errors: usize) -> TrayState {
+        let now = Instant::now();
+        // Errors present at startup are old news; only new failures raise the badge.
+        let new_error = self.errors.is_some_and(|previous| errors > previous);
+        self.errors = Some(errors);
+
+        if new_error {
+            self.state = TrayState::Error;
+            self.hold_until = Some(now + ERROR_HOLD);
+        } else if active {
+            if self.state != TrayState::Error {
+                self.state = TrayState::Active;
+                self.hold_until = None;
+            }
+        } else if self.was_active && self.state != TrayState::Error {
+            self.state = TrayState::Done;
+            self.hold_until = Some(now + DONE_HOLD);
+        }
+        self.was_active = active;
+
+        if self.hold_until.is_some_and(|until| now >= until) {
+            self.hold_until = None;
+            self.state = if active {
+                TrayState::Active
+            } else {
+                TrayState::Idle
+            };
+        }
+        self.frame = !self.frame;
+        self.state
+    }
+
+    fn icon<R: Runtime>(&self, app: &AppHandle<R>) -> Option<Image<'static>> {
+        match self.state {
+            TrayState::Idle => app
+                .default_window_icon()
+                .map(|icon| icon.clone().to_owned()),
+            TrayState::Active if self.frame => {
+                Some(tauri::include_image!("./icons/tray/active-1.png"))
+            }
+            TrayState::Active => Some(tauri::include_image!("./icons/tray/active-0.png")),
+            TrayState::Done => Some(tauri::include_image!("./icons/tray/done.png")),
+            TrayState::Error => Some(tauri::include_image!("./icons/tray/error.png")),
+        }
+    }
+}
+
+/// Summary line such as `3 active • 2 paused • 1 error • 4.2 MB/s`.
+///
+/// `active` and `speed` come from the engines; paused and failed downloads are
+/// counted from the database since the engines no longer track them.
+/// Zero paused or error counts are left out.
+fn summary_text(active: usize, speed: u64, downloads: &[db::Download]) -> String {
+    let count = |status: DownloadStatus| downloads.iter().filter(|d| d.status == status).count();
+    let mut parts = vec![format!("{} active", active)];
+    let paused = count(DownloadStatus::Paused);
+    if paused > 0 {
+        parts.push(format!("{} paused", paused));
+    }
+    let errors = count(DownloadStatus::Error);
+    if errors > 0 {
+        parts.push(format!(
+            "{} error{}",
+            errors,
+            if errors == 1 { "" } else { "s" }
+        ));
+    }
+    parts.push(format_speed(speed));
+    parts.join(" • ")
+}
+
+/// Overall progress (0-100) of the running downloads with a known size.
+fn overall_progress(downloads: &[db::Download]) -> Option<u64> {
+    let (done, total) = downloads
+        .iter()
+        .filter(|d| d.status == DownloadStatus::Downloading && d.size > 0)
+        .fold((0u64, 0u64), |(done, total), d| {
+            (
+                done + d.downloaded.clamp(0, d.size) as u64,
+                total + d.size as u64,
+            )
+        });
+    (total > 0).then(|| done * 100 / total)
+}
+
+/// Mirrors the indicator onto the taskbar (Windows) or dock (macOS) progress bar.
+fn update_progress_bar<R: Runtime>(app: &AppHandle<R>, state: TrayState, progress: Option<u64>) {
+    let Some(window) = app.get_webview_window("main") else {
+        return;
+    };
+    let (status, progress) = match (state, progress) {
+        (TrayState::Error, _) => (ProgressBarStatus::Error, progress.or(Some(100))),
+        (TrayState::Active, Some(progress)) => (ProgressBarStatus::Normal, Some(progress)),
+        (TrayState::Active, None) => (ProgressBarStatus::Indeterminate, None),
+        _ => (ProgressBarStatus::None, None),
+    };
+    let _ = window.set_progress_bar(ProgressBarState {
+        status: Some(status),
+        progress,
+    });
+}
+
+/// One row of the tray's download list.
+#[derive(PartialEq)]
+struct TrayDownload {
+    id: String,
+    label: String,
+    paused: bool,
+}
+
+/// Downloading and paused downloads (downloading first), capped at [`TRAY_DOWNLOAD_LIMIT`].
+fn tray_downloads(downloads: &[db::Download]) -> Vec<TrayDownload> {
+    let mut downloads = downloads
+        .iter()
+        .filter(|d| {
+            matches!(
+                d.status,
+                DownloadStatus::Downloading | DownloadStatus::Paused
+            )
+        })
+        .collect::<Vec<_>>();
+    downloads.sort_by_key(|d| d.status != DownloadStatus::Downloading);
+    downloads
+        .into_iter()
+        .take(TRAY_DOWNLOAD_LIMIT)
+        .map(|d| {
+            let percent = if d.size > 0 {
+                (d.downloaded.max(0) * 100 / d.size).min(100)
+            } else {
+                0
+            };
+            TrayDownload {
+                label: format!("{} — {}%", d.filename, percent),
+                paused: d.status == DownloadStatus::Paused,
+                id: d.id.clone(),
+            }
+        })
+        .collect()
+}
+
+fn set_tray_icon<R: Runtime>(tray: &TrayIcon<R>, icon: Option<Image<'static>>) {
+    if let Err(e) = tray.set_icon(icon) {
+        tracing::error!("[Tray] Failed to update icon: {}", e);
+    }
+}
+
+/// Replaces the contents of the "Downloads" submenu.
+fn rebuild_download_menu<R: Runtime>(
+    app: &AppHandle<R>,
+    submenu: &Submenu<R>,
+    downloads: &[TrayDownload],
+) -> tauri::Result<()> {
+    for item in submenu.items()? {
+        submenu.remove(&item)?;
+    }
+    if downloads.is_empty() {
+        let empty = MenuItem::new(app, "No active downloads", false, None::<&str>)?;
+        return submenu.append(&empty);
+    }
+    for download in downloads {
+        let toggle = if download.paused {
+            MenuItem::with_id(
+                app,
+                format!("{}{}", RESUME_PREFIX, download.id),
+                "Resume",
+                true,
+                None::<&str>,
+            )?
+        } else {
+            MenuItem::with_id(
+                app,
+                format!("{}{}", PAUSE_PREFIX, download.id),
+                "Pause",
+                true,
+                None::<&str>,
+            )?
+        };
+        let show = MenuItem::with_id(
+            app,
+            format!("{}{}", SHOW_PREFIX, download.id),
+            "Show",
+            true,
+            None::<&str>,
+        )?;
+        let entry = Submenu::with_items(app, &download.label, true, &[&toggle, &show])?;
+        submenu.append(&entry)?;
+    }
+    Ok(())
+}
+
+/// Handles the per-download entries of the "Downloads" submenu.
+fn handle_download_menu_event<R: Runtime>(app: &AppHandle<R>, event_id: &str) {
+    if let Some(id) = event_id.strip_prefix(SHOW_PREFIX) {
+        show_or_create_window(app);
+        // Lets the frontend scroll to and highlight the download.
+        let _ = app.emit("tray-show-download", id.to_string());
+        return;
+    }
+
+    let (id, resume) = if let Some(id) = event_id.strip_prefix(PAUSE_PREFIX) {
+        (id.to_string(), false)
+    } else if let Some(id) = event_id.strip_prefix(RESUME_PREFIX) {
+        (id.to_string(), true)
+    } else {
+        return;
+    };
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let result = if resume {
+            commands::resume_download(
+                app.clone(),
+                app.state::<db::DbState>(),
+                app.state::<DownloadManager>(),
+                app.state::<TorrentManager>(),
+                id.clone(),
+                None,
+            )
+            .await
+        } else {
+            commands::pause_download(
+                app.clone(),
+                app.state::<db::DbState>(),
+                app.state::<DownloadManager>(),
+                app.state::<TorrentManager>(),
+                id.clone(),
+            )
+            .await
+        };
+        if let Err(e) = result {
+            tracing::error!("[Tray] Failed to toggle {}: {}", id, e);
+        }
+    });
+}
+
 /// Helper function to show or recreate the main window.
 /// If the window was destroyed to save RAM, this recreates it.
 pub fn show_or_create_window<R: Runtime>(app: &AppHandle<R>) {
@@ -50,28 +335,39 @@ pub fn show_or_create_window<R: Runtime>(app: &AppHandle<R>) {
 /// Bootstraps the system tray icon, context menu, and event handlers.
 ///
 /// The tray includes:
+/// - "Downloads": Up to [`TRAY_DOWNLOAD_LIMIT`] active downloads with their
+///   progress, each with Pause/Resume and Show.
 /// - "Show Ciel": Restores and focuses the main window.
+/// - "Open Logs": Opens the log folder in the file manager.
 /// - "Quit": Completely exits the application.
 /// - Left-click handler: Conveniently toggles window visibility.
+///
+/// The icon itself carries a badge while downloads run, after they all finish,
+/// and when one fails.
 pub fn create_tray<R: Runtime>(app: &AppHandle<R>) -> tauri::Result<()> {
     // Define context menu items
-    let summary_i = MenuItem::with_id(app, "summary", "📥 0 Active • 0 B/s", false, None::<&str>)?;
+    let summary_i = MenuItem::with_id(app, "summary", "📥 0 active • 0 B/s", false, None::<&str>)?;
+    let downloads_i = Submenu::with_id(app, "downloads", "Downloads", true)?;
+    rebuild_download_menu(app, &downloads_i, &[])?;
     let sep1 = PredefinedMenuItem::separator(app)?;
     let pause_all_i = MenuItem::with_id(app, "pause_all", "Pause All", true, None::<&str>)?;
     let resume_all_i = MenuItem::with_id(app, "resume_all", "Resume All", true, None::<&str>)?;
     let sep2 = PredefinedMenuItem::separator(app)?;
     let show_i = MenuItem::with_id(app, "show", "Show Ciel", true, None::<&str>)?;
+    let logs_i = MenuItem::with_id(app, "open_logs", "Open Logs", true, None::<&str>)?;
     let quit_i = MenuItem::with_id(app, "quit", "Quit", true, None::<&str>)?;
 
     let menu = Menu::with_items(
         app,
         &[
             &summary_i,
+            &downloads_i,
             &sep1,
             &pause_all_i,
             &resume_all_i,
             &sep2,
             &show_i,
+            &logs_i,
             &quit_i,
         ],
     )?;
@@ -79,13 +375,16 @@ pub fn create_tray<R: Runtime>(app: &AppHandle<R>) -> tauri::Result<()> {
     // Background loop to update the tray summary in real-time
     let app_handle = app.clone();
     let summary_clone = summary_i.clone();
+    let downloads_clone = downloads_i.clone();
 
     tauri::async_runtime::spawn(async move {
+        let mut listed: Vec<TrayDownload> = Vec::new();
+        let mut indicator = TrayIndicator::new();
         loop {
             tokio::time::sleep(std::time::Duration::from_secs(2)).await;
 
-            let manager = app_handle.state::<crate::commands::DownloadManager>();
-            let torrent_manager = app_handle.state::<crate::torrent::TorrentManager>();
+            let manager = app_handle.state::<DownloadManager>();
+            let torrent_manager = app_handle.state::<TorrentManager>();
 
             let (h_count, h_speed) = manager.get_global_status().await;
             let (t_count, t_speed) = torrent_manager.get_global_status().await;
@@ -93,10 +392,37 @@ pub fn create_tray<R: Runtime>(app: &AppHandle<R>) -> tauri::Result<()> {
             let total_count = h_count + t_count;
             let total_speed = h_speed + t_speed;
 
-            let speed_text = format_speed(total_speed);
-            let text = format!("📥 {} Active • {}", total_count, speed_text);
+            let db_path = app_handle.state::<db::DbState>().path.clone();
+            let all_downloads = db::get_all_downloads(&db_path).unwrap_or_default();
 
-            let _ = summary_clone.set_text(text);
+            let summary = summary_text(total_count, total_speed, &all_downloads);
+            let _ = summary_clone.set_text(format!("📥 {}", summary));
+            if let Some(tray) = app_handle.tray_by_id("main") {
+                let _ = tray.set_tooltip(Some(format!("Ciel • {}", summary)));
+            }
+
+            // Only rebuild the list when something changed to avoid menu flicker.
+            let downloads = tray_downloads(&all_downloads);
+            if downloads != listed {
+                if let Err(e) = rebuild_download_menu(&app_handle, &downloads_clone, &downloads) {
+                    tracing::error!("[Tray] Failed to update download list: {}", e);
+                }
+                listed = downloads;
+            }
+
+            let errors = all_downloads
+                .iter()
+                .filter(|d| d.status == DownloadStatus::Error)
+                .count();
+            let previous = indicator.state;
+            let state = indicator.update(total_count > 0, errors);
+            // Only the active badge animates; other states are set once.
+            if state != previous || state == TrayState::Active {
+                if let Some(tray) = app_handle.tray_by_id("main") {
+                    set_tray_icon(&tray, indicator.icon(&app_handle));
+                }
+            }
+            update_progress_bar(&app_handle, state, overall_progress(&all_downloads));
         }
     });
 
@@ -108,14 +434,12 @@ pub fn create_tray<R: Runtime>(app: &AppHandle<R>) -> tauri::Result<()> {
         .on_menu_event(move |app, event| {
             let app_handle = app.clone();
             match event.id.as_ref() {
-                "quit" => {
-                    let marker = app.state::<CrashMarkerState>();
-                    marker.clear();
-                    app.exit(0)
-                }
+                // The exit is held until transfers persist; see `shutdown`.
+                "quit" => app.exit(0),
                 "show" => {
                     show_or_create_window(app);
                 }
+                "open_logs" => crate::logging::open_log_dir(app),
                 "pause_all" => {
                     tauri::async_runtime::spawn(async move {
                         scheduler::pause_all_downloads(&app_handle).await;
@@ -126,7 +450,7 @@ pub fn create_tray<R: Runtime>(app: &AppHandle<R>) -> tauri::Result<()> {
                         scheduler::resume_all_downloads(&app_handle).await;
                     });
                 }
-                _ => {}
+                other => handle_download_menu_event(app, other),
             }
         })
         .on_tray_icon_event(|tray, event| match event {
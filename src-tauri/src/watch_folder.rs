@@ -0,0 +1,192 @@
+//! Watched Folder Module
+//!
+//! Polls a user-chosen folder and imports anything dropped into it:
+//! - `.torrent` files are added as torrents.
+//! - `.magnet` / `.txt` files are read line by line; magnet links become
+//!   torrents and http(s) links become HTTP downloads.
+//!
+//! Processed files are moved into a `processed/` subfolder so they are only
+//! picked up once. Each import emits `download-added` so the UI can refresh.
+
+use crate::commands::{self, DownloadManager};
+use crate::db;
+use crate::torrent::TorrentManager;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager, Runtime};
+
+const PROCESSED_DIR: &str = "processed";
+
+/// Starts a background loop that scans the watch folder every 10 seconds.
+///
+/// Gated behind the `watch_folder_enabled` setting; `watch_folder` holds the path.
+pub fn start_folder_watcher<R: Runtime>(app: AppHandle<R>) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(Duration::from_secs(10)).await;
+
+            let db_state = app.state::<db::DbState>();
+            let settings = db::get_all_settings(&db_state.path).unwrap_or_default();
+
+            let enabled = settings
+                .get("watch_folder_enabled")
+                .map(|v| v == "true")
+                .unwrap_or(false);
+            let folder = settings.get("watch_folder").cloned().unwrap_or_default();
+            if !enabled || folder.trim().is_empty() {
+                continue;
+            }
+
+            let folder = PathBuf::from(folder.trim());
+            let entries = match std::fs::read_dir(&folder) {
+                Ok(entries) => entries,
+                Err(e) => {
+                    tracing::error!("[WatchFolder] Cannot read {}: {}", folder.display(), e);
+                    continue;
+                }
+            };
+
+            let files: Vec<PathBuf> = entries
+                .filter_map(|e| e.ok())
+                .map(|e| e.path())
+                .filter(|p| p.is_file())
+                .collect();
+
+            for file in files {
+                let extension = file
+                    .extension()
+                    .map(|e| e.to_string_lossy().to_lowercase())
+                    .unwrap_or_default();
+                match extension.as_str() {
+                    "torrent" => import_torrent_file(&app, &folder, &file).await,
+                    "magnet" | "txt" => import_link_list(&app, &folder, &file).await,
+                    _ => {}
+                }
+            }
+        }
+    });
+}
+
+/// Moves a processed file into `processed/`, avoiding name clashes.
+fn move_to_processed(folder: &Path, file: &Path) -> Option<PathBuf> {
+    let processed_dir = folder.join(PROCESSED_DIR);
+    if let Err(e) = std::fs::create_dir_all(&processed_dir) {
+        tracing::error!(
+            "[WatchFolder] Cannot create {}: {}",
+            processed_dir.display(),
+            e
+        );
+        return None;
+    }
+
+    let name = file.file_name()?;
+    let mut target = processed_dir.join(name);
+    let mut counter = 1;
+    while target.exists() {
+        let stem = file.file_stem().unwrap_or_default().to_string_lossy();
+        let extension = file.extension().unwrap_or_default().to_string_lossy();
+        target = processed_dir.join(format!("{} ({}).{}", stem, counter, extension));
+        counter += 1;
+    }
+
+    match std::fs::rename(file, &target) {
+        Ok(_) => Some(target),
+        Err(e) => {
+            tracing::error!("[WatchFolder] Cannot move {}: {}", file.display(), e);
+            None
+        }
+    }
+}
+
+async fn import_torrent_file<R: Runtime>(app: &AppHandle<R>, folder: &Path, file: &Path) {
+    let filename = file
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_else(|| "torrent".to_string());
+
+    // The engine reads the .torrent when the download starts, so point it at the
+    // file's final location rather than the one we are about to vacate.
+    let Some(processed) = move_to_processed(folder, file) else {
+        return;
+    };
+    add_torrent(app, processed.to_string_lossy().to_string(), filename).await;
+}
+
+async fn import_link_list<R: Runtime>(app: &AppHandle<R>, folder: &Path, file: &Path) {
+    let contents = match std::fs::read_to_string(file) {
+        Ok(c) => c,
+        Err(e) => {
+            tracing::error!("[WatchFolder] Cannot read {}: {}", file.display(), e);
+            return;
+        }
+    };
+    if move_to_processed(folder, file).is_none() {
+        return;
+    }
+
+    let db_state = app.state::<db::DbState>();
+    for line in contents.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if db::find_download_by_url(&db_state.path, line)
+            .ok()
+            .flatten()
+            .is_some()
+        {
+            tracing::info!("[WatchFolder] Skipping duplicate {}", line);
+            continue;
+        }
+
+        if line.starts_with("magnet:") {
+            add_torrent(app, line.to_string(), "magnet".to_string()).await;
+        } else if line.starts_with("http://") || line.starts_with("https://") {
+            let filename =
+                crate::downloader::extract_filename(line, &reqwest::header::HeaderMap::new());
+            let result = commands::http::create_http_download(
+                app.clone(),
+                app.state::<db::DbState>(),
+                app.state::<DownloadManager>(),
+                app.state::<TorrentManager>(),
+                commands::http::NewHttpDownload {
+                    url: line.to_string(),
+                    filename,
+                    ..Default::default()
+                },
+            )
+            .await;
+            match result {
+                Ok(download) => {
+                    let _ = app.emit("download-added", download.id);
+                }
+                Err(e) => tracing::error!("[WatchFolder] Failed to add {}: {}", line, e),
+            }
+        }
+    }
+}
+
+async fn add_torrent<R: Runtime>(app: &AppHandle<R>, url: String, filename: String) {
+    let result = commands::add_torrent(
+        app.clone(),
+        app.state::<db::DbState>(),
+        app.state::<DownloadManager>(),
+        app.state::<TorrentManager>(),
+        url.clone(),
+        filename,
+        String::new(),
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+    )
+    .await;
+    match result {
+        Ok(download) => {
+            tracing::info!("[WatchFolder] Imported {}", url);
+            let _ = app.emit("download-added", download.id);
+        }
+        Err(e) => tracing::error!("[WatchFolder] Failed to add {}: {}", url, e),
+    }
+}
@@ -1,7 +1,7 @@
 import { useEffect, useRef, useState } from "react";
 import { AlertCircle, Clock, Database as DatabaseIcon, FileDown, Loader2 } from "lucide-react";
 import { invoke } from "@tauri-apps/api/core";
-import { open } from "@tauri-apps/plugin-dialog";
+import { open, save } from "@tauri-apps/plugin-dialog";
 import { AnimatePresence, motion } from "framer-motion";
 import clsx from "clsx";
 import { useSettings } from "../hooks/useSettings";
@@ -88,6 +88,25 @@ export function AddDownloadModal({ onClose, onAdded, initialUrl = "" }: { onClos
         return undefined;
     };
 
+    // With ask_location on, a single file gets a save dialog preset to its detected name.
+    // Returns the chosen path, null if cancelled, or undefined when the setting is off.
+    const getSaveFilePath = async (filename: string) => {
+        try {
+            const settings = await invoke<Record<string, string>>("get_settings");
+            if (settings.ask_location === "true") {
+                const folder = settings.download_path;
+                const separator = folder?.includes("\\") ? "\\" : "/";
+                const selected = await save({
+                    defaultPath: folder ? `${folder.replace(/[\\/]+$/, "")}${separator}${filename}` : filename,
+                });
+                return selected ?? null;
+            }
+        } catch (e) {
+            console.error("Failed to check ask_location setting:", e);
+        }
+        return undefined;
+    };
+
     const handleTorrentFileBrowse = async () => {
         try {
             const selected = await open({
@@ -147,7 +166,7 @@ export function AddDownloadModal({ onClose, onAdded, initialUrl = "" }: { onClos
                     if (analysisRunId.current !== currentRunId) return;
                 }
 
-                if (isTorrentFile || typeInfo.is_magnet) {
+                if (isTorrentFile || typeInfo.is_magnet || typeInfo.url_type === "torrent_file") {
                     setStatus("Reading torrent metadata...");
                     analysisStatusTimers.current = [
                         window.setTimeout(() => {
@@ -168,6 +187,11 @@ export function AddDownloadModal({ onClose, onAdded, initialUrl = "" }: { onClos
                     setStatus("Torrent ready. Select files to continue.");
                     setIsAdding(false);
                 } else {
+                    if (typeInfo.url_type === "video") {
+                        setStatus("Error: This is a video page, not a file. Video downloads are not supported yet.");
+                        setIsAdding(false);
+                        return;
+                    }
                     if (isHtmlResponse(typeInfo)) {
                         setStatus("Error: This server returned a web page instead of the file. It may need login cookies or a browser download link.");
                         setIsAdding(false);
@@ -176,17 +200,18 @@ export function AddDownloadModal({ onClose, onAdded, initialUrl = "" }: { onClos
 
                     if (analysisRunId.current !== currentRunId) return;
                     clearAnalysisStatusTimers();
-                    const output_folder = await getSaveLocation();
-                    if (output_folder === null) {
+                    const filename = typeInfo.hinted_filename || "download";
+                    const save_path = await getSaveFilePath(filename);
+                    if (save_path === null) {
                         setIsAdding(false);
                         return;
                     }
 
                     await invoke("add_download", {
                         url: typeInfo.resolved_url || singleUrl,
-                        filename: typeInfo.hinted_filename || "download",
-                        filepath: "",
-                        outputFolder: output_folder || null,
+                        filename,
+                        filepath: save_path || "",
+                        outputFolder: null,
                         userAgent: userAgent || null,
                         cookies: cookies || null,
                         size: typeInfo.content_length ?? null,
@@ -236,7 +261,7 @@ export function AddDownloadModal({ onClose, onAdded, initialUrl = "" }: { onClos
                 } else {
                     const typeInfo = await invoke<any>("validate_url_type", { url: currentUrl });
 
-                    if (typeInfo.is_magnet) {
+                    if (typeInfo.is_magnet || typeInfo.url_type === "torrent_file") {
                     // For bulk, we bypass interactive selection and download ALL files (indices: null)
                         await invoke("add_torrent", {
                             url: currentUrl,
@@ -246,6 +271,8 @@ export function AddDownloadModal({ onClose, onAdded, initialUrl = "" }: { onClos
                             outputFolder: output_folder || null,
                             startPaused: paused
                         });
+                    } else if (typeInfo.url_type === "video") {
+                        console.error(`Skipped ${currentUrl}: video pages are not supported`);
                     } else if (isHtmlResponse(typeInfo)) {
                         console.error(`Skipped ${currentUrl}: server returned an HTML page instead of a file`);
                     } else {
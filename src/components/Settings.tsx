@@ -326,6 +326,16 @@ export function Settings() {
                             <p className="text-xs text-text-tertiary font-medium">How many different files Ciel will download at once.</p>
                         </div>
 
+                        <SettingItem
+                            label="Torrents Share the Limit"
+                            description="Count torrents toward simultaneous downloads. When off, torrents always start immediately."
+                        >
+                            <SettingToggle
+                                enabled={localSettings.queue_include_torrents}
+                                onToggle={() => handleChange("queue_include_torrents", !localSettings.queue_include_torrents)}
+                            />
+                        </SettingItem>
+
                         <SettingItem
                             label="Auto-Resume Downloads"
                             description="Automatically resume interrupted downloads when app starts."
@@ -361,6 +371,16 @@ export function Settings() {
                             />
                         </SettingItem>
 
+                        <SettingItem
+                            label="Organize on finish"
+                            description="Move completed files into category folders inside the download folder."
+                        >
+                            <SettingToggle
+                                enabled={localSettings.organize_on_finish}
+                                onToggle={() => handleChange("organize_on_finish", !localSettings.organize_on_finish)}
+                            />
+                        </SettingItem>
+
                         <SettingItem
                             label="Shutdown when done"
                             description="Shutdown the PC automatically after all downloads are finished."
@@ -22,6 +22,50 @@ const hydrateDownload = (download: DownloadItem): DownloadItem => ({
             : download.phase_elapsed_secs,
 });
 
+const applyProgress = (download: DownloadItem, progress: ProgressPayload): DownloadItem => {
+    if (download.status === "completed") {
+        // Completed torrents keep reporting upload stats while seeding.
+        if (progress.status_phase !== "seeding") return download;
+        return {
+            ...download,
+            connections: progress.connections,
+            uploaded: progress.uploaded,
+            upload_speed: progress.upload_speed,
+            ratio: progress.ratio,
+            status_text: progress.status_text,
+            status_phase: progress.status_phase,
+        };
+    }
+
+    const total = Math.max(progress.total, 0);
+    const downloaded =
+        total > 0
+            ? Math.min(Math.max(progress.downloaded, 0), total)
+            : Math.max(progress.downloaded, 0);
+    const networkReceivedRaw = progress.network_received ?? progress.downloaded;
+    const networkReceived =
+        total > 0
+            ? Math.min(Math.max(networkReceivedRaw, downloaded), total)
+            : Math.max(networkReceivedRaw, downloaded);
+
+    return {
+        ...download,
+        downloaded,
+        network_received: networkReceived,
+        verified_speed: progress.verified_speed ?? progress.speed,
+        size: total,
+        speed: progress.speed,
+        eta: progress.eta,
+        connections: progress.connections,
+        status: progress.status_text === "Paused" || progress.status_phase === "paused" ? "paused" : "downloading",
+        status_text: progress.status_text,
+        status_phase: progress.status_phase,
+        phase_elapsed_secs: progress.phase_elapsed_secs,
+        playable_file: progress.playable_file,
+        playable_bytes: progress.playable_bytes,
+    };
+};
+
 export function useDownloads() {
     const [downloads, setDownloads] = useState<DownloadItem[]>([]);
     const [autocatchUrl, setAutocatchUrl] = useState("");
@@ -55,8 +99,9 @@ export function useDownloads() {
 
             if (settings.auto_resume === "true" && !hasAutoResumed.current) {
                 hasAutoResumed.current = true;
+                // HTTP/FTP downloads are resumed by the backend at startup.
                 for (const download of downloads) {
-                    if (download.status === "downloading") {
+                    if (download.status === "downloading" && download.protocol === "torrent") {
                         markRestoring(download.id);
                         await invoke("resume_download", { id: download.id }).catch(console.error);
                         await new Promise((resolve) => setTimeout(resolve, 250));
@@ -66,7 +111,9 @@ export function useDownloads() {
 
             if (!hasStartupReconciled.current && settings.auto_resume !== "true") {
                 hasStartupReconciled.current = true;
-                const staleActive = downloads.filter((download) => download.status === "downloading");
+                const staleActive = downloads.filter(
+                    (download) => download.status === "downloading" && download.protocol === "torrent"
+                );
                 for (const download of staleActive) {
                     markRestoring(download.id);
                     await invoke("resume_download", { id: download.id }).catch(console.error);
@@ -83,36 +130,18 @@ export function useDownloads() {
 
         const unlistenProgress = listen<ProgressPayload>("download-progress", (event) => {
             const progress = event.payload;
+            setDownloads((prev) =>
+                prev.map((download) => (download.id === progress.id ? applyProgress(download, progress) : download))
+            );
+        });
+
+        // HTTP/FTP progress arrives batched: the latest update of each running download.
+        const unlistenBatch = listen<ProgressPayload[]>("downloads-progress", (event) => {
+            const updates = new Map(event.payload.map((progress) => [progress.id, progress]));
             setDownloads((prev) =>
                 prev.map((download) => {
-                    if (download.id !== progress.id) return download;
-                    if (download.status === "completed") return download;
-
-                    const total = Math.max(progress.total, 0);
-                    const downloaded =
-                        total > 0
-                            ? Math.min(Math.max(progress.downloaded, 0), total)
-                            : Math.max(progress.downloaded, 0);
-                    const networkReceivedRaw = progress.network_received ?? progress.downloaded;
-                    const networkReceived =
-                        total > 0
-                            ? Math.min(Math.max(networkReceivedRaw, downloaded), total)
-                            : Math.max(networkReceivedRaw, downloaded);
-
-                    return {
-                        ...download,
-                        downloaded,
-                        network_received: networkReceived,
-                        verified_speed: progress.verified_speed ?? progress.speed,
-                        size: total,
-                        speed: progress.speed,
-                        eta: progress.eta,
-                        connections: progress.connections,
-                        status: progress.status_text === "Paused" || progress.status_phase === "paused" ? "paused" : "downloading",
-                        status_text: progress.status_text,
-                        status_phase: progress.status_phase,
-                        phase_elapsed_secs: progress.phase_elapsed_secs,
-                    };
+                    const progress = updates.get(download.id);
+                    return progress ? applyProgress(download, progress) : download;
                 })
             );
         });
@@ -121,6 +150,20 @@ export function useDownloads() {
             refreshDownloads();
         });
 
+        const unlistenAdded = listen<string>("download-added", () => {
+            refreshDownloads();
+        });
+
+        // Full row sent on every status change; new downloads arrive via refreshes.
+        const unlistenUpdated = listen<DownloadItem>("download-updated", (event) => {
+            const updated = event.payload;
+            setDownloads((prev) =>
+                prev.map((download) =>
+                    download.id === updated.id ? hydrateDownload({ ...download, ...updated }) : download
+                )
+            );
+        });
+
         const unlistenName = listen<{ id: string; filename: string }>("download-name-updated", (event) => {
             setDownloads((prev) =>
                 prev.map((download) =>
@@ -131,11 +174,11 @@ export function useDownloads() {
             );
         });
 
-        const unlistenAutocatch = listen<string>("autocatch-url", async (event) => {
+        const unlistenAutocatch = listen<{ url: string; kind: string }>("autocatch-url", async (event) => {
             try {
                 const settings = await invoke<Record<string, string>>("get_settings");
                 if (settings.autocatch_enabled === "true") {
-                    setAutocatchUrl(event.payload);
+                    setAutocatchUrl(event.payload.url);
                 }
             } catch (err) {
                 console.error("Failed to check autocatch setting:", err);
@@ -154,7 +197,10 @@ export function useDownloads() {
 
         return () => {
             unlistenProgress.then((unlisten) => unlisten());
+            unlistenBatch.then((unlisten) => unlisten());
             unlistenCompleted.then((unlisten) => unlisten());
+            unlistenAdded.then((unlisten) => unlisten());
+            unlistenUpdated.then((unlisten) => unlisten());
             unlistenName.then((unlisten) => unlisten());
             unlistenAutocatch.then((unlisten) => unlisten());
             unlistenError.then((unlisten) => unlisten());
@@ -18,8 +18,10 @@ export interface SettingsState {
     scheduler_start_time: string;
     scheduler_pause_time: string;
     auto_organize: boolean;
+    organize_on_finish: boolean;
     cookie_browser: string;
     force_multi_http: boolean;
+    queue_include_torrents: boolean;
 }
 
 const DEFAULT_SETTINGS: SettingsState = {
@@ -39,8 +41,10 @@ const DEFAULT_SETTINGS: SettingsState = {
     scheduler_start_time: "02:00",
     scheduler_pause_time: "08:00",
     auto_organize: false,
+    organize_on_finish: false,
     cookie_browser: "none",
     force_multi_http: false,
+    queue_include_torrents: true,
 };
 
 // Simple global observers to sync multiple hook instances
@@ -70,8 +74,10 @@ export function useSettings() {
                 scheduler_start_time: result.scheduler_start_time || DEFAULT_SETTINGS.scheduler_start_time,
                 scheduler_pause_time: result.scheduler_pause_time || DEFAULT_SETTINGS.scheduler_pause_time,
                 auto_organize: result.auto_organize === "true",
+                organize_on_finish: result.organize_on_finish === "true",
                 cookie_browser: result.cookie_browser || DEFAULT_SETTINGS.cookie_browser,
                 force_multi_http: result.force_multi_http === "true",
+                queue_include_torrents: result.queue_include_torrents !== "false",
             };
             setSettings(newSettings);
             observers.forEach(obs => obs(newSettings));
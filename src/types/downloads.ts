@@ -20,19 +20,35 @@ export interface DownloadItem {
     network_received?: number;
     verified_speed?: number;
     speed: number;
+    instant_speed?: number;
     eta: number;
     connections: number;
-    protocol: "http" | "torrent";
+    protocol: "http" | "torrent" | "ftp";
     status: "downloading" | "paused" | "completed" | "queued" | "error";
     filepath: string;
     status_text?: string;
     status_phase?: string;
     phase_elapsed_secs?: number;
+    playable_file?: number | null;
+    playable_bytes?: number | null;
+    uploaded?: number;
+    upload_speed?: number;
+    ratio?: number;
     error_message?: string | null;
     metadata: string | null;
     user_agent: string | null;
     cookies: string | null;
     category: string;
+    priority: number;
+    speed_limit: number;
+    proxy: string | null;
+    headers: string | null;
+    etag: string | null;
+    last_modified: string | null;
+    mirrors: string | null;
+    checksum: string | null;
+    source_page_url: string | null;
+    username: string | null;
 }
 
 export interface ProgressPayload {
@@ -42,9 +58,33 @@ export interface ProgressPayload {
     network_received?: number;
     verified_speed?: number;
     speed: number;
+    instant_speed?: number;
     eta: number;
     connections: number;
+    uploaded?: number;
+    upload_speed?: number;
+    ratio?: number;
     status_text?: string;
     status_phase?: string;
     phase_elapsed_secs?: number;
+    // Sequential torrents only: the largest selected file and its gap-free bytes from the start.
+    playable_file?: number | null;
+    playable_bytes?: number | null;
+}
+
+export interface ChunkInfo {
+    start: number;
+    end: number;
+    downloaded: number;
+    state: "completed" | "active" | "pending";
+}
+
+/** Returned by `get_chunk_progress` and sent as `download-chunk-progress` when `detailed_progress` is on. */
+export interface ChunkProgress {
+    id: string;
+    connections: number;
+    completed: number;
+    active: number;
+    pending: number;
+    chunks: ChunkInfo[];
 }
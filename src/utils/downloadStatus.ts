@@ -57,6 +57,7 @@ export const getPhaseHint = (phase?: string) => {
         case "restarting":
             return "restarting";
         case "verifying_data":
+        case "checking":
             return "checking files";
         case "finding_peers":
             return "searching peers";
@@ -70,6 +71,10 @@ export const getPhaseHint = (phase?: string) => {
             return "loading metadata";
         case "fallback_single":
             return "single connection";
+        case "stalled":
+            return "stalled";
+        case "throttled":
+            return "server busy";
         default:
             return "";
     }
@@ -112,6 +117,11 @@ export const getTorrentPhaseDisplay = (download: DownloadItem) => {
                 title: "Checking existing files",
                 detail: download.status_text?.replace(/^Verifying local data\.\.\.\s*/i, "") || `Verifying local data${suffix}`,
             };
+        case "checking":
+            return {
+                title: "Checking existing files",
+                detail: download.status_text?.replace(/^Checking\.\.\.\s*/i, "") || `Hashing data already on disk${suffix}`,
+            };
         case "restoring_session":
             return {
                 title: "Restoring torrent",